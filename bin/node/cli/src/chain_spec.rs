@@ -323,6 +323,8 @@ pub fn testnet_genesis(
 		}),
 		pallet_vesting: Some(Default::default()),
 		pallet_ethereum: Some(Default::default()),
+		pallet_did: Some(Default::default()),
+		pallet_username_registry: Some(Default::default()),
 	}
 }
 
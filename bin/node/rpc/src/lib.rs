@@ -32,7 +32,7 @@
 
 use std::sync::Arc;
 
-use node_primitives::{Block, BlockNumber, AccountId, Index, Balance, Hash};
+use node_primitives::{Block, BlockNumber, AccountId, Index, Balance, Hash, Moment};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
 use sc_consensus_epochs::SharedEpochChanges;
@@ -115,6 +115,9 @@ pub fn create_full<C, P, SC, B>(
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_social_treasury_rpc::SocialTreasuryRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash, u32>,
+	C::Api: pallet_did_rpc::DidRuntimeApi<Block, AccountId, BlockNumber, Moment>,
+	C::Api: pallet_username_registry_rpc::UsernameRuntimeApi<Block, AccountId, Balance, BlockNumber>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
@@ -125,6 +128,9 @@ pub fn create_full<C, P, SC, B>(
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_contracts_rpc::{Contracts, ContractsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use pallet_social_treasury_rpc::{SocialTreasury, SocialTreasuryApi};
+	use pallet_did_rpc::{Did, DidApi};
+	use pallet_username_registry_rpc::{UsernameApi, UsernameRegistry};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
@@ -161,6 +167,15 @@ pub fn create_full<C, P, SC, B>(
 	io.extend_with(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
+	io.extend_with(
+		SocialTreasuryApi::to_delegate(SocialTreasury::new(client.clone()))
+	);
+	io.extend_with(
+		DidApi::to_delegate(Did::<_, Block, AccountId, BlockNumber, Moment>::new(client.clone()))
+	);
+	io.extend_with(
+		UsernameApi::to_delegate(UsernameRegistry::new(client.clone()))
+	);
 	io.extend_with(
 		sc_consensus_babe_rpc::BabeApi::to_delegate(
 			BabeRpcHandler::new(
@@ -17,10 +17,16 @@
 
 //! Some configurable implementations as associated type for the substrate runtime.
 
-use node_primitives::Balance;
-use sp_runtime::traits::Convert;
-use frame_support::traits::{OnUnbalanced, Currency};
-use crate::{Balances, Authorship, NegativeImbalance};
+use codec::Encode;
+use node_primitives::{AccountId, Balance};
+use sp_runtime::{DispatchError, traits::{Convert, Hash}};
+use frame_support::traits::{OnUnbalanced, Currency, Filter, Get};
+use pallet_democracy::VoteThreshold;
+use pallet_social_treasury::{EscalateProposal, ProposalIndex, ReferendumIndex};
+use crate::{
+	Balances, Authorship, NegativeImbalance, Call, Democracy, EnactmentPeriod, Runtime,
+	UsernameRegistry,
+};
 
 pub struct Author;
 impl OnUnbalanced<NegativeImbalance> for Author {
@@ -29,6 +35,37 @@ impl OnUnbalanced<NegativeImbalance> for Author {
 	}
 }
 
+/// Escalates a social treasury spend proposal to a democracy referendum. The preimage of the
+/// approval call is noted through democracy by the escalating account, which pays its deposit.
+pub struct EscalateToReferendum;
+impl EscalateProposal<AccountId> for EscalateToReferendum {
+	fn escalate(who: &AccountId, proposal_id: ProposalIndex) -> Result<ReferendumIndex, DispatchError> {
+		let call: Call = pallet_social_treasury::Call::approve_proposal(proposal_id).into();
+		let encoded = call.encode();
+		let proposal_hash = <Runtime as frame_system::Trait>::Hashing::hash(&encoded[..]);
+		Democracy::note_preimage(
+			frame_system::RawOrigin::Signed(who.clone()).into(),
+			encoded,
+		)?;
+
+		Ok(Democracy::internal_start_referendum(
+			proposal_hash,
+			VoteThreshold::SuperMajorityApprove,
+			EnactmentPeriod::get(),
+		))
+	}
+}
+
+/// Lets an account contribute to social treasury funding rounds only if a registrar approved its
+/// primary username, so that matching funds cannot be farmed by splitting contributions between
+/// fresh accounts.
+pub struct ApprovedUsernameHolders;
+impl Filter<AccountId> for ApprovedUsernameHolders {
+	fn filter(who: &AccountId) -> bool {
+		UsernameRegistry::has_approved_username(who)
+	}
+}
+
 /// Struct that handles the conversion of Balance -> `u64`. This is used for staking's election
 /// calculation.
 pub struct CurrencyToVoteHandler;
@@ -82,7 +82,7 @@ pub use pallet_staking::StakerStatus;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
-use impls::{CurrencyToVoteHandler, Author};
+use impls::{CurrencyToVoteHandler, Author, EscalateToReferendum, ApprovedUsernameHolders};
 
 /// Constant values used within the runtime.
 pub mod constants;
@@ -113,10 +113,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// Native version.
@@ -171,7 +171,7 @@ impl frame_system::Trait for Runtime {
 	type Hash = Hash;
 	type Hashing = BlakeTwo256;
 	type AccountId = AccountId;
-	type Lookup = Indices;
+	type Lookup = UsernameRegistry;
 	type Header = generic::Header<BlockNumber, BlakeTwo256>;
 	type Event = Event;
 	type BlockHashCount = BlockHashCount;
@@ -412,7 +412,10 @@ impl pallet_session::Trait for Runtime {
 	type ValidatorIdOf = pallet_staking::StashOf<Self>;
 	type ShouldEndSession = Babe;
 	type NextSessionRotation = Babe;
-	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<
+		Self,
+		pallet_validator_registry::NoteEraMissions<Self, Staking>,
+	>;
 	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = SessionKeys;
 	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
@@ -739,7 +742,7 @@ impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for R
 			.using_encoded(|payload| {
 				C::sign(payload, public)
 			})?;
-		let address = Indices::unlookup(account);
+		let address = UsernameRegistry::unlookup(account);
 		let (call, extra, _) = raw_payload.deconstruct();
 		Some((call, (address, signature.into(), extra)))
 	}
@@ -934,10 +937,30 @@ impl pallet_ethereum::Trait for Runtime {
 	type FindAuthor = EthereumFindAuthor<Babe>;
 }
 
+parameter_types! {
+	pub const MaxStatusListLength: u32 = 16 * 1024;
+	pub const DidDepositBase: Balance = 1 * DOLLARS;
+	pub const DidDepositPerByte: Balance = 1 * CENTS;
+	pub const MaxDidNameLength: u32 = 64;
+	pub const MaxDidValueLength: u32 = 1024;
+	pub const DidUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 4;
+	pub const MaxDidExpiriesPerBlock: u32 = 64;
+}
+
 impl pallet_did::Trait for Runtime {
 	type Event = Event;
 	type Public = MultiSigner;
 	type Signature = Signature;
+	type MaxStatusListLength = MaxStatusListLength;
+	type Currency = Balances;
+	type DepositBase = DidDepositBase;
+	type DepositPerByte = DidDepositPerByte;
+	type MaxNameLength = MaxDidNameLength;
+	type MaxValueLength = MaxDidValueLength;
+	type UnsignedPriority = DidUnsignedPriority;
+	type MaxExpiriesPerBlock = MaxDidExpiriesPerBlock;
+	type Usernames = UsernameRegistry;
+	type WeightInfo = weights::pallet_did::WeightInfo;
 }
 
 impl pallet_fungible::Trait for Runtime {
@@ -967,6 +990,21 @@ impl pallet_mission_tokens::Trait for Runtime {
 	type MaxMissionTokensSupply = MaxMissionTokensSupply;
 }
 
+parameter_types! {
+	pub const MissionRewardRate: Perbill = Perbill::from_percent(100);
+	pub const MissionRewardPerPoint: u128 = 1;
+	pub const MaxEraRewardsPerBlock: u32 = 256;
+	pub const MaxRoundProjects: u32 = 100;
+	pub const MaxRoundContributions: u32 = 10;
+	pub const MaxRoundContributors: u32 = 1_000;
+	pub const MaxBountyMilestones: u32 = 20;
+	pub const MaxActiveChildBounties: u32 = 100;
+	pub const EscalationPeriod: BlockNumber = 14 * DAYS;
+	pub const MissionVotingPeriod: BlockNumber = 7 * DAYS;
+	pub const MissionVoteQuorum: Perbill = Perbill::from_percent(10);
+	pub const MaxMissionVoters: u32 = 256;
+}
+
 impl pallet_social_treasury::Trait for Runtime {
 	type ApproveOrigin = EnsureOneOf<
 		AccountId,
@@ -995,17 +1033,50 @@ impl pallet_social_treasury::Trait for Runtime {
 	type BountyCuratorDeposit = BountyCuratorDeposit;
 	type BountyValueMinimum = BountyValueMinimum;
 	type MaximumReasonLength = MaximumReasonLength;
+	type MaxBountyMilestones = MaxBountyMilestones;
+	type MaxActiveChildBounties = MaxActiveChildBounties;
 	type BurnDestination = ();
+	type MissionRewardCurve = pallet_social_treasury::RewardPointsRate<
+		MissionRewardRate,
+		MissionRewardPerPoint,
+	>;
+	type MaxEraRewardsPerBlock = MaxEraRewardsPerBlock;
+	type MaxRoundProjects = MaxRoundProjects;
+	type MaxRoundContributions = MaxRoundContributions;
+	type MaxRoundContributors = MaxRoundContributors;
+	type ContributorFilter = ApprovedUsernameHolders;
+	type EscalationPeriod = EscalationPeriod;
+	type Escalate = EscalateToReferendum;
+	type MissionVotingPeriod = MissionVotingPeriod;
+	type MissionVoteQuorum = MissionVoteQuorum;
+	type MaxMissionVoters = MaxMissionVoters;
 	type WeightInfo = weights::pallet_social_treasury::WeightInfo;
 }
 
+parameter_types! {
+	pub const MaxNominatorMissions: u32 = 16;
+}
+
 impl pallet_validator_registry::Trait for Runtime {
 	type Event = Event;
+	type MaxNominatorMissions = MaxNominatorMissions;
 }
 
 parameter_types! {
 	pub const MinUsernameLength: u32 = 5;
 	pub const MaxUsernameLength: u32 = 50;
+	pub const MaxUsernamesPerAccount: u32 = 10;
+	pub const UsernameBaseDeposit: Balance = 1 * DOLLARS;
+	pub const ShortUsernameLength: u32 = 8;
+	pub const ShortUsernameDeposit: Balance = 10 * DOLLARS;
+	pub const UsernameRegistrationPeriod: BlockNumber = 365 * DAYS;
+	pub const UsernameGracePeriod: BlockNumber = 30 * DAYS;
+	pub const UsernameReleaseReward: Perbill = Perbill::from_percent(10);
+	pub const MaxSubUsernames: u32 = 100;
+	pub const UsernameEndingPeriod: BlockNumber = 1 * HOURS;
+	pub const UsernameRevealPeriod: BlockNumber = 1 * DAYS;
+	pub const MaxBidsPerAuction: u32 = 100;
+	pub const ApprovedUsernamesOnly: bool = false;
 }
 
 impl pallet_username_registry::Trait for Runtime {
@@ -1013,8 +1084,26 @@ impl pallet_username_registry::Trait for Runtime {
 	type MaxRegistrars = MaxRegistrars;
 	type MinUsernameLength = MinUsernameLength;
 	type MaxUsernameLength = MaxUsernameLength;
+	type MaxUsernamesPerAccount = MaxUsernamesPerAccount;
+	type Currency = Balances;
+	type BaseDeposit = UsernameBaseDeposit;
+	type ShortUsernameLength = ShortUsernameLength;
+	type ShortUsernameDeposit = ShortUsernameDeposit;
+	type RegistrationPeriod = UsernameRegistrationPeriod;
+	type GracePeriod = UsernameGracePeriod;
+	type ReleaseReward = UsernameReleaseReward;
+	type Slashed = Treasury;
+	type MaxSubUsernames = MaxSubUsernames;
 	type ForceOrigin = EnsureRootOrHalfCouncil;
 	type RegistrarOrigin = EnsureRootOrHalfCouncil;
+	type AuctionOrigin = EnsureRootOrHalfCouncil;
+	type Randomness = RandomnessCollectiveFlip;
+	type EndingPeriod = UsernameEndingPeriod;
+	type RevealPeriod = UsernameRevealPeriod;
+	type MaxBidsPerAuction = MaxBidsPerAuction;
+	type AuctionProceeds = Treasury;
+	type AccountLookup = Indices;
+	type ApprovedUsernamesOnly = ApprovedUsernamesOnly;
 	type WeightInfo = weights::pallet_username_registry::WeightInfo;
 }
 
@@ -1058,18 +1147,18 @@ construct_runtime!(
 		Multisig: pallet_multisig::{Module, Call, Storage, Event<T>},
 		Evm: pallet_evm::{Module, Call, Storage, Event<T>},
 		Ethereum: pallet_ethereum::{Module, Call, Storage, Event, Config, ValidateUnsigned},
-		Did: pallet_did::{Module, Call, Storage, Event<T>},
+		Did: pallet_did::{Module, Call, Storage, Config, Event<T>, ValidateUnsigned},
 		Fungible: pallet_fungible::{Module, Call, Storage, Event<T>},
 		Swaps: pallet_swaps::{Module, Call, Storage, Event<T>},
 		MissionTokens: pallet_mission_tokens::{Module, Call, Storage, Event<T>},
 		SocialTreasury: pallet_social_treasury::{Module, Call, Storage, Event<T>},
 		ValidatorRegistry: pallet_validator_registry::{Module, Call, Storage, Event<T>},
-		UsernameRegistry: pallet_username_registry::{Module, Call, Storage, Event<T>},
+		UsernameRegistry: pallet_username_registry::{Module, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
-/// The address format for describing accounts.
-pub type Address = <Indices as StaticLookup>::Source;
+/// The address format for describing accounts, by id, index or username.
+pub type Address = <UsernameRegistry as StaticLookup>::Source;
 /// Block header type as expected by this runtime.
 pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 /// Block type as expected by this runtime.
@@ -1293,6 +1382,87 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_social_treasury_rpc_runtime_api::SocialTreasuryApi<
+		Block,
+		AccountId,
+		Balance,
+		BlockNumber,
+		Hash,
+		u32,
+	> for Runtime {
+		fn pots() -> Vec<pallet_social_treasury_rpc_runtime_api::MissionPot<u32, Balance, BlockNumber>> {
+			SocialTreasury::pots_info()
+		}
+
+		fn proposals(
+			mission_token_id: Option<u32>,
+		) -> Vec<pallet_social_treasury_rpc_runtime_api::ProposalInfo<AccountId, Balance, u32>> {
+			SocialTreasury::proposals_info(mission_token_id)
+		}
+
+		fn tips(
+			mission_token_id: Option<u32>,
+		) -> Vec<pallet_social_treasury_rpc_runtime_api::TipInfo<AccountId, Balance, BlockNumber, Hash, u32>> {
+			SocialTreasury::tips_info(mission_token_id)
+		}
+
+		fn bounties(
+			mission_token_id: Option<u32>,
+		) -> Vec<pallet_social_treasury_rpc_runtime_api::BountyInfo<AccountId, Balance, BlockNumber, u32>> {
+			SocialTreasury::bounties_info(mission_token_id)
+		}
+	}
+
+	impl pallet_username_registry_rpc_runtime_api::UsernameApi<
+		Block,
+		AccountId,
+		Balance,
+		BlockNumber,
+	> for Runtime {
+		fn resolve(
+			username: Vec<u8>,
+		) -> Option<pallet_username_registry_rpc_runtime_api::UsernameInfo<AccountId, Balance, BlockNumber>> {
+			UsernameRegistry::username_info(&username)
+		}
+
+		fn usernames_of(account: AccountId) -> pallet_username_registry_rpc_runtime_api::AccountUsernames {
+			UsernameRegistry::account_usernames(&account)
+		}
+
+		fn validate_username(username: Vec<u8>) -> pallet_username_registry_rpc_runtime_api::UsernameValidity {
+			UsernameRegistry::username_validity(&username)
+		}
+	}
+
+	impl pallet_did_rpc_runtime_api::DidApi<Block, AccountId, BlockNumber, Moment> for Runtime {
+		fn resolve(identity: AccountId) -> pallet_did_rpc_runtime_api::DidRecord<AccountId, BlockNumber, Moment> {
+			Did::did_record(&identity)
+		}
+
+		fn delegates(
+			identity: AccountId,
+			page: u32,
+			page_size: u32,
+		) -> Vec<pallet_did_rpc_runtime_api::DelegateRecord<AccountId, BlockNumber>> {
+			Did::delegates(&identity, page, page_size)
+		}
+
+		fn attributes(
+			identity: AccountId,
+			page: u32,
+			page_size: u32,
+		) -> Vec<pallet_did_rpc_runtime_api::AttributeRecord<BlockNumber, Moment>> {
+			Did::attributes(&identity, page, page_size)
+		}
+
+		fn credential_status(
+			issuer: AccountId,
+			credential: [u8; 32],
+		) -> pallet_did_rpc_runtime_api::CredentialStatus<BlockNumber> {
+			Did::credential_status(&issuer, &credential)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			SessionKeys::generate(seed)
@@ -34,3 +34,4 @@ pub mod pallet_vesting;
 pub mod pallet_elections_phragmen;
 pub mod pallet_social_treasury;
 pub mod pallet_username_registry;
+pub mod pallet_did;
@@ -39,6 +39,29 @@ impl pallet_social_treasury::WeightInfo for WeightInfo {
 			.saturating_add(DbWeight::get().reads(2 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
+	fn escalate_proposal() -> Weight {
+		(60000000 as Weight)
+			.saturating_add(DbWeight::get().reads(7 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn open_mission_vote() -> Weight {
+		(30000000 as Weight)
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn vote_on_proposal() -> Weight {
+		(70000000 as Weight)
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn close_mission_vote(v: u32, ) -> Weight {
+		(40000000 as Weight)
+			.saturating_add((30000000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+			.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(v as Weight)))
+	}
 	fn report_awesome(r: u32, ) -> Weight {
 		(101602000 as Weight)
 			.saturating_add((2000 as Weight).saturating_mul(r as Weight))
@@ -121,6 +144,98 @@ impl pallet_social_treasury::WeightInfo for WeightInfo {
 			.saturating_add(DbWeight::get().reads(1 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
+	fn set_bounty_milestones(m: u32, ) -> Weight {
+		(40000000 as Weight)
+			.saturating_add((1000000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn release_milestone() -> Weight {
+		(90000000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn add_child_bounty(d: u32, ) -> Weight {
+		(100000000 as Weight)
+			.saturating_add((1000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(7 as Weight))
+	}
+	fn propose_child_curator() -> Weight {
+		(40000000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn accept_child_curator() -> Weight {
+		(70000000 as Weight)
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn award_child_bounty() -> Weight {
+		(46000000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn claim_child_bounty() -> Weight {
+		(160000000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(7 as Weight))
+	}
+	fn close_child_bounty() -> Weight {
+		(150000000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(7 as Weight))
+	}
+	fn propose_stream() -> Weight {
+		(79604000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn approve_stream() -> Weight {
+		(19000000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn claim_stream() -> Weight {
+		(105000000 as Weight)
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn cancel_stream_proposed() -> Weight {
+		(61001000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn cancel_stream_active() -> Weight {
+		(157232000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn open_round() -> Weight {
+		(60000000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn register_project() -> Weight {
+		(30000000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn contribute() -> Weight {
+		(80000000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn close_round(p: u32, c: u32, ) -> Weight {
+		(100000000 as Weight)
+			.saturating_add((60000000 as Weight).saturating_mul(p as Weight))
+			.saturating_add((12000000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+			.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(DbWeight::get().writes((11 as Weight).saturating_mul(c as Weight)))
+	}
 	fn on_initialize_proposals(p: u32, ) -> Weight {
 		(119765000 as Weight)
 			.saturating_add((108368000 as Weight).saturating_mul(p as Weight))
@@ -137,4 +252,29 @@ impl pallet_social_treasury::WeightInfo for WeightInfo {
 			.saturating_add(DbWeight::get().writes(2 as Weight))
 			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(b as Weight)))
 	}
+	fn on_initialize_streams(s: u32, ) -> Weight {
+		(112536000 as Weight)
+			.saturating_add((107132000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().reads((3 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(s as Weight)))
+	}
+	fn on_initialize_era_rewards(v: u32, n: u32, ) -> Weight {
+		(20000000 as Weight)
+			.saturating_add((40000000 as Weight).saturating_mul(v as Weight))
+			.saturating_add((10000000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(v as Weight)))
+	}
+	fn on_initialize_era_minting(m: u32, ) -> Weight {
+		(10000000 as Weight)
+			.saturating_add((30000000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+			.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(m as Weight)))
+	}
 }
@@ -30,27 +30,215 @@ impl pallet_username_registry::WeightInfo for WeightInfo {
 			.saturating_add(DbWeight::get().reads(1 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
-	fn register(r: u32, ) -> Weight {
-		(110_679_000 as Weight)
-			.saturating_add((389_000 as Weight).saturating_mul(r as Weight))
+	fn remove_registrar(r: u32, ) -> Weight {
+		(35_000_000 as Weight)
+			.saturating_add((400_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
-	fn unregister() -> Weight {
-		(91_553_000 as Weight)
+	fn set_fee(r: u32, ) -> Weight {
+		(26_590_000 as Weight)
+			.saturating_add((317_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn set_account_id(r: u32, ) -> Weight {
+		(28_066_000 as Weight)
+			.saturating_add((311_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn set_fields(r: u32, ) -> Weight {
+		(26_504_000 as Weight)
+			.saturating_add((315_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
+	fn register(r: u32, l: u32, ) -> Weight {
+		(110_679_000 as Weight)
+			.saturating_add((389_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn request_judgement(r: u32, ) -> Weight {
+		(120_475_000 as Weight)
+			.saturating_add((406_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn cancel_request(r: u32, ) -> Weight {
+		(98_350_000 as Weight)
+			.saturating_add((372_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn unregister(s: u32, ) -> Weight {
+		(91_553_000 as Weight)
+			.saturating_add((4_500_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(s as Weight)))
+	}
 	fn provide_judgement(r: u32, ) -> Weight {
 		(72_869_000 as Weight)
 			.saturating_add((423_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(DbWeight::get().reads(2 as Weight))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
 	}
-	fn kill_username() -> Weight {
+	fn kill_username(s: u32, ) -> Weight {
 		(123_199_000 as Weight)
+			.saturating_add((4_500_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight)))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(s as Weight)))
+	}
+	fn set_primary_username() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn renew() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn release_expired(s: u32, ) -> Weight {
+		(120_000_000 as Weight)
+			.saturating_add((4_500_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(s as Weight)))
+	}
+	fn transfer_username() -> Weight {
+		(130_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(7 as Weight))
+			.saturating_add(DbWeight::get().writes(8 as Weight))
+	}
+	fn list_username() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn unlist_username() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn buy_username() -> Weight {
+		(160_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(9 as Weight))
+			.saturating_add(DbWeight::get().writes(9 as Weight))
+	}
+	fn make_offer() -> Weight {
+		(65_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn withdraw_offer() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn accept_offer() -> Weight {
+		(165_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(9 as Weight))
+			.saturating_add(DbWeight::get().writes(9 as Weight))
+	}
+	fn create_sub_username(s: u32, l: u32, ) -> Weight {
+		(75_000_000 as Weight)
+			.saturating_add((250_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn accept_sub_username(s: u32, ) -> Weight {
+		(80_000_000 as Weight)
+			.saturating_add((250_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(7 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn revoke_sub_username(s: u32, ) -> Weight {
+		(70_000_000 as Weight)
+			.saturating_add((250_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn set_sub_username_config() -> Weight {
+		(35_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn open_auction(l: u32, ) -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn bid() -> Weight {
+		(90_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn commit_bid() -> Weight {
+		(80_000_000 as Weight)
 			.saturating_add(DbWeight::get().reads(3 as Weight))
 			.saturating_add(DbWeight::get().writes(3 as Weight))
-			.saturating_add(DbWeight::get().writes((1 as Weight)))
+	}
+	fn reveal_bid() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn settle_auction(b: u32, l: u32, ) -> Weight {
+		(90_000_000 as Weight)
+			.saturating_add((45_000_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add((3_000_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(l as Weight)))
+	}
+	fn link_did() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn unlink_did() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn reserve_username(l: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn unreserve_username() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn block_pattern() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn unblock_pattern() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn on_initialize_auctions(a: u32, ) -> Weight {
+		(2_000_000 as Weight)
+			.saturating_add((4_000_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
 	}
 }
@@ -120,5 +120,7 @@ pub fn config_endowed(
 		}),
 		pallet_vesting: Some(Default::default()),
 		pallet_ethereum: Some(Default::default()),
+		pallet_did: Some(Default::default()),
+		pallet_username_registry: Some(Default::default()),
 	}
 }
@@ -0,0 +1,121 @@
+//! Runtime API definition for the DID pallet.
+//!
+//! The runtime returns the on-chain state of an identity; building the W3C DID Document from it
+//! is left to the RPC. It also reports the status of credentials anchored by an issuer.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// The type of a verification method key.
+#[derive(Eq, PartialEq, Clone, Copy, Encode, Decode, RuntimeDebug)]
+pub enum VerificationKeyType {
+	/// A Schnorrkel/Ristretto x25519 signing key.
+	Sr25519,
+	/// An Ed25519 signing key.
+	Ed25519,
+	/// A compressed ECDSA secp256k1 signing key.
+	EcdsaSecp256k1,
+	/// An X25519 key agreement key, which cannot sign.
+	X25519,
+}
+
+/// An unexpired verification method of an identity.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct VerificationMethodRecord<BlockNumber> {
+	/// The identifier of the key.
+	pub id: [u8; 32],
+	/// The type of the key.
+	pub key_type: VerificationKeyType,
+	/// The public key.
+	pub public_key: Vec<u8>,
+	/// The block until which the key is valid.
+	pub valid_until: BlockNumber,
+}
+
+/// An unexpired delegate of an identity.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct DelegateRecord<AccountId, BlockNumber> {
+	/// The purpose of the delegate.
+	pub delegate_type: Vec<u8>,
+	/// The delegate account.
+	pub delegate: AccountId,
+	/// The block until which the delegate is valid.
+	pub valid_until: BlockNumber,
+}
+
+/// An unexpired attribute of an identity.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct AttributeRecord<BlockNumber, Moment> {
+	/// The name of the attribute.
+	pub name: Vec<u8>,
+	/// The value of the attribute.
+	pub value: Vec<u8>,
+	/// The block until which the attribute is valid.
+	pub valid_until: BlockNumber,
+	/// When the attribute was created.
+	pub creation: Moment,
+}
+
+/// The on-chain state of an identity.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct DidRecord<AccountId, BlockNumber, Moment> {
+	/// The identity.
+	pub identity: AccountId,
+	/// The owner of the identity.
+	pub owner: AccountId,
+	/// The unexpired verification methods, ordered by key id.
+	pub verification_methods: Vec<VerificationMethodRecord<BlockNumber>>,
+	/// The unexpired delegates, ordered by delegate type.
+	pub delegates: Vec<DelegateRecord<AccountId, BlockNumber>>,
+	/// The unexpired attributes, ordered by name.
+	pub attributes: Vec<AttributeRecord<BlockNumber, Moment>>,
+	/// Who last updated the identity, at which block and time.
+	pub updated: Option<(AccountId, BlockNumber, Moment)>,
+	/// The usernames linked to the identity.
+	pub usernames: Vec<Vec<u8>>,
+}
+
+/// The status of a credential anchored by an issuer.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum CredentialStatus<BlockNumber> {
+	/// The issuer never anchored the credential.
+	Unknown,
+	/// The credential is valid until the given block.
+	Active(BlockNumber),
+	/// The credential expired at the given block.
+	Expired(BlockNumber),
+	/// The credential was revoked, at the given block unless revoked by a status list.
+	Revoked(Option<BlockNumber>),
+}
+
+sp_api::decl_runtime_apis! {
+	/// The API to resolve identities of the DID pallet.
+	pub trait DidApi<AccountId, BlockNumber, Moment> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Moment: Codec,
+	{
+		/// The on-chain state of `identity`.
+		fn resolve(identity: AccountId) -> DidRecord<AccountId, BlockNumber, Moment>;
+
+		/// The page `page` of `page_size` delegates of `identity`, expired ones included.
+		fn delegates(
+			identity: AccountId,
+			page: u32,
+			page_size: u32,
+		) -> Vec<DelegateRecord<AccountId, BlockNumber>>;
+
+		/// The page `page` of `page_size` attributes of `identity`, expired ones included.
+		fn attributes(
+			identity: AccountId,
+			page: u32,
+			page_size: u32,
+		) -> Vec<AttributeRecord<BlockNumber, Moment>>;
+
+		/// The status of `credential` anchored by `issuer`.
+		fn credential_status(issuer: AccountId, credential: [u8; 32]) -> CredentialStatus<BlockNumber>;
+	}
+}
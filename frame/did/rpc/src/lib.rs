@@ -0,0 +1,370 @@
+//! RPC interface for the DID pallet.
+//!
+//! Resolves `did:social:<ss58>` identifiers to W3C DID Core documents. Unexpired keys and
+//! delegates become verification methods, and attributes named as in `ethr-did-resolver` become public
+//! keys (`did/pub/<algorithm>/<purpose>/<encoding>`) or service endpoints (`did/svc/<type>`).
+//! Usernames linked to the identity become `Username` service endpoints.
+//! Verifiers can also check the status of a credential anchored by an issuer DID.
+
+use std::sync::Arc;
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde_json::{json, Value};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use pallet_did_rpc_runtime_api::{CredentialStatus, DidRecord, VerificationKeyType};
+pub use pallet_did_rpc_runtime_api::DidApi as DidRuntimeApi;
+pub use self::gen_client::Client as DidClient;
+
+/// The method prefix of the identifiers resolved.
+pub const DID_PREFIX: &str = "did:social:";
+
+/// The type of the service endpoints listing the usernames linked to an identity.
+pub const USERNAME_SERVICE_TYPE: &str = "Username";
+
+/// Delegate types whose delegates can sign on behalf of the identity. Signer delegates added
+/// before `sigAuth` use the legacy `x25519VerificationKey2018` type.
+const SIGNING_DELEGATE_TYPES: &[&str] = &["sigAuth", "x25519VerificationKey2018"];
+
+#[rpc]
+pub trait DidApi<BlockHash> {
+	/// Resolve a `did:social:<ss58>` identifier to its DID Document and metadata.
+	#[rpc(name = "did_resolve")]
+	fn resolve(&self, did: String, at: Option<BlockHash>) -> Result<Value>;
+
+	/// The status of the credential with the given hash anchored by the issuer DID.
+	#[rpc(name = "did_credentialStatus")]
+	fn credential_status(&self, issuer: String, credential: H256, at: Option<BlockHash>) -> Result<Value>;
+}
+
+/// A struct that implements the [`DidApi`].
+pub struct Did<C, Block, AccountId, BlockNumber, Moment> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<(Block, AccountId, BlockNumber, Moment)>,
+}
+
+impl<C, Block, AccountId, BlockNumber, Moment> Did<C, Block, AccountId, BlockNumber, Moment> {
+	/// Create new `Did` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Did { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The identifier is not a `did:social` identifier.
+	InvalidDid,
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::InvalidDid => 2,
+		}
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber, Moment> DidApi<<Block as BlockT>::Hash>
+	for Did<C, Block, AccountId, BlockNumber, Moment>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: DidRuntimeApi<Block, AccountId, BlockNumber, Moment>,
+	AccountId: Codec + Ss58Codec + Send + Sync + 'static,
+	BlockNumber: Codec + std::fmt::Display + Send + Sync + 'static,
+	Moment: Codec + Into<u64> + Send + Sync + 'static,
+{
+	fn resolve(&self, did: String, at: Option<<Block as BlockT>::Hash>) -> Result<Value> {
+		let identity = identity_of(&did)?;
+
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let record = api.resolve(&at, identity).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(Error::RuntimeError.into()),
+			message: "Unable to resolve DID.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		Ok(resolution(record))
+	}
+
+	fn credential_status(
+		&self,
+		issuer: String,
+		credential: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Value> {
+		let issuer = identity_of(&issuer)?;
+
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		let status = api.credential_status(&at, issuer, credential.into()).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(Error::RuntimeError.into()),
+			message: "Unable to query credential status.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		Ok(match status {
+			CredentialStatus::Unknown => json!({ "status": "unknown" }),
+			CredentialStatus::Active(until) => json!({ "status": "active", "validUntil": until.to_string() }),
+			CredentialStatus::Expired(at) => json!({ "status": "expired", "expiredAt": at.to_string() }),
+			CredentialStatus::Revoked(Some(at)) => json!({ "status": "revoked", "revokedAt": at.to_string() }),
+			CredentialStatus::Revoked(None) => json!({ "status": "revoked" }),
+		})
+	}
+}
+
+/// The identity of a `did:social:<ss58>` identifier.
+fn identity_of<AccountId: Ss58Codec>(did: &str) -> Result<AccountId> {
+	did.strip_prefix(DID_PREFIX)
+		.and_then(|address| AccountId::from_ss58check(address).ok())
+		.ok_or_else(|| RpcError {
+			code: ErrorCode::ServerError(Error::InvalidDid.into()),
+			message: "Invalid DID.".into(),
+			data: Some(format!("Expected {}<ss58 address>", DID_PREFIX).into()),
+		})
+}
+
+/// The `did:social` identifier of `account`.
+pub fn did_of<AccountId: Ss58Codec>(account: &AccountId) -> String {
+	format!("{}{}", DID_PREFIX, account.to_ss58check())
+}
+
+/// Build the DID resolution result, with the DID Document and its metadata, of `record`.
+pub fn resolution<AccountId, BlockNumber, Moment>(
+	record: DidRecord<AccountId, BlockNumber, Moment>,
+) -> Value where
+	AccountId: Ss58Codec,
+	BlockNumber: std::fmt::Display,
+	Moment: Into<u64>,
+{
+	let did = did_of(&record.identity);
+	let controller_key = format!("{}#controller", did);
+
+	let mut verification_methods = vec![json!({
+		"id": controller_key,
+		"type": "Sr25519VerificationKey2020",
+		"controller": did_of(&record.owner),
+		"publicKeyHex": hex::encode(record.owner.as_ref()),
+	})];
+	let mut authentication = vec![json!(controller_key)];
+	let mut assertion_methods = vec![json!(controller_key)];
+	let mut key_agreements = Vec::new();
+	let mut services = Vec::new();
+
+	for method in &record.verification_methods {
+		let id = format!("{}#key-{}", did, hex::encode(&method.id[..8]));
+		let key_type = match method.key_type {
+			VerificationKeyType::Sr25519 => "Sr25519VerificationKey2020",
+			VerificationKeyType::Ed25519 => "Ed25519VerificationKey2018",
+			VerificationKeyType::EcdsaSecp256k1 => "EcdsaSecp256k1VerificationKey2019",
+			VerificationKeyType::X25519 => "X25519KeyAgreementKey2019",
+		};
+
+		verification_methods.push(json!({
+			"id": id,
+			"type": key_type,
+			"controller": did,
+			"publicKeyHex": hex::encode(&method.public_key),
+		}));
+		match method.key_type {
+			VerificationKeyType::X25519 => key_agreements.push(json!(id)),
+			_ => {
+				authentication.push(json!(id));
+				assertion_methods.push(json!(id));
+			},
+		}
+	}
+
+	// Delegates are ordered by type, so that each type forms a group of verification methods.
+	let mut key_index = 0;
+	for delegate in &record.delegates {
+		key_index += 1;
+		let id = format!("{}#delegate-{}", did, key_index);
+		let delegate_type = String::from_utf8_lossy(&delegate.delegate_type);
+
+		verification_methods.push(json!({
+			"id": id,
+			"type": delegate_type,
+			"controller": did,
+			"publicKeyHex": hex::encode(delegate.delegate.as_ref()),
+		}));
+		if SIGNING_DELEGATE_TYPES.contains(&&*delegate_type) {
+			authentication.push(json!(id));
+		}
+		assertion_methods.push(json!(id));
+	}
+
+	let mut service_index = 0;
+	for attribute in &record.attributes {
+		let name = String::from_utf8_lossy(&attribute.name);
+		match name.split('/').collect::<Vec<_>>().as_slice() {
+			["did", "pub", algorithm, purpose, encoding] => {
+				let key_type = match *algorithm {
+					"Secp256k1" => "EcdsaSecp256k1VerificationKey2019",
+					"Ed25519" => "Ed25519VerificationKey2018",
+					"Sr25519" => "Sr25519VerificationKey2020",
+					"X25519" => "X25519KeyAgreementKey2019",
+					_ => continue,
+				};
+				// Hex keys are stored raw, other encodings are stored already encoded.
+				let (property, value) = match *encoding {
+					"hex" => ("publicKeyHex", hex::encode(&attribute.value)),
+					"base64" => ("publicKeyBase64", String::from_utf8_lossy(&attribute.value).into_owned()),
+					"base58" => ("publicKeyBase58", String::from_utf8_lossy(&attribute.value).into_owned()),
+					_ => continue,
+				};
+
+				key_index += 1;
+				let id = format!("{}#delegate-{}", did, key_index);
+				let mut method = json!({ "id": id, "type": key_type, "controller": did });
+				method[property] = json!(value);
+				verification_methods.push(method);
+
+				match *purpose {
+					"sigAuth" => {
+						authentication.push(json!(id));
+						assertion_methods.push(json!(id));
+					},
+					"veriKey" => assertion_methods.push(json!(id)),
+					"enc" => key_agreements.push(json!(id)),
+					_ => {},
+				}
+			},
+			["did", "svc", service_type] => {
+				service_index += 1;
+				services.push(json!({
+					"id": format!("{}#service-{}", did, service_index),
+					"type": service_type,
+					"serviceEndpoint": String::from_utf8_lossy(&attribute.value),
+				}));
+			},
+			_ => {},
+		}
+	}
+
+	for username in &record.usernames {
+		service_index += 1;
+		services.push(json!({
+			"id": format!("{}#service-{}", did, service_index),
+			"type": USERNAME_SERVICE_TYPE,
+			"serviceEndpoint": format!("@{}", String::from_utf8_lossy(username)),
+		}));
+	}
+
+	let mut document = json!({
+		"@context": ["https://www.w3.org/ns/did/v1"],
+		"id": did,
+		"controller": did_of(&record.owner),
+		"verificationMethod": verification_methods,
+		"authentication": authentication,
+		"assertionMethod": assertion_methods,
+	});
+	if !key_agreements.is_empty() {
+		document["keyAgreement"] = json!(key_agreements);
+	}
+	if !services.is_empty() {
+		document["service"] = json!(services);
+	}
+
+	let mut metadata = json!({});
+	if let Some((updated_by, block_number, moment)) = record.updated {
+		let millis: u64 = moment.into();
+		let updated = chrono::NaiveDateTime::from_timestamp(
+			(millis / 1000) as i64,
+			((millis % 1000) * 1_000_000) as u32,
+		);
+		metadata = json!({
+			"updated": updated.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+			"updatedBy": did_of(&updated_by),
+			"versionId": block_number.to_string(),
+		});
+	}
+
+	json!({
+		"didDocument": document,
+		"didDocumentMetadata": metadata,
+		"didResolutionMetadata": { "contentType": "application/did+json" },
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pallet_did_rpc_runtime_api::{AttributeRecord, DelegateRecord, VerificationMethodRecord};
+	use sp_core::crypto::AccountId32;
+
+	#[test]
+	fn resolution_should_build_document() {
+		let alice = AccountId32::from([1u8; 32]);
+		let bob = AccountId32::from([2u8; 32]);
+		let record = DidRecord {
+			identity: alice.clone(),
+			owner: alice.clone(),
+			verification_methods: vec![VerificationMethodRecord {
+				id: [3u8; 32],
+				key_type: VerificationKeyType::X25519,
+				public_key: vec![0xef; 32],
+				valid_until: 10u32,
+			}],
+			delegates: vec![DelegateRecord {
+				delegate_type: b"sigAuth".to_vec(),
+				delegate: bob.clone(),
+				valid_until: 10u32,
+			}],
+			attributes: vec![
+				AttributeRecord {
+					name: b"did/pub/Ed25519/enc/hex".to_vec(),
+					value: vec![0xab, 0xcd],
+					valid_until: 10u32,
+					creation: 0u64,
+				},
+				AttributeRecord {
+					name: b"did/svc/HubService".to_vec(),
+					value: b"https://hubs.example.com".to_vec(),
+					valid_until: 10u32,
+					creation: 0u64,
+				},
+			],
+			updated: Some((alice.clone(), 5u32, 1_600_000_000_000u64)),
+			usernames: vec![b"alice".to_vec()],
+		};
+
+		let result = resolution(record);
+		let did = did_of(&alice);
+		let document = &result["didDocument"];
+
+		assert_eq!(document["id"], json!(did));
+		assert_eq!(document["verificationMethod"].as_array().unwrap().len(), 4);
+		assert_eq!(document["verificationMethod"][1]["type"], json!("X25519KeyAgreementKey2019"));
+		assert_eq!(document["verificationMethod"][2]["type"], json!("sigAuth"));
+		assert_eq!(document["verificationMethod"][3]["publicKeyHex"], json!("abcd"));
+		assert_eq!(
+			document["authentication"],
+			json!([format!("{}#controller", did), format!("{}#delegate-1", did)]),
+		);
+		assert_eq!(
+			document["keyAgreement"],
+			json!([format!("{}#key-0303030303030303", did), format!("{}#delegate-2", did)]),
+		);
+		assert_eq!(document["service"][0]["serviceEndpoint"], json!("https://hubs.example.com"));
+		assert_eq!(document["service"][1]["type"], json!(USERNAME_SERVICE_TYPE));
+		assert_eq!(document["service"][1]["serviceEndpoint"], json!("@alice"));
+		assert_eq!(result["didDocumentMetadata"]["updated"], json!("2020-09-13T12:26:40Z"));
+		assert_eq!(result["didDocumentMetadata"]["versionId"], json!("5"));
+	}
+}
@@ -0,0 +1,98 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2019-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 2.0.0-rc6
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+    fn change_owner() -> Weight {
+        (58_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn add_delegate(t: u32) -> Weight {
+        (50_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn revoke_delegate(t: u32) -> Weight {
+        (44_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn add_attribute(b: u32) -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add((3_000 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(5 as Weight))
+    }
+    fn revoke_attribute(n: u32) -> Weight {
+        (42_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn delete_attribute(n: u32) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn add_key() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn revoke_key() -> Weight {
+        (38_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn rotate_key() -> Weight {
+        (72_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(7 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+    fn execute(b: u32) -> Weight {
+        (96_000_000 as Weight)
+            .saturating_add((4_000 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
+    fn issue_credential() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn revoke_credential() -> Weight {
+        (31_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn publish_status_list(b: u32) -> Weight {
+        (48_000_000 as Weight)
+            .saturating_add((3_000 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+}
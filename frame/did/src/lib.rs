@@ -32,6 +32,8 @@
 //! * **Identity Ownership** By default an identity is owned by itself, meaning whoever controls the account with that key.
 //!     The owner can be updated to a new key pair.
 //! * **Delegate:** A Delegate recives delegated permissions from a DID for a specific purpose.
+//! * **Verification Method:** A typed public key of an identity, independent of any account,
+//!     used to verify signatures or, for X25519 keys, to agree on encryption keys.
 //! * **Attribute:** It is a feature that gives extra information of an identity.
 //! * **Valid Delegate:** The action of obtaining the validity period of the delegate.
 //! * **Valid Attribute:** The action of obtaining the validity period of an attribute.
@@ -42,6 +44,11 @@
 //! * **Add Attribute:** The process of assigning a specific identity attribute or feature.
 //! * **Revoke Attribute:** The process of revoking a specific identity attribute or feature.
 //! * **Delete Attribute:** The process of deleting a specific identity attribute or feature.
+//! * **Deposit:** Delegates and attributes hold a deposit, per byte of their name and value,
+//!     reserved from the identity owner until they are revoked or deleted. Credentials and
+//!     status lists issued by an identity hold a deposit per byte as well.
+//! * **Expiry Queue:** Delegates and attributes given a validity period are queued by the block
+//!     at which they expire, and removed from storage at that block, with their deposits returned.
 //!
 //! ### Goals
 //!
@@ -53,12 +60,21 @@
 //! ### Dispatchable Functions
 //!
 //! * `change_owner` - Transfers an `identity` represented as an `AccountId` from the owner account (`origin`) to a `target` account.
+//!    The deposits held for the identity move to the new owner.
 //! * `add_delegate` - Creates a new delegate with an expiration period and for a specific purpose.
 //! * `revoke_delegate` - Revokes an identity's delegate by setting its expiration to the current block number.
 //! * `add_attribute` - Creates a new attribute/property as part of an identity. Sets its expiration period.
 //! * `revoke_attribute` - Revokes an attribute/property from an identity. Sets its expiration period to the actual block number.
 //! * `delete_attribute` - Removes an attribute/property from an identity. This attribute/property becomes unavailable.
+//! * `add_key` - Adds a typed verification method key to an identity.
+//! * `revoke_key` - Removes a verification method key from an identity.
+//! * `rotate_key` - Replaces a verification method key of an identity with a new one, atomically.
 //! * `execute` - Executes off-chain signed transactions.
+//! * `execute_unsigned` - Executes off-chain signed transactions relayed without a fee-paying account.
+//! * `issue_credential` - Anchors the hash of a verifiable credential issued by an identity, with an
+//!    expiration period and an optional entry in one of its revocation status lists.
+//! * `revoke_credential` - Revokes an anchored credential.
+//! * `publish_status_list` - Publishes a revocation status list of an identity.
 //!
 //! ### Public Functions
 //!
@@ -71,6 +87,17 @@
 //! * `attribute_and_id` - Get the `attribute` and its `hash` identifier.
 //! * `check_signature` - Validates the signer from a signature.
 //! * `valid_signer` - Validates a signature from a valid signer delegate or the owner of an identity.
+//! * `check_key_signature` - Validates a signature made with a verification method key, according
+//!    to its declared type.
+//! * `signing_payload` - Get the payload to sign for an off-chain transaction. It includes the
+//!    identity transaction nonce, the genesis hash and spec name of the chain, and an expiry block,
+//!    so that transactions can be replayed neither on this chain nor on another. A transaction
+//!    naming a verification method key of the identity is verified with that key.
+//! * `did_record` - Get the owner, unexpired delegates and attributes of an identity, used to
+//!    resolve its DID Document.
+//! * `delegates` - Get a page of the delegates of an identity.
+//! * `attributes` - Get a page of the attributes of an identity.
+//! * `credential_status` - Whether a credential anchored by an issuer is active, expired or revoked.
 //!
 //! *
 
@@ -79,13 +106,35 @@
 
 use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, StorageMap,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    ensure,
+    storage::{migration::StorageKeyIterator, IterableStorageDoubleMap},
+    traits::{Currency, ExistenceRequirement, Get, ReservableCurrency},
+    transactional,
+    weights::Weight,
+    Blake2_128Concat, StorageDoubleMap, StorageMap, StorageValue,
 };
-use frame_system::ensure_signed;
-use sp_core::RuntimeDebug;
+use frame_system::{ensure_none, ensure_signed};
+use pallet_did_rpc_runtime_api::{
+    AttributeRecord, CredentialStatus, DelegateRecord, DidRecord, VerificationMethodRecord,
+};
+pub use pallet_did_rpc_runtime_api::VerificationKeyType;
+use sp_core::{ecdsa, ed25519, sr25519, RuntimeDebug};
 use sp_io::hashing::blake2_256;
-use sp_runtime::traits::{IdentifyAccount, Member, Verify};
-use sp_std::{prelude::*, vec::Vec};
+use sp_runtime::{
+    traits::{IdentifyAccount, Member, One, Saturating, Verify, Zero},
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+        ValidTransaction,
+    },
+    DispatchError,
+};
+use sp_std::{
+    convert::{TryFrom, TryInto},
+    prelude::*,
+    vec::Vec,
+};
 
 #[cfg(test)]
 mod mock;
@@ -93,6 +142,24 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+mod default_weights;
+
+pub trait WeightInfo {
+    fn change_owner() -> Weight;
+    fn add_delegate(t: u32) -> Weight;
+    fn revoke_delegate(t: u32) -> Weight;
+    fn add_attribute(b: u32) -> Weight;
+    fn revoke_attribute(n: u32) -> Weight;
+    fn delete_attribute(n: u32) -> Weight;
+    fn add_key() -> Weight;
+    fn revoke_key() -> Weight;
+    fn rotate_key() -> Weight;
+    fn execute(b: u32) -> Weight;
+    fn issue_credential() -> Weight;
+    fn revoke_credential() -> Weight;
+    fn publish_status_list(b: u32) -> Weight;
+}
+
 /// Attributes or properties that make an identity.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, Default, RuntimeDebug)]
 pub struct Attribute<BlockNumber, Moment> {
@@ -114,28 +181,175 @@ pub struct AttributeTransaction<Signature, AccountId> {
     pub validity: u32,
     pub signer: AccountId,
     pub identity: AccountId,
+    /// The transaction nonce of the identity.
+    pub nonce: u64,
+    /// The last block at which the transaction can be executed.
+    pub expiry: u32,
+    /// The verification method key of the identity that signed the transaction, and the raw
+    /// signature made with it. If unset, `signature` must be made by `signer`.
+    pub key_signature: Option<([u8; 32], Vec<u8>)>,
+}
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// A typed public key of an identity.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct VerificationMethod<BlockNumber, Balance> {
+    pub key_type: VerificationKeyType,
+    pub public_key: Vec<u8>,
+    pub validity: BlockNumber,
+    /// The deposit reserved from the identity owner for the key.
+    pub deposit: Balance,
+}
+
+/// A delegate or an attribute of an identity queued for removal once expired.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub enum Expiring<AccountId> {
+    /// The identity, type and account of a delegate.
+    Delegate(AccountId, Vec<u8>, AccountId),
+    /// The identity and id of an attribute.
+    Attribute(AccountId, [u8; 32]),
+}
+
+/// Delegates of this type may sign off-chain transactions on behalf of an identity.
+pub const SIGNER_DELEGATE_TYPE: &[u8] = b"sigAuth";
+
+/// The type signer delegates were added with before `SIGNER_DELEGATE_TYPE`. Delegates of this
+/// type may still sign off-chain transactions until they are replaced.
+#[deprecated(note = "signer delegates should be added with `SIGNER_DELEGATE_TYPE`")]
+pub const LEGACY_SIGNER_DELEGATE_TYPE: &[u8] = b"x25519VerificationKey2018";
+
+/// Delegates of this type may issue and revoke credentials on behalf of an identity.
+pub const CREDENTIAL_DELEGATE_TYPE: &[u8] = b"veriKey";
+
+/// Delegates of this type may link usernames to, and unlink them from, an identity.
+pub const USERNAME_DELEGATE_TYPE: &[u8] = b"nameAuth";
+
+/// A verifiable credential anchored by its issuer.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct Credential<AccountId, BlockNumber, Moment> {
+    /// The issuer owner or delegate that anchored the credential.
+    pub issued_by: AccountId,
+    pub issued: Moment,
+    pub validity: BlockNumber,
+    /// The block at which the credential was revoked, if it was.
+    pub revoked: Option<BlockNumber>,
+    /// The revocation status list of the issuer, and the index within it, of the credential.
+    pub status: Option<(u32, u32)>,
+}
+
+// A value placed in storage that represents the current version of the DID storage. This value
+// is used by the `on_runtime_upgrade` logic to determine whether we run storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    /// Delegates and attributes in maps keyed by tuples.
+    V1_0_0,
+    /// Delegates and attributes in double maps keyed by identity first.
+    V2_0_0,
+    /// Delegates and attributes with a finite validity queued for removal once expired.
+    V3_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
 }
 
 pub trait Trait: frame_system::Trait + pallet_timestamp::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type Public: IdentifyAccount<AccountId = Self::AccountId>;
     type Signature: Verify<Signer = Self::Public> + Member + Decode + Encode;
+    /// The maximum length in bytes of a revocation status list.
+    type MaxStatusListLength: Get<u32>;
+    /// The currency in which delegate and attribute deposits are held.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The base deposit held for a delegate or an attribute.
+    type DepositBase: Get<BalanceOf<Self>>;
+    /// The deposit held per byte of a delegate type, or of an attribute name and value.
+    type DepositPerByte: Get<BalanceOf<Self>>;
+    /// The maximum length of a delegate type or an attribute name.
+    type MaxNameLength: Get<u32>;
+    /// The maximum length of an attribute value.
+    type MaxValueLength: Get<u32>;
+    /// The priority of off-chain signed transactions relayed as unsigned transactions.
+    type UnsignedPriority: Get<TransactionPriority>;
+    /// The maximum number of expired delegates and attributes removed in a block.
+    /// Any more are postponed to the next block.
+    type MaxExpiriesPerBlock: Get<u32>;
+    /// The usernames linked to identities, listed in their DID Documents.
+    type Usernames: IdentityUsernames<Self::AccountId>;
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+/// Something that links human-readable usernames to identities.
+pub trait IdentityUsernames<AccountId> {
+    /// The usernames linked to `identity`.
+    fn usernames_of(identity: &AccountId) -> Vec<Vec<u8>>;
+}
+
+impl<AccountId> IdentityUsernames<AccountId> for () {
+    fn usernames_of(_identity: &AccountId) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as DID {
-        /// Identity delegates stored by type.
+        /// Identity delegates stored by identity, then type and delegate.
         /// Delegates are only valid for a specific period defined as blocks number.
-        pub DelegateOf get(fn delegate_of): map hasher(blake2_128_concat) (T::AccountId, Vec<u8>, T::AccountId) => Option<T::BlockNumber>;
-        /// The attributes that belong to an identity.
+        pub DelegateOf get(fn delegate_of):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) (Vec<u8>, T::AccountId)
+            => Option<T::BlockNumber>;
+        /// The verification methods of an identity, stored by identity, then key id.
+        pub VerificationMethodOf get(fn verification_method_of):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32]
+            => Option<VerificationMethod<T::BlockNumber, BalanceOf<T>>>;
+        /// The attributes that belong to an identity, stored by identity, then attribute id.
         /// Attributes are only valid for a specific period defined as blocks number.
-        pub AttributeOf get(fn attribute_of): map hasher(blake2_128_concat) (T::AccountId, [u8; 32]) => Attribute<T::BlockNumber, T::Moment>;
+        pub AttributeOf get(fn attribute_of):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32]
+            => Attribute<T::BlockNumber, T::Moment>;
         /// Attribute nonce used to generate a unique hash even if the attribute is deleted and recreated.
         pub AttributeNonce get(fn nonce_of): map hasher(twox_64_concat) (T::AccountId, Vec<u8>) => u64;
         /// Identity owner.
         pub OwnerOf get(fn owner_of): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+        /// Delegates and attributes to remove, stored by the block at which they expire.
+        pub ExpiryQueue get(fn expiry_queue):
+            map hasher(twox_64_concat) T::BlockNumber => Vec<Expiring<T::AccountId>>;
+        /// The nonce of the next off-chain signed transaction of an identity.
+        pub TransactionNonce get(fn transaction_nonce): map hasher(blake2_128_concat) T::AccountId => u64;
         /// Tracking the latest identity update.
         pub UpdatedBy get(fn updated_by): map hasher(blake2_128_concat) T::AccountId => (T::AccountId, T::BlockNumber, T::Moment);
+        /// The deposit held for each delegate, stored by identity, then type and delegate.
+        pub DelegateDeposit get(fn delegate_deposit):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) (Vec<u8>, T::AccountId)
+            => BalanceOf<T>;
+        /// The deposit held for each attribute, stored by identity, then attribute id.
+        pub AttributeDeposit get(fn attribute_deposit):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32]
+            => BalanceOf<T>;
+        /// The total deposit reserved from the owner of an identity.
+        pub DepositOf get(fn deposit_of): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Credentials anchored by an issuer, stored by issuer, then credential hash.
+        pub CredentialOf get(fn credential_of):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32]
+            => Option<Credential<T::AccountId, T::BlockNumber, T::Moment>>;
+        /// The deposit held for each credential, stored by issuer, then credential hash.
+        pub CredentialDeposit get(fn credential_deposit):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32]
+            => BalanceOf<T>;
+        /// Revocation status lists published by an issuer, stored by issuer, then list id.
+        /// A list is a bitstring where a set bit revokes the credential at that index.
+        pub StatusListOf get(fn status_list_of):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) u32 => Vec<u8>;
+        /// The deposit held for each status list, stored by issuer, then list id.
+        pub StatusListDeposit get(fn status_list_deposit):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) u32
+            => BalanceOf<T>;
+        /// Storage version of the pallet.
+        StorageVersion build(|_: &GenesisConfig| Releases::V3_0_0): Releases;
     }
 }
 
@@ -144,8 +358,31 @@ decl_module! {
       type Error = Error<T>;
 
       fn deposit_event() = default;
-        /// Transfers ownership of an identity.
-        #[weight = 0]
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::remove_expired(now)
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = 0;
+            if StorageVersion::get() == Releases::V1_0_0 {
+                Self::migrate_to_double_maps();
+                StorageVersion::put(Releases::V2_0_0);
+                weight = T::MaximumBlockWeight::get();
+            }
+            if StorageVersion::get() == Releases::V2_0_0 {
+                Self::migrate_to_expiry_queue();
+                StorageVersion::put(Releases::V3_0_0);
+                weight = T::MaximumBlockWeight::get();
+            }
+            weight
+        }
+
+        /// Transfers ownership of an identity, together with its deposits.
+        /// The deposits are moved as free balance and reserved again on the new owner, so a new
+        /// owner that does not exist yet is created when they reach the existential deposit.
+        #[weight = T::WeightInfo::change_owner()]
+        #[transactional]
         pub fn change_owner(
             origin,
             identity: T::AccountId,
@@ -157,6 +394,15 @@ decl_module! {
             let now_timestamp = <pallet_timestamp::Module<T>>::now();
             let now_block_number = <frame_system::Module<T>>::block_number();
 
+            // Move the whole deposit to the new owner, or nothing at all.
+            let deposit = Self::deposit_of(&identity);
+            if !deposit.is_zero() {
+                let unmoved = T::Currency::unreserve(&who, deposit);
+                ensure!(unmoved.is_zero(), Error::<T>::DepositNotMovable);
+                T::Currency::transfer(&who, &new_owner, deposit, ExistenceRequirement::AllowDeath)?;
+                T::Currency::reserve(&new_owner, deposit)?;
+            }
+
             if <OwnerOf<T>>::contains_key(&identity) {
                 // Update to new owner.
                 <OwnerOf<T>>::mutate(&identity, |o| *o = Some(new_owner.clone()));
@@ -178,7 +424,7 @@ decl_module! {
         }
 
         /// Creates a new delegate with an expiration period and for a specific purpose.
-        #[weight = 0]
+        #[weight = T::WeightInfo::add_delegate(delegate_type.len() as u32)]
         pub fn add_delegate(
             origin,
             identity: T::AccountId,
@@ -187,7 +433,10 @@ decl_module! {
             valid_for: Option<T::BlockNumber>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            ensure!(delegate_type.len() <= 64, Error::<T>::InvalidDelegate);
+            ensure!(
+                delegate_type.len() <= T::MaxNameLength::get() as usize,
+                Error::<T>::InvalidDelegate
+            );
 
             Self::create_delegate( &who, &identity, &delegate, &delegate_type, valid_for)?;
 
@@ -205,7 +454,8 @@ decl_module! {
         }
 
         /// Revokes an identity's delegate by setting its expiration to the current block number.
-        #[weight = 0]
+        /// Its deposit is returned to the owner.
+        #[weight = T::WeightInfo::revoke_delegate(delegate_type.len() as u32)]
         pub fn revoke_delegate(
             origin,
             identity: T::AccountId,
@@ -215,14 +465,23 @@ decl_module! {
             let who = ensure_signed(origin)?;
             Self::is_owner(&identity, &who)?;
             Self::valid_listed_delegate(&identity, &delegate_type, &delegate)?;
-            ensure!(delegate_type.len() <= 64, Error::<T>::InvalidDelegate);
+            ensure!(
+                delegate_type.len() <= T::MaxNameLength::get() as usize,
+                Error::<T>::InvalidDelegate
+            );
 
             let now_timestamp = <pallet_timestamp::Module<T>>::now();
             let now_block_number = <frame_system::Module<T>>::block_number();
 
             // Update only the validity period to revoke the delegate.
             <DelegateOf<T>>::mutate(
-                (&identity, &delegate_type, &delegate), |b| *b = Some(now_block_number),
+                &identity, (&delegate_type, &delegate), |b| *b = Some(now_block_number),
+            );
+            let deposit = <DelegateDeposit<T>>::take(&identity, (&delegate_type, &delegate));
+            Self::unreserve_deposit(&identity, deposit);
+            Self::schedule_expiry(
+                now_block_number,
+                Expiring::Delegate(identity.clone(), delegate_type.clone(), delegate.clone()),
             );
             <UpdatedBy<T>>::insert(&identity, (who, now_block_number, now_timestamp));
             Self::deposit_event(RawEvent::DelegateRevoked(identity, delegate_type, delegate));
@@ -230,8 +489,8 @@ decl_module! {
         }
 
         /// Creates a new attribute as part of an identity.
-        /// Sets its expiration period.
-        #[weight = 0]
+        /// Sets its expiration period and reserves its deposit from the owner.
+        #[weight = T::WeightInfo::add_attribute((name.len() + value.len()) as u32)]
         pub fn add_attribute(
             origin,
             identity: T::AccountId,
@@ -240,7 +499,7 @@ decl_module! {
             valid_for: Option<T::BlockNumber>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            ensure!(name.len() <= 64, Error::<T>::AttributeCreationFailed);
+            ensure!(name.len() <= T::MaxNameLength::get() as usize, Error::<T>::AttributeCreationFailed);
 
             Self::create_attribute(&who, &identity, &name, &value, valid_for)?;
             Self::deposit_event(RawEvent::AttributeAdded(identity, name, valid_for));
@@ -248,11 +507,11 @@ decl_module! {
         }
 
         /// Revokes an attribute/property from an identity.
-        /// Sets its expiration period to the actual block number.
-        #[weight = 0]
+        /// Sets its expiration period to the actual block number and returns its deposit.
+        #[weight = T::WeightInfo::revoke_attribute(name.len() as u32)]
         pub fn revoke_attribute(origin, identity: T::AccountId, name: Vec<u8>) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            ensure!(name.len() <= 64, Error::<T>::AttributeRemovalFailed);
+            ensure!(name.len() <= T::MaxNameLength::get() as usize, Error::<T>::AttributeRemovalFailed);
 
             Self::reset_attribute(who, &identity, &name)?;
             Self::deposit_event(RawEvent::AttributeRevoked(
@@ -264,17 +523,22 @@ decl_module! {
         }
 
         /// Removes an attribute from an identity. This attribute/property becomes unavailable.
-        #[weight = 0]
+        /// Its deposit, unless already returned on revocation, is returned to the owner.
+        #[weight = T::WeightInfo::delete_attribute(name.len() as u32)]
         pub fn delete_attribute(origin, identity: T::AccountId, name: Vec<u8>) -> DispatchResult {
             let who = ensure_signed(origin)?;
             Self::is_owner(&identity, &who)?;
-            ensure!(name.len() <= 64, Error::<T>::AttributeRemovalFailed);
+            ensure!(name.len() <= T::MaxNameLength::get() as usize, Error::<T>::AttributeRemovalFailed);
 
             let now_block_number = <frame_system::Module<T>>::block_number();
             let result = Self::attribute_and_id(&identity, &name);
 
             match result {
-                Some((_, id)) => <AttributeOf<T>>::remove((&identity, &id)),
+                Some((_, id)) => {
+                    <AttributeOf<T>>::remove(&identity, &id);
+                    let deposit = <AttributeDeposit<T>>::take(&identity, &id);
+                    Self::unreserve_deposit(&identity, deposit);
+                },
                 None => return Err(Error::<T>::AttributeRemovalFailed.into()),
             }
 
@@ -287,24 +551,184 @@ decl_module! {
             Ok(())
         }
 
+        /// Adds a typed verification method key to an identity.
+        #[weight = T::WeightInfo::add_key()]
+        pub fn add_key(
+            origin,
+            identity: T::AccountId,
+            key_type: VerificationKeyType,
+            public_key: Vec<u8>,
+            valid_for: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::is_owner(&identity, &who)?;
+
+            let key_id = Self::insert_key(&identity, key_type, public_key, valid_for)?;
+            Self::note_update(&identity, who);
+            Self::deposit_event(RawEvent::KeyAdded(identity, key_id, key_type));
+            Ok(())
+        }
+
+        /// Removes a verification method key from an identity and returns its deposit.
+        #[weight = T::WeightInfo::revoke_key()]
+        pub fn revoke_key(origin, identity: T::AccountId, key_id: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::is_owner(&identity, &who)?;
+
+            Self::remove_key(&identity, &key_id)?;
+            Self::note_update(&identity, who);
+            Self::deposit_event(RawEvent::KeyRevoked(identity, key_id));
+            Ok(())
+        }
+
+        /// Replaces a verification method key of an identity with a new one.
+        /// Either both the old key is removed and the new one added, or nothing changes.
+        #[weight = T::WeightInfo::rotate_key()]
+        pub fn rotate_key(
+            origin,
+            identity: T::AccountId,
+            old_key_id: [u8; 32],
+            key_type: VerificationKeyType,
+            public_key: Vec<u8>,
+            valid_for: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::is_owner(&identity, &who)?;
+            ensure!(
+                <VerificationMethodOf<T>>::contains_key(&identity, &old_key_id),
+                Error::<T>::UnknownKey
+            );
+
+            let key_id = Self::insert_key(&identity, key_type, public_key, valid_for)?;
+            Self::remove_key(&identity, &old_key_id)?;
+            Self::note_update(&identity, who);
+            Self::deposit_event(RawEvent::KeyRotated(identity, old_key_id, key_id, key_type));
+            Ok(())
+        }
+
         /// Executes off-chain signed transaction.
-        #[weight = 0]
+        #[weight = T::WeightInfo::execute((transaction.name.len() + transaction.value.len()) as u32)]
         pub fn execute(
             origin,
             transaction: AttributeTransaction<T::Signature, T::AccountId>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-
-            let mut encoded = transaction.name.encode();
-            encoded.extend(transaction.value.encode());
-            encoded.extend(transaction.validity.encode());
-            encoded.extend(transaction.identity.encode());
+            ensure_signed(origin)?;
 
             // Execute the storage update if the signer is valid.
-            Self::signed_attribute(who, &encoded, &transaction)?;
+            Self::signed_attribute(&transaction)?;
+            Self::deposit_event(RawEvent::AttributeTransactionExecuted(transaction));
+            Ok(())
+        }
+
+        /// Executes off-chain signed transaction relayed as an unsigned transaction,
+        /// so that the relayer pays no fee. Validated by `validate_unsigned`.
+        /// The transaction nonce is consumed even if the transaction fails to apply.
+        #[weight = T::WeightInfo::execute((transaction.name.len() + transaction.value.len()) as u32)]
+        pub fn execute_unsigned(
+            origin,
+            transaction: AttributeTransaction<T::Signature, T::AccountId>,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+
+            Self::signed_attribute(&transaction)?;
             Self::deposit_event(RawEvent::AttributeTransactionExecuted(transaction));
             Ok(())
         }
+
+        /// Anchors the hash of a credential issued by an identity.
+        /// The origin must be the issuer owner or one of its credential delegates.
+        /// A deposit, per byte of the stored credential, is reserved from the issuer owner.
+        #[weight = T::WeightInfo::issue_credential()]
+        pub fn issue_credential(
+            origin,
+            issuer: T::AccountId,
+            credential: [u8; 32],
+            valid_for: Option<T::BlockNumber>,
+            status: Option<(u32, u32)>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::valid_delegate(&issuer, CREDENTIAL_DELEGATE_TYPE, &who)?;
+            ensure!(
+                !<CredentialOf<T>>::contains_key(&issuer, &credential),
+                Error::<T>::CredentialExists
+            );
+
+            let now_block_number = <frame_system::Module<T>>::block_number();
+            let validity: T::BlockNumber = match valid_for {
+                Some(blocks) => now_block_number + blocks,
+                None => u32::max_value().into(),
+            };
+
+            let record = Credential {
+                issued_by: who.clone(),
+                issued: <pallet_timestamp::Module<T>>::now(),
+                validity,
+                revoked: None,
+                status,
+            };
+            let deposit = Self::reserve_deposit(&issuer, record.encoded_size())?;
+
+            <CredentialOf<T>>::insert(&issuer, &credential, record);
+            <CredentialDeposit<T>>::insert(&issuer, &credential, deposit);
+            Self::deposit_event(RawEvent::CredentialIssued(issuer, credential, who, validity));
+            Ok(())
+        }
+
+        /// Revokes a credential anchored by an identity.
+        /// The origin must be the issuer owner or one of its credential delegates.
+        #[weight = T::WeightInfo::revoke_credential()]
+        pub fn revoke_credential(
+            origin,
+            issuer: T::AccountId,
+            credential: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::valid_delegate(&issuer, CREDENTIAL_DELEGATE_TYPE, &who)?;
+
+            let now_block_number = <frame_system::Module<T>>::block_number();
+            <CredentialOf<T>>::try_mutate(&issuer, &credential, |maybe_credential| -> DispatchResult {
+                let credential = maybe_credential.as_mut().ok_or(Error::<T>::UnknownCredential)?;
+                ensure!(credential.revoked.is_none(), Error::<T>::CredentialRevoked);
+                credential.revoked = Some(now_block_number);
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::CredentialRevoked(issuer, credential, now_block_number));
+            Ok(())
+        }
+
+        /// Publishes, or replaces, a revocation status list of an identity.
+        /// An empty list removes it.
+        /// The origin must be the issuer owner or one of its credential delegates.
+        /// A deposit, per byte of the list, is reserved from the issuer owner in place of the
+        /// deposit of the list it replaces.
+        #[weight = T::WeightInfo::publish_status_list(bits.len() as u32)]
+        pub fn publish_status_list(
+            origin,
+            issuer: T::AccountId,
+            list_id: u32,
+            bits: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::valid_delegate(&issuer, CREDENTIAL_DELEGATE_TYPE, &who)?;
+            ensure!(
+                bits.len() <= T::MaxStatusListLength::get() as usize,
+                Error::<T>::StatusListTooLong
+            );
+
+            let previous = Self::status_list_deposit(&issuer, list_id);
+            if bits.is_empty() {
+                <StatusListOf<T>>::remove(&issuer, list_id);
+                <StatusListDeposit<T>>::remove(&issuer, list_id);
+            } else {
+                let deposit = Self::reserve_deposit(&issuer, bits.len())?;
+                <StatusListOf<T>>::insert(&issuer, list_id, bits);
+                <StatusListDeposit<T>>::insert(&issuer, list_id, deposit);
+            }
+            Self::unreserve_deposit(&issuer, previous);
+            Self::deposit_event(RawEvent::StatusListPublished(issuer, list_id, who));
+            Ok(())
+        }
     }
 }
 
@@ -318,10 +742,26 @@ decl_event!(
     OwnerChanged(AccountId, AccountId, AccountId, BlockNumber),
     DelegateAdded(AccountId, Vec<u8>, AccountId, Option<BlockNumber>),
     DelegateRevoked(AccountId, Vec<u8>, AccountId),
+    /// An expired delegate was removed. \[identity, delegate_type, delegate\]
+    DelegateExpired(AccountId, Vec<u8>, AccountId),
     AttributeAdded(AccountId,Vec<u8>, Option<BlockNumber>),
     AttributeRevoked(AccountId,Vec<u8>,BlockNumber),
     AttributeDeleted(AccountId,Vec<u8>,BlockNumber),
+    /// An expired attribute was removed. \[identity, name\]
+    AttributeExpired(AccountId, Vec<u8>),
     AttributeTransactionExecuted(AttributeTransaction<Signature,AccountId>),
+    /// A verification method key was added. \[identity, key_id, key_type\]
+    KeyAdded(AccountId, [u8; 32], VerificationKeyType),
+    /// A verification method key was removed. \[identity, key_id\]
+    KeyRevoked(AccountId, [u8; 32]),
+    /// A verification method key was replaced. \[identity, old_key_id, key_id, key_type\]
+    KeyRotated(AccountId, [u8; 32], [u8; 32], VerificationKeyType),
+    /// A credential was anchored by an issuer. \[issuer, credential, issued_by, valid_until\]
+    CredentialIssued(AccountId, [u8; 32], AccountId, BlockNumber),
+    /// A credential was revoked. \[issuer, credential, block_number\]
+    CredentialRevoked(AccountId, [u8; 32], BlockNumber),
+    /// A revocation status list was published. \[issuer, list_id, published_by\]
+    StatusListPublished(AccountId, u32, AccountId),
   }
 );
 
@@ -336,6 +776,30 @@ decl_error! {
         InvalidAttribute,
         Overflow,
         BadTransaction,
+        /// The credential is already anchored by the issuer.
+        CredentialExists,
+        /// The credential is not anchored by the issuer.
+        UnknownCredential,
+        /// The credential is already revoked.
+        CredentialRevoked,
+        /// The revocation status list is longer than allowed.
+        StatusListTooLong,
+        /// The attribute value is longer than allowed.
+        ValueTooLong,
+        /// The off-chain transaction expired.
+        TransactionExpired,
+        /// The off-chain transaction nonce is not the next nonce of the identity.
+        BadNonce,
+        /// The public key does not have the length of its declared type.
+        InvalidKey,
+        /// The key is already a verification method of the identity.
+        KeyExists,
+        /// The key is not an unexpired verification method of the identity.
+        UnknownKey,
+        /// The key is a key agreement key, which cannot sign.
+        KeyCannotSign,
+        /// The deposits of the identity are no longer fully reserved on its owner.
+        DepositNotMovable,
     }
 }
 
@@ -364,7 +828,7 @@ impl<T: Trait> Module<T> {
         delegate_type: &[u8],
         delegate: &T::AccountId,
     ) -> DispatchResult {
-        ensure!(delegate_type.len() <= 64, Error::<T>::InvalidDelegate);
+        ensure!(delegate_type.len() <= T::MaxNameLength::get() as usize, Error::<T>::InvalidDelegate);
         ensure!(
             Self::valid_listed_delegate(identity, delegate_type, delegate).is_ok()
                 || Self::is_owner(identity, delegate).is_ok(),
@@ -380,18 +844,18 @@ impl<T: Trait> Module<T> {
         delegate: &T::AccountId,
     ) -> DispatchResult {
         ensure!(
-            <DelegateOf<T>>::contains_key((&identity, delegate_type, &delegate)),
+            <DelegateOf<T>>::contains_key(&identity, (delegate_type, &delegate)),
             Error::<T>::InvalidDelegate
         );
 
-        let validity = Self::delegate_of((identity, delegate_type, delegate));
+        let validity = Self::delegate_of(identity, (delegate_type, delegate));
         match validity > Some(<frame_system::Module<T>>::block_number()) {
             true => Ok(()),
             false => Err(Error::<T>::InvalidDelegate.into()),
         }
     }
 
-    // Creates a new delegete for an account.
+    /// Creates a new delegete for an account and reserves its deposit from the owner.
     pub fn create_delegate(
         who: &T::AccountId,
         identity: &T::AccountId,
@@ -412,7 +876,19 @@ impl<T: Trait> Module<T> {
             None => u32::max_value().into(),
         };
 
-        <DelegateOf<T>>::insert((&identity, delegate_type, delegate), &validity);
+        let deposit = Self::reserve_deposit(identity, delegate_type.len())?;
+        // An expired delegate being renewed still holds its previous deposit.
+        let previous = <DelegateDeposit<T>>::take(&identity, (delegate_type, delegate));
+        Self::unreserve_deposit(identity, previous);
+
+        <DelegateOf<T>>::insert(&identity, (delegate_type, delegate), &validity);
+        <DelegateDeposit<T>>::insert(&identity, (delegate_type, delegate), deposit);
+        if valid_for.is_some() {
+            Self::schedule_expiry(
+                validity,
+                Expiring::Delegate(identity.clone(), delegate_type.to_vec(), delegate.clone()),
+            );
+        }
         Ok(())
     }
 
@@ -436,11 +912,182 @@ impl<T: Trait> Module<T> {
         msg: &[u8],
         signer: &T::AccountId,
     ) -> DispatchResult {
-        // Owner or a delegate signer.
-        Self::valid_delegate(&identity, b"x25519VerificationKey2018", &signer)?;
+        // Owner or a delegate signer, including those added with the legacy type.
+        #[allow(deprecated)]
+        let legacy_signer = Self::valid_listed_delegate(&identity, LEGACY_SIGNER_DELEGATE_TYPE, &signer);
+        if legacy_signer.is_err() {
+            Self::valid_delegate(&identity, SIGNER_DELEGATE_TYPE, &signer)?;
+        }
         Self::check_signature(&signature, &msg, &signer)
     }
 
+    /// The identifier of a verification method key.
+    pub fn key_id(key_type: VerificationKeyType, public_key: &[u8]) -> [u8; 32] {
+        (key_type, public_key).using_encoded(blake2_256)
+    }
+
+    /// Checks if a signature was made with an unexpired verification method key of an identity,
+    /// verifying it according to the declared type of the key.
+    pub fn check_key_signature(
+        identity: &T::AccountId,
+        key_id: &[u8; 32],
+        signature: &[u8],
+        msg: &[u8],
+    ) -> DispatchResult {
+        let method = Self::verification_method_of(identity, key_id)
+            .filter(|method| method.validity > <frame_system::Module<T>>::block_number())
+            .ok_or(Error::<T>::UnknownKey)?;
+
+        let valid = match method.key_type {
+            VerificationKeyType::Sr25519 => {
+                Self::verify_raw::<sr25519::Signature>(signature, msg, &method.public_key)
+            }
+            VerificationKeyType::Ed25519 => {
+                Self::verify_raw::<ed25519::Signature>(signature, msg, &method.public_key)
+            }
+            VerificationKeyType::EcdsaSecp256k1 => {
+                Self::verify_raw::<ecdsa::Signature>(signature, msg, &method.public_key)
+            }
+            VerificationKeyType::X25519 => return Err(Error::<T>::KeyCannotSign.into()),
+        };
+        ensure!(valid, Error::<T>::BadSignature);
+        Ok(())
+    }
+
+    /// Verifies a raw signature against a raw public key of the signature scheme `S`.
+    fn verify_raw<S>(signature: &[u8], msg: &[u8], public_key: &[u8]) -> bool
+    where
+        S: Verify + for<'a> TryFrom<&'a [u8]>,
+        <S::Signer as IdentifyAccount>::AccountId: for<'a> TryFrom<&'a [u8]>,
+    {
+        let signer = <<S::Signer as IdentifyAccount>::AccountId as TryFrom<&[u8]>>::try_from(public_key);
+        match (S::try_from(signature), signer) {
+            (Ok(signature), Ok(signer)) => signature.verify(msg, &signer),
+            _ => false,
+        }
+    }
+
+    /// Adds a verification method key to an identity and reserves its deposit from the owner.
+    fn insert_key(
+        identity: &T::AccountId,
+        key_type: VerificationKeyType,
+        public_key: Vec<u8>,
+        valid_for: Option<T::BlockNumber>,
+    ) -> Result<[u8; 32], DispatchError> {
+        let key_length = match key_type {
+            VerificationKeyType::EcdsaSecp256k1 => 33,
+            _ => 32,
+        };
+        ensure!(public_key.len() == key_length, Error::<T>::InvalidKey);
+        let key_id = Self::key_id(key_type, &public_key);
+        ensure!(
+            !<VerificationMethodOf<T>>::contains_key(identity, &key_id),
+            Error::<T>::KeyExists
+        );
+
+        let validity = match valid_for {
+            Some(blocks) => <frame_system::Module<T>>::block_number() + blocks,
+            None => u32::max_value().into(),
+        };
+        let deposit = Self::reserve_deposit(identity, public_key.len())?;
+        <VerificationMethodOf<T>>::insert(identity, &key_id, VerificationMethod {
+            key_type,
+            public_key,
+            validity,
+            deposit,
+        });
+        Ok(key_id)
+    }
+
+    /// Removes a verification method key from an identity and returns its deposit.
+    fn remove_key(identity: &T::AccountId, key_id: &[u8; 32]) -> DispatchResult {
+        let method = <VerificationMethodOf<T>>::take(identity, key_id).ok_or(Error::<T>::UnknownKey)?;
+        Self::unreserve_deposit(identity, method.deposit);
+        Ok(())
+    }
+
+    /// Queues a delegate or an attribute for removal at the block it expires.
+    /// Entries expiring at the current block are removed at the next one, as the queue of the
+    /// current block was already processed.
+    fn schedule_expiry(validity: T::BlockNumber, entry: Expiring<T::AccountId>) {
+        let next_block_number = <frame_system::Module<T>>::block_number() + One::one();
+        <ExpiryQueue<T>>::append(validity.max(next_block_number), entry);
+    }
+
+    /// Removes the delegates and attributes queued to expire at `now`, returning their deposits.
+    /// At most `MaxExpiriesPerBlock` entries are processed, the rest are postponed to the next
+    /// block. Entries renewed since they were queued are skipped.
+    fn remove_expired(now: T::BlockNumber) -> Weight {
+        if !<ExpiryQueue<T>>::contains_key(now) {
+            return T::DbWeight::get().reads(1);
+        }
+
+        let mut queue = <ExpiryQueue<T>>::take(now);
+        let max_expiries = T::MaxExpiriesPerBlock::get() as usize;
+        if queue.len() > max_expiries {
+            let postponed = queue.split_off(max_expiries);
+            <ExpiryQueue<T>>::mutate(now + One::one(), |next| next.extend(postponed));
+        }
+
+        let processed = queue.len() as Weight;
+        for entry in queue {
+            match entry {
+                Expiring::Delegate(identity, delegate_type, delegate) => {
+                    let key = (&delegate_type, &delegate);
+                    if Self::delegate_of(&identity, key).map_or(false, |validity| validity <= now) {
+                        <DelegateOf<T>>::remove(&identity, key);
+                        let deposit = <DelegateDeposit<T>>::take(&identity, key);
+                        Self::unreserve_deposit(&identity, deposit);
+                        Self::deposit_event(RawEvent::DelegateExpired(identity, delegate_type, delegate));
+                    }
+                }
+                Expiring::Attribute(identity, id) => {
+                    if !<AttributeOf<T>>::contains_key(&identity, &id) {
+                        continue;
+                    }
+                    let attribute = Self::attribute_of(&identity, &id);
+                    if attribute.validity <= now {
+                        <AttributeOf<T>>::remove(&identity, &id);
+                        let deposit = <AttributeDeposit<T>>::take(&identity, &id);
+                        Self::unreserve_deposit(&identity, deposit);
+                        Self::deposit_event(RawEvent::AttributeExpired(identity, attribute.name));
+                    }
+                }
+            }
+        }
+        T::DbWeight::get().reads_writes(3 + 4 * processed, 2 + 5 * processed)
+    }
+
+    /// Keeps track of an update of an identity.
+    fn note_update(identity: &T::AccountId, who: T::AccountId) {
+        <UpdatedBy<T>>::insert(
+            identity,
+            (
+                who,
+                <frame_system::Module<T>>::block_number(),
+                <pallet_timestamp::Module<T>>::now(),
+            ),
+        );
+    }
+
+    /// Returns the payload to sign for an off-chain transaction, from all its fields but the
+    /// signatures, the id of the signing key, the genesis hash and the spec name of the chain.
+    pub fn signing_payload(transaction: &AttributeTransaction<T::Signature, T::AccountId>) -> Vec<u8> {
+        (
+            &transaction.name,
+            &transaction.value,
+            transaction.validity,
+            &transaction.signer,
+            &transaction.identity,
+            transaction.nonce,
+            transaction.expiry,
+            transaction.key_signature.as_ref().map(|(key_id, _)| key_id),
+            <frame_system::Module<T>>::block_hash(T::BlockNumber::zero()),
+            T::Version::get().spec_name,
+        )
+            .encode()
+    }
+
     /// Adds a new attribute to an identity and colects the storage fee.
     pub fn create_attribute(
         who: &T::AccountId,
@@ -450,6 +1097,7 @@ impl<T: Trait> Module<T> {
         valid_for: Option<T::BlockNumber>,
     ) -> DispatchResult {
         Self::is_owner(&identity, &who)?;
+        ensure!(value.len() <= T::MaxValueLength::get() as usize, Error::<T>::ValueTooLong);
         let now_timestamp = <pallet_timestamp::Module<T>>::now();
         let now_block_number = <frame_system::Module<T>>::block_number();
         let mut nonce = Self::nonce_of((&identity, name.to_vec()));
@@ -467,7 +1115,7 @@ impl<T: Trait> Module<T> {
 
         let id = (&identity, name, lookup_nonce).using_encoded(blake2_256);
 
-        if <AttributeOf<T>>::contains_key((&identity, &id)) {
+        if <AttributeOf<T>>::contains_key(&identity, &id) {
             Err(Error::<T>::AttributeCreationFailed.into())
         } else {
             let new_attribute = Attribute {
@@ -480,7 +1128,12 @@ impl<T: Trait> Module<T> {
 
             // Prevent panic overflow
             nonce = nonce.checked_add(1).ok_or(Error::<T>::Overflow)?;
-            <AttributeOf<T>>::insert((&identity, &id), new_attribute);
+            let deposit = Self::reserve_deposit(identity, name.len() + value.len())?;
+            <AttributeOf<T>>::insert(&identity, &id, new_attribute);
+            <AttributeDeposit<T>>::insert(&identity, &id, deposit);
+            if valid_for.is_some() {
+                Self::schedule_expiry(validity, Expiring::Attribute(identity.clone(), id));
+            }
             <AttributeNonce<T>>::mutate((&identity, name.to_vec()), |n| *n = nonce);
             <UpdatedBy<T>>::insert(
                 identity,
@@ -494,7 +1147,7 @@ impl<T: Trait> Module<T> {
         }
     }
 
-    /// Updates the attribute validity to make it expire and invalid.
+    /// Updates the attribute validity to make it expire and invalid, and returns its deposit.
     pub fn reset_attribute(
         who: T::AccountId,
         identity: &T::AccountId,
@@ -505,8 +1158,12 @@ impl<T: Trait> Module<T> {
         let result = Self::attribute_and_id(identity, name);
         match result {
             Some((mut attribute, id)) => {
-                attribute.validity = <frame_system::Module<T>>::block_number();
-                <AttributeOf<T>>::mutate((&identity, id), |a| *a = attribute);
+                let now_block_number = <frame_system::Module<T>>::block_number();
+                attribute.validity = now_block_number;
+                <AttributeOf<T>>::mutate(&identity, id, |a| *a = attribute);
+                let deposit = <AttributeDeposit<T>>::take(&identity, id);
+                Self::unreserve_deposit(identity, deposit);
+                Self::schedule_expiry(now_block_number, Expiring::Attribute(identity.clone(), id));
             }
             None => return Err(Error::<T>::AttributeResetFailed.into()),
         }
@@ -525,7 +1182,7 @@ impl<T: Trait> Module<T> {
 
     /// Validates if an attribute belongs to an identity and it has not expired.
     pub fn valid_attribute(identity: &T::AccountId, name: &[u8], value: &[u8]) -> DispatchResult {
-        ensure!(name.len() <= 64, Error::<T>::InvalidAttribute);
+        ensure!(name.len() <= T::MaxNameLength::get() as usize, Error::<T>::InvalidAttribute);
         let result = Self::attribute_and_id(identity, name);
 
         let (attr, _) = match result {
@@ -560,45 +1217,301 @@ impl<T: Trait> Module<T> {
         // Needs to use actual attribute nonce -1.
         let id = (&identity, name, lookup_nonce).using_encoded(blake2_256);
 
-        if <AttributeOf<T>>::contains_key((&identity, &id)) {
-            Some((Self::attribute_of((identity, id)), id))
+        if <AttributeOf<T>>::contains_key(&identity, &id) {
+            Some((Self::attribute_of(identity, id), id))
         } else {
             None
         }
     }
 
-    /// Creates a new attribute from a off-chain transaction.
+    /// Returns the owner, unexpired keys, delegates and attributes of an identity.
+    /// Keys are ordered by id, delegates by type and attributes by name.
+    pub fn did_record(identity: &T::AccountId) -> DidRecord<T::AccountId, T::BlockNumber, T::Moment> {
+        let now_block_number = <frame_system::Module<T>>::block_number();
+
+        let mut verification_methods = <VerificationMethodOf<T>>::iter_prefix(identity)
+            .filter(|(_, method)| method.validity > now_block_number)
+            .map(|(id, method)| VerificationMethodRecord {
+                id,
+                key_type: method.key_type,
+                public_key: method.public_key,
+                valid_until: method.validity,
+            })
+            .collect::<Vec<_>>();
+        verification_methods.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut delegates = <DelegateOf<T>>::iter_prefix(identity)
+            .filter(|(_, validity)| *validity > now_block_number)
+            .map(|((delegate_type, delegate), validity)| DelegateRecord {
+                delegate_type,
+                delegate,
+                valid_until: validity,
+            })
+            .collect::<Vec<_>>();
+        delegates.sort_by(|a, b| a.delegate_type.cmp(&b.delegate_type));
+
+        let mut attributes = <AttributeOf<T>>::iter_prefix_values(identity)
+            .filter(|attr| attr.validity > now_block_number)
+            .map(Self::attribute_record)
+            .collect::<Vec<_>>();
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let updated = match <UpdatedBy<T>>::contains_key(identity) {
+            true => Some(Self::updated_by(identity)),
+            false => None,
+        };
+
+        DidRecord {
+            identity: identity.clone(),
+            owner: Self::identity_owner(identity),
+            verification_methods,
+            delegates,
+            attributes,
+            updated,
+            usernames: T::Usernames::usernames_of(identity),
+        }
+    }
+
+    /// Returns the page `page` of `page_size` delegates of an identity, expired ones included.
+    pub fn delegates(
+        identity: &T::AccountId,
+        page: u32,
+        page_size: u32,
+    ) -> Vec<DelegateRecord<T::AccountId, T::BlockNumber>> {
+        <DelegateOf<T>>::iter_prefix(identity)
+            .skip(page.saturating_mul(page_size) as usize)
+            .take(page_size as usize)
+            .map(|((delegate_type, delegate), validity)| DelegateRecord {
+                delegate_type,
+                delegate,
+                valid_until: validity,
+            })
+            .collect()
+    }
+
+    /// Returns the page `page` of `page_size` attributes of an identity, expired ones included.
+    pub fn attributes(
+        identity: &T::AccountId,
+        page: u32,
+        page_size: u32,
+    ) -> Vec<AttributeRecord<T::BlockNumber, T::Moment>> {
+        <AttributeOf<T>>::iter_prefix_values(identity)
+            .skip(page.saturating_mul(page_size) as usize)
+            .take(page_size as usize)
+            .map(Self::attribute_record)
+            .collect()
+    }
+
+    /// Returns whether a credential anchored by an issuer is active, expired or revoked,
+    /// either directly or through the issuer's revocation status list.
+    pub fn credential_status(
+        issuer: &T::AccountId,
+        credential: &[u8; 32],
+    ) -> CredentialStatus<T::BlockNumber> {
+        let credential = match Self::credential_of(issuer, credential) {
+            Some(credential) => credential,
+            None => return CredentialStatus::Unknown,
+        };
+
+        if let Some(block_number) = credential.revoked {
+            return CredentialStatus::Revoked(Some(block_number));
+        }
+        if let Some((list_id, index)) = credential.status {
+            if Self::listed_as_revoked(issuer, list_id, index) {
+                return CredentialStatus::Revoked(None);
+            }
+        }
+        match credential.validity > <frame_system::Module<T>>::block_number() {
+            true => CredentialStatus::Active(credential.validity),
+            false => CredentialStatus::Expired(credential.validity),
+        }
+    }
+
+    /// Whether the bit at `index` of a revocation status list is set.
+    /// Bits are read most significant first, as in the W3C Status List 2021.
+    fn listed_as_revoked(issuer: &T::AccountId, list_id: u32, index: u32) -> bool {
+        Self::status_list_of(issuer, list_id)
+            .get((index / 8) as usize)
+            .map_or(false, |byte| byte & (0x80u8 >> (index % 8)) != 0)
+    }
+
+    fn attribute_record(attr: Attribute<T::BlockNumber, T::Moment>) -> AttributeRecord<T::BlockNumber, T::Moment> {
+        AttributeRecord {
+            name: attr.name,
+            value: attr.value,
+            valid_until: attr.validity,
+            creation: attr.creation,
+        }
+    }
+
+    /// Reserves from the identity owner the deposit for `bytes` of delegate, attribute,
+    /// credential or status list data.
+    fn reserve_deposit(identity: &T::AccountId, bytes: usize) -> Result<BalanceOf<T>, DispatchError> {
+        let deposit = T::DepositBase::get()
+            .saturating_add(T::DepositPerByte::get().saturating_mul((bytes as u32).into()));
+        T::Currency::reserve(&Self::identity_owner(identity), deposit)?;
+        <DepositOf<T>>::mutate(identity, |total| *total = total.saturating_add(deposit));
+        Ok(deposit)
+    }
+
+    /// Returns a delegate or attribute deposit to the identity owner.
+    fn unreserve_deposit(identity: &T::AccountId, deposit: BalanceOf<T>) {
+        if deposit.is_zero() {
+            return;
+        }
+        T::Currency::unreserve(&Self::identity_owner(identity), deposit);
+        <DepositOf<T>>::mutate(identity, |total| *total = total.saturating_sub(deposit));
+    }
+
+    /// Moves delegates and attributes from the maps keyed by tuples to the double maps keyed
+    /// by identity first. Both live under the same storage names, so the old entries are
+    /// drained before the new ones are written.
+    fn migrate_to_double_maps() {
+        let delegates = StorageKeyIterator::<
+            (T::AccountId, Vec<u8>, T::AccountId),
+            T::BlockNumber,
+            Blake2_128Concat,
+        >::new(b"DID", b"DelegateOf")
+            .drain()
+            .collect::<Vec<_>>();
+        let attributes = StorageKeyIterator::<
+            (T::AccountId, [u8; 32]),
+            Attribute<T::BlockNumber, T::Moment>,
+            Blake2_128Concat,
+        >::new(b"DID", b"AttributeOf")
+            .drain()
+            .collect::<Vec<_>>();
+
+        for ((identity, delegate_type, delegate), validity) in delegates {
+            <DelegateOf<T>>::insert(&identity, (delegate_type, delegate), validity);
+        }
+        for ((identity, id), attribute) in attributes {
+            <AttributeOf<T>>::insert(&identity, id, attribute);
+        }
+    }
+
+    /// Queues the delegates and attributes with a finite validity, added before the expiry
+    /// queue, for removal once expired. Those already expired are removed from the next block.
+    fn migrate_to_expiry_queue() {
+        let no_expiry: T::BlockNumber = u32::max_value().into();
+        let delegates = <DelegateOf<T>>::iter()
+            .filter(|(_, _, validity)| *validity != no_expiry)
+            .collect::<Vec<_>>();
+        let attributes = <AttributeOf<T>>::iter()
+            .filter(|(_, _, attribute)| attribute.validity != no_expiry)
+            .collect::<Vec<_>>();
+
+        for (identity, (delegate_type, delegate), validity) in delegates {
+            Self::schedule_expiry(validity, Expiring::Delegate(identity, delegate_type, delegate));
+        }
+        for (identity, id, attribute) in attributes {
+            Self::schedule_expiry(attribute.validity, Expiring::Attribute(identity, id));
+        }
+    }
+
+    /// Checks that an off-chain transaction was signed by the verification method key it names,
+    /// or else by the owner or a not expired signer delegate of the identity.
+    fn check_transaction_signature(
+        transaction: &AttributeTransaction<T::Signature, T::AccountId>,
+    ) -> DispatchResult {
+        let payload = Self::signing_payload(transaction);
+        match &transaction.key_signature {
+            Some((key_id, signature)) => {
+                Self::check_key_signature(&transaction.identity, key_id, signature, &payload)
+            }
+            None => Self::valid_signer(
+                &transaction.identity,
+                &transaction.signature,
+                &payload,
+                &transaction.signer,
+            ),
+        }
+    }
+
+    /// Creates a new attribute from a off-chain transaction, on behalf of its signer.
+    ///
+    /// The nonce is consumed once the transaction is authenticated, before it is applied, so
+    /// that a relayed transaction failing to apply cannot be included again.
     fn signed_attribute(
-        who: T::AccountId,
-        encoded: &[u8],
         transaction: &AttributeTransaction<T::Signature, T::AccountId>,
     ) -> DispatchResult {
-        // Verify that the Data was signed by the owner or a not expired signer delegate.
-        Self::valid_signer(
-            &transaction.identity,
-            &transaction.signature,
-            &encoded,
-            &transaction.signer,
-        )?;
+        let now_block_number = <frame_system::Module<T>>::block_number();
+        ensure!(
+            T::BlockNumber::from(transaction.expiry) >= now_block_number,
+            Error::<T>::TransactionExpired
+        );
+        ensure!(
+            transaction.nonce == Self::transaction_nonce(&transaction.identity),
+            Error::<T>::BadNonce
+        );
+
+        Self::check_transaction_signature(transaction)?;
         Self::is_owner(&transaction.identity, &transaction.signer)?;
-        ensure!(transaction.name.len() <= 64, Error::<T>::BadTransaction);
+        <TransactionNonce<T>>::mutate(&transaction.identity, |nonce| *nonce += 1);
+
+        ensure!(
+            transaction.name.len() <= T::MaxNameLength::get() as usize,
+            Error::<T>::BadTransaction
+        );
 
-        let now_block_number = <frame_system::Module<T>>::block_number();
         let validity = now_block_number + transaction.validity.into();
 
         // If validity was set to 0 in the transaction,
         // it will set the attribute latest valid block to the actual block.
         if validity > now_block_number {
             Self::create_attribute(
-                &who,
+                &transaction.signer,
                 &transaction.identity,
                 &transaction.name,
                 &transaction.value,
                 Some(transaction.validity.into()),
             )?;
         } else {
-            Self::reset_attribute(who, &transaction.identity, &transaction.name)?;
+            Self::reset_attribute(transaction.signer.clone(), &transaction.identity, &transaction.name)?;
         }
         Ok(())
     }
 }
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        if let Call::execute_unsigned(transaction) = call {
+            let now_block_number = <frame_system::Module<T>>::block_number();
+            let expiry = T::BlockNumber::from(transaction.expiry);
+            if expiry < now_block_number {
+                return InvalidTransaction::Stale.into();
+            }
+
+            let nonce = Self::transaction_nonce(&transaction.identity);
+            if transaction.nonce < nonce {
+                return InvalidTransaction::Stale.into();
+            }
+
+            // Check the signature last, as it is the most expensive.
+            let signature_valid = Self::is_owner(&transaction.identity, &transaction.signer).is_ok()
+                && Self::check_transaction_signature(transaction).is_ok();
+            if !signature_valid {
+                return InvalidTransaction::BadProof.into();
+            }
+
+            let mut valid = ValidTransaction::with_tag_prefix("DidExecute")
+                .priority(T::UnsignedPriority::get())
+                .and_provides((&transaction.identity, transaction.nonce))
+                .longevity(
+                    TryInto::<u64>::try_into(expiry - now_block_number)
+                        .unwrap_or(64_u64)
+                        .saturating_add(1),
+                )
+                .propagate(true);
+            // Transactions with future nonces wait for the previous ones.
+            if transaction.nonce > nonce {
+                valid = valid.and_requires((&transaction.identity, transaction.nonce - 1));
+            }
+            valid.build()
+        } else {
+            InvalidTransaction::Call.into()
+        }
+    }
+}
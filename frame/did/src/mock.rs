@@ -6,6 +6,7 @@ use sp_core::{sr25519, Pair, H256};
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
+    transaction_validity::TransactionPriority,
     Perbill,
 };
 
@@ -23,6 +24,14 @@ parameter_types! {
   pub const MaximumBlockWeight: Weight = 1024;
   pub const MaximumBlockLength: u32 = 2 * 1024;
   pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+  pub const MaxStatusListLength: u32 = 4;
+  pub const ExistentialDeposit: u64 = 1;
+  pub const DepositBase: u64 = 10;
+  pub const DepositPerByte: u64 = 1;
+  pub const MaxNameLength: u32 = 64;
+  pub const MaxValueLength: u32 = 32;
+  pub const UnsignedPriority: TransactionPriority = 100;
+  pub const MaxExpiriesPerBlock: u32 = 2;
 }
 
 impl system::Trait for Test {
@@ -47,12 +56,22 @@ impl system::Trait for Test {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type PalletInfo = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
 
+impl pallet_balances::Trait for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
 impl timestamp::Trait for Test {
     type Moment = u64;
     type OnTimestampSet = ();
@@ -64,18 +83,37 @@ impl Trait for Test {
     type Event = ();
     type Public = sr25519::Public;
     type Signature = sr25519::Signature;
+    type MaxStatusListLength = MaxStatusListLength;
+    type Currency = Balances;
+    type DepositBase = DepositBase;
+    type DepositPerByte = DepositPerByte;
+    type MaxNameLength = MaxNameLength;
+    type MaxValueLength = MaxValueLength;
+    type UnsignedPriority = UnsignedPriority;
+    type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+    type Usernames = ();
+    type WeightInfo = ();
 }
 
 pub type DID = Module<Test>;
 pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut t = system::GenesisConfig::default()
         .build_storage::<Test>()
-        .unwrap()
-        .into()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: ["Alice", "Bob", "Charlie", "Dave", "Satoshi", "BadBoy"]
+            .iter()
+            .map(|name| (account_key(name), 1_000))
+            .collect(),
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    t.into()
 }
 
 pub fn account_pair(s: &str) -> sr25519::Pair {
@@ -1,7 +1,17 @@
-use crate::{mock::*, AttributeTransaction, Error};
+use crate::{
+    mock::*, Attribute, AttributeNonce, AttributeOf, AttributeTransaction, Call, DelegateOf, Error,
+    Expiring, Releases, StorageVersion, VerificationKeyType,
+};
+use pallet_did_rpc_runtime_api::CredentialStatus;
 use codec::Encode;
-use frame_support::{assert_noop, assert_ok};
-use sp_core::Pair;
+use frame_support::{
+    assert_noop, assert_ok, unsigned::ValidateUnsigned, storage::migration::put_storage_value,
+    traits::{OnInitialize, OnRuntimeUpgrade},
+    Blake2_128Concat, StorageDoubleMap, StorageHasher, StorageMap, StorageValue,
+};
+use sp_core::{ed25519, Pair};
+use sp_io::hashing::blake2_256;
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionSource};
 
 #[test]
 fn validate_claim() {
@@ -40,8 +50,8 @@ fn validate_delegated_claim() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
 
-        // Predefined delegate type: "Sr25519VerificationKey2018"
-        let delegate_type = b"x25519VerificationKey2018".to_vec();
+        // Predefined signer delegate type.
+        let delegate_type = b"sigAuth".to_vec();
         let data = b"I am Satoshi Nakamoto".to_vec();
 
         let satoshi_public = account_key("Satoshi"); // Get Satoshi's public key.
@@ -54,7 +64,7 @@ fn validate_delegated_claim() {
                 Origin::signed(satoshi_public.clone()),
                 satoshi_public,  // owner
                 nakamoto_public, // new signer delgate
-                delegate_type,   // "Sr25519VerificationKey2018"
+                delegate_type,   // "sigAuth"
                 Some(5)
             ) // valid for 5 blocks
         );
@@ -82,6 +92,34 @@ fn validate_delegated_claim() {
     });
 }
 
+#[test]
+fn legacy_signer_delegates_should_still_sign() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let satoshi_public = account_key("Satoshi");
+        let nakamoto_pair = account_pair("Nakamoto");
+        let nakamoto_public = nakamoto_pair.public();
+        let claim = b"I am Satoshi Nakamoto".to_vec().encode();
+        let nakamoto_sig = nakamoto_pair.sign(&claim);
+
+        assert_ok!(DID::add_delegate(
+            Origin::signed(satoshi_public),
+            satoshi_public,
+            nakamoto_public,
+            b"x25519VerificationKey2018".to_vec(),
+            Some(5)
+        ));
+        assert_ok!(DID::valid_signer(&satoshi_public, &nakamoto_sig, &claim, &nakamoto_public));
+
+        System::set_block_number(6);
+        assert_noop!(
+            DID::valid_signer(&satoshi_public, &nakamoto_sig, &claim, &nakamoto_public),
+            Error::<Test>::InvalidDelegate
+        );
+    });
+}
+
 #[test]
 fn add_on_chain_and_revoke_off_chain_attribute() {
     new_test_ext().execute_with(|| {
@@ -109,21 +147,18 @@ fn add_on_chain_and_revoke_off_chain_attribute() {
         // Set validity to 0 in order to revoke the attribute.
         validity = 0;
         value = [0].to_vec();
-        let mut encoded = name.encode();
-        encoded.extend(value.encode());
-        encoded.extend(validity.encode());
-        encoded.extend(alice_public.encode());
-
-        let revoke_sig = alice_pair.sign(&encoded);
-
-        let revoke_transaction = AttributeTransaction {
-            signature: revoke_sig,
+        let mut revoke_transaction = AttributeTransaction {
+            signature: Default::default(),
             name: name.clone(),
             value,
             validity,
             signer: alice_public,
             identity: alice_public,
+            nonce: 0,
+            expiry: 10,
+            key_signature: None,
         };
+        revoke_transaction.signature = alice_pair.sign(&DID::signing_payload(&revoke_transaction));
 
         // Revoke with off-chain signed transaction.
         assert_ok!(DID::execute(
@@ -200,3 +235,879 @@ fn attacker_add_new_delegate_should_fail() {
         );
     });
 }
+
+#[test]
+fn did_record_should_list_unexpired_delegates_and_attributes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+        let charlie_public = account_key("Charlie");
+
+        // Add two delegates, one of which expires at block 3.
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            bob_public,
+            b"sigAuth".to_vec(),
+            None
+        ));
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            charlie_public,
+            b"veriKey".to_vec(),
+            Some(2)
+        ));
+
+        // Add an attribute, then revoke another one.
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"did/svc/HubService".to_vec(),
+            b"https://hubs.example.com".to_vec(),
+            None
+        ));
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"did/pub/Ed25519/veriKey/hex".to_vec(),
+            [1, 2, 3].to_vec(),
+            None
+        ));
+        assert_ok!(DID::revoke_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"did/pub/Ed25519/veriKey/hex".to_vec()
+        ));
+
+        let record = DID::did_record(&alice_public);
+        assert_eq!(record.owner, alice_public);
+        assert_eq!(record.delegates.len(), 2);
+        assert_eq!(record.delegates[0].delegate, bob_public);
+        assert_eq!(record.attributes.len(), 1);
+        assert_eq!(record.attributes[0].name, b"did/svc/HubService".to_vec());
+        assert_eq!(record.updated.map(|(who, block, _)| (who, block)), Some((alice_public, 1)));
+
+        // The second delegate expired.
+        System::set_block_number(3);
+        let record = DID::did_record(&alice_public);
+        assert_eq!(record.delegates.len(), 1);
+
+        // Nothing is resolved for another identity.
+        let record = DID::did_record(&bob_public);
+        assert_eq!(record.owner, bob_public);
+        assert!(record.delegates.is_empty());
+        assert!(record.attributes.is_empty());
+        assert!(record.updated.is_none());
+    });
+}
+
+#[test]
+fn delegates_and_attributes_should_be_paged() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        for name in &["Bob", "Charlie", "Dave"] {
+            assert_ok!(DID::add_delegate(
+                Origin::signed(alice_public),
+                alice_public,
+                account_key(name),
+                b"veriKey".to_vec(),
+                Some(1)
+            ));
+        }
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"MyAttribute".to_vec(),
+            [1, 2, 3].to_vec(),
+            None
+        ));
+
+        // Expired delegates are listed too.
+        System::set_block_number(5);
+
+        assert_eq!(DID::delegates(&alice_public, 0, 2).len(), 2);
+        assert_eq!(DID::delegates(&alice_public, 1, 2).len(), 1);
+        assert!(DID::delegates(&alice_public, 2, 2).is_empty());
+        assert!(DID::delegates(&account_key("Bob"), 0, 2).is_empty());
+
+        let attributes = DID::attributes(&alice_public, 0, 10);
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].value, [1, 2, 3].to_vec());
+    });
+}
+
+#[test]
+fn runtime_upgrade_should_migrate_to_double_maps() {
+    new_test_ext().execute_with(|| {
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+        let delegate_type = b"veriKey".to_vec();
+        let attribute = Attribute {
+            name: b"MyAttribute".to_vec(),
+            value: [1, 2, 3].to_vec(),
+            validity: 100,
+            creation: 0,
+            nonce: 0,
+        };
+        let id = (&alice_public, &attribute.name, 0u64).using_encoded(blake2_256);
+
+        // Write entries as the maps keyed by tuples did.
+        put_storage_value(
+            b"DID",
+            b"DelegateOf",
+            &Blake2_128Concat::hash(&(alice_public, delegate_type.clone(), bob_public).encode()),
+            10u64,
+        );
+        put_storage_value(
+            b"DID",
+            b"AttributeOf",
+            &Blake2_128Concat::hash(&(alice_public, id).encode()),
+            attribute.clone(),
+        );
+        <AttributeNonce<Test>>::insert((alice_public, attribute.name.clone()), 1);
+
+        DID::on_runtime_upgrade();
+
+        assert_eq!(DID::delegate_of(alice_public, (delegate_type, bob_public)), Some(10));
+        assert_eq!(DID::attribute_of(alice_public, id), attribute);
+        assert_ok!(DID::valid_attribute(&alice_public, b"MyAttribute", &[1, 2, 3]));
+        assert_eq!(DID::delegates(&alice_public, 0, 10).len(), 1);
+        assert_eq!(DID::expiry_queue(10).len(), 1);
+        assert_eq!(DID::expiry_queue(100), vec![Expiring::Attribute(alice_public, id)]);
+    });
+}
+
+#[test]
+fn runtime_upgrade_should_queue_expiring_entries() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(7);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+        let charlie_public = account_key("Charlie");
+        let delegate_type = b"veriKey".to_vec();
+        let attribute = Attribute {
+            name: b"MyAttribute".to_vec(),
+            value: [1, 2, 3].to_vec(),
+            validity: 5,
+            creation: 0,
+            nonce: 0,
+        };
+        let id = (&alice_public, &attribute.name, 0u64).using_encoded(blake2_256);
+
+        // Entries added before the expiry queue, one of them without expiry.
+        StorageVersion::put(Releases::V2_0_0);
+        <DelegateOf<Test>>::insert(&alice_public, (delegate_type.clone(), bob_public), 10);
+        <DelegateOf<Test>>::insert(
+            &alice_public,
+            (delegate_type.clone(), charlie_public),
+            u32::max_value() as u64,
+        );
+        <AttributeOf<Test>>::insert(&alice_public, id, attribute);
+
+        DID::on_runtime_upgrade();
+
+        assert_eq!(
+            DID::expiry_queue(10),
+            vec![Expiring::Delegate(alice_public, delegate_type.clone(), bob_public)]
+        );
+        // Already expired entries are queued for the next block.
+        assert_eq!(DID::expiry_queue(8), vec![Expiring::Attribute(alice_public, id)]);
+
+        DID::on_initialize(8);
+        assert!(!<AttributeOf<Test>>::contains_key(&alice_public, &id));
+        DID::on_initialize(10);
+        assert_eq!(DID::delegate_of(alice_public, (delegate_type.clone(), bob_public)), None);
+        assert_eq!(
+            DID::delegate_of(alice_public, (delegate_type, charlie_public)),
+            Some(u32::max_value() as u64)
+        );
+
+        // The migration runs only once.
+        assert_eq!(StorageVersion::get(), Releases::V3_0_0);
+        assert_eq!(DID::on_runtime_upgrade(), 0);
+    });
+}
+
+#[test]
+fn credentials_should_be_issued_and_revoked_by_issuer_delegates() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+        let charlie_public = account_key("Charlie");
+        let journalist = blake2_256(b"verified journalist");
+        let contributor = blake2_256(b"mission contributor");
+
+        // Only the owner and credential delegates may issue.
+        assert_noop!(
+            DID::issue_credential(Origin::signed(bob_public), alice_public, journalist, None, None),
+            Error::<Test>::InvalidDelegate
+        );
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            bob_public,
+            b"veriKey".to_vec(),
+            None
+        ));
+        assert_ok!(DID::issue_credential(
+            Origin::signed(bob_public),
+            alice_public,
+            journalist,
+            Some(10),
+            None
+        ));
+        assert_noop!(
+            DID::issue_credential(Origin::signed(alice_public), alice_public, journalist, None, None),
+            Error::<Test>::CredentialExists
+        );
+        assert_eq!(DID::credential_status(&alice_public, &journalist), CredentialStatus::Active(11));
+        assert_eq!(DID::credential_of(&alice_public, &journalist).unwrap().issued_by, bob_public);
+        assert_eq!(DID::credential_status(&charlie_public, &journalist), CredentialStatus::Unknown);
+
+        // The credential expires.
+        System::set_block_number(11);
+        assert_eq!(DID::credential_status(&alice_public, &journalist), CredentialStatus::Expired(11));
+
+        // Revocation by the owner.
+        assert_ok!(DID::issue_credential(
+            Origin::signed(alice_public),
+            alice_public,
+            contributor,
+            None,
+            None
+        ));
+        assert_noop!(
+            DID::revoke_credential(Origin::signed(charlie_public), alice_public, contributor),
+            Error::<Test>::InvalidDelegate
+        );
+        assert_ok!(DID::revoke_credential(Origin::signed(alice_public), alice_public, contributor));
+        assert_noop!(
+            DID::revoke_credential(Origin::signed(bob_public), alice_public, contributor),
+            Error::<Test>::CredentialRevoked
+        );
+        assert_noop!(
+            DID::revoke_credential(Origin::signed(bob_public), alice_public, [0; 32]),
+            Error::<Test>::UnknownCredential
+        );
+        assert_eq!(
+            DID::credential_status(&alice_public, &contributor),
+            CredentialStatus::Revoked(Some(11))
+        );
+    });
+}
+
+#[test]
+fn credentials_should_be_revoked_by_status_lists() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let credential = blake2_256(b"mission contributor");
+
+        assert_ok!(DID::issue_credential(
+            Origin::signed(alice_public),
+            alice_public,
+            credential,
+            None,
+            Some((7, 9))
+        ));
+        let valid_until = u32::max_value() as u64;
+        assert_eq!(DID::credential_status(&alice_public, &credential), CredentialStatus::Active(valid_until));
+
+        assert_noop!(
+            DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![0; 5]),
+            Error::<Test>::StatusListTooLong
+        );
+
+        // Index 9 is the second most significant bit of the second byte.
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![0xff, 0xbf]));
+        assert_eq!(DID::credential_status(&alice_public, &credential), CredentialStatus::Active(valid_until));
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![0, 0x40]));
+        assert_eq!(DID::credential_status(&alice_public, &credential), CredentialStatus::Revoked(None));
+
+        // An empty list removes it.
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![]));
+        assert!(DID::status_list_of(&alice_public, 7).is_empty());
+        assert_eq!(DID::credential_status(&alice_public, &credential), CredentialStatus::Active(valid_until));
+    });
+}
+
+#[test]
+fn credentials_and_status_lists_should_hold_deposits() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let credential = blake2_256(b"mission contributor");
+
+        // The deposit covers the 50 bytes of the stored credential.
+        assert_ok!(DID::issue_credential(
+            Origin::signed(alice_public),
+            alice_public,
+            credential,
+            None,
+            None
+        ));
+        assert_eq!(DID::credential_deposit(&alice_public, &credential), 60);
+        assert_eq!(Balances::reserved_balance(alice_public), 60);
+
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![0xff, 0xbf]));
+        assert_eq!(DID::status_list_deposit(&alice_public, 7), 12);
+        assert_eq!(Balances::reserved_balance(alice_public), 72);
+
+        // A replacement list holds its own deposit in place of the previous one.
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![0, 0x40, 0]));
+        assert_eq!(Balances::reserved_balance(alice_public), 73);
+
+        assert_ok!(DID::publish_status_list(Origin::signed(alice_public), alice_public, 7, vec![]));
+        assert_eq!(DID::status_list_deposit(&alice_public, 7), 0);
+        assert_eq!(Balances::reserved_balance(alice_public), 60);
+        assert_eq!(DID::deposit_of(&alice_public), 60);
+    });
+}
+
+#[test]
+fn deposits_should_be_reserved_and_returned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+
+        // 10 + 7 bytes.
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            bob_public,
+            b"veriKey".to_vec(),
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(alice_public), 17);
+
+        // 10 + 11 + 3 bytes.
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"MyAttribute".to_vec(),
+            [1, 2, 3].to_vec(),
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(alice_public), 41);
+        assert_eq!(DID::deposit_of(alice_public), 41);
+
+        assert_ok!(DID::revoke_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            b"veriKey".to_vec(),
+            bob_public
+        ));
+        assert_eq!(Balances::reserved_balance(alice_public), 24);
+
+        // Revoking returns the deposit, so deleting afterwards returns nothing more.
+        assert_ok!(DID::revoke_attribute(Origin::signed(alice_public), alice_public, b"MyAttribute".to_vec()));
+        assert_eq!(Balances::reserved_balance(alice_public), 0);
+        assert_ok!(DID::delete_attribute(Origin::signed(alice_public), alice_public, b"MyAttribute".to_vec()));
+        assert_eq!(Balances::reserved_balance(alice_public), 0);
+        assert_eq!(DID::deposit_of(alice_public), 0);
+    });
+}
+
+#[test]
+fn deposits_should_move_with_ownership() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"MyAttribute".to_vec(),
+            [1, 2, 3].to_vec(),
+            None
+        ));
+        assert_ok!(DID::change_owner(Origin::signed(alice_public), alice_public, bob_public));
+        assert_eq!(Balances::reserved_balance(alice_public), 0);
+        assert_eq!(Balances::reserved_balance(bob_public), 24);
+        assert_eq!(Balances::free_balance(alice_public), 976);
+
+        // The new owner gets the deposit back.
+        assert_ok!(DID::delete_attribute(Origin::signed(bob_public), alice_public, b"MyAttribute".to_vec()));
+        assert_eq!(Balances::reserved_balance(bob_public), 0);
+        assert_eq!(Balances::free_balance(bob_public), 1_024);
+    });
+}
+
+#[test]
+fn deposits_should_move_to_a_new_account() {
+    new_test_ext().execute_with(|| {
+        let alice_public = account_key("Alice");
+        let newcomer = account_key("Newcomer");
+
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"MyAttribute".to_vec(),
+            [1, 2, 3].to_vec(),
+            None
+        ));
+        assert_eq!(Balances::total_balance(&newcomer), 0);
+
+        assert_ok!(DID::change_owner(Origin::signed(alice_public), alice_public, newcomer));
+        assert_eq!(DID::identity_owner(&alice_public), newcomer);
+        assert_eq!(DID::deposit_of(&alice_public), 24);
+        assert_eq!(Balances::reserved_balance(alice_public), 0);
+        assert_eq!(Balances::reserved_balance(newcomer), 24);
+        assert_eq!(Balances::free_balance(alice_public), 976);
+    });
+}
+
+#[test]
+fn oversized_or_unfunded_attributes_should_fail() {
+    new_test_ext().execute_with(|| {
+        let alice_public = account_key("Alice");
+
+        assert_noop!(
+            DID::add_attribute(Origin::signed(alice_public), alice_public, vec![0; 65], vec![], None),
+            Error::<Test>::AttributeCreationFailed
+        );
+        assert_noop!(
+            DID::add_attribute(Origin::signed(alice_public), alice_public, b"MyAttribute".to_vec(), vec![0; 33], None),
+            Error::<Test>::ValueTooLong
+        );
+        assert_noop!(
+            DID::add_delegate(Origin::signed(alice_public), alice_public, account_key("Bob"), vec![0; 65], None),
+            Error::<Test>::InvalidDelegate
+        );
+
+        // Eve has no funds for the deposit.
+        let eve_public = account_key("Eve");
+        assert_noop!(
+            DID::add_attribute(Origin::signed(eve_public), eve_public, b"MyAttribute".to_vec(), vec![], None),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn off_chain_transactions_should_not_be_replayed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_pair = account_pair("Alice");
+        let alice_public = alice_pair.public();
+        let signed_transaction = |nonce, expiry| {
+            let mut transaction = AttributeTransaction {
+                signature: Default::default(),
+                name: b"MyAttribute".to_vec(),
+                value: [1, 2, 3].to_vec(),
+                validity: 100,
+                signer: alice_public,
+                identity: alice_public,
+                nonce,
+                expiry,
+                key_signature: None,
+            };
+            transaction.signature = alice_pair.sign(&DID::signing_payload(&transaction));
+            transaction
+        };
+
+        let transaction = signed_transaction(0, 5);
+        assert_ok!(DID::execute(Origin::signed(account_key("Bob")), transaction.clone()));
+        assert_ok!(DID::valid_attribute(&alice_public, b"MyAttribute", &[1, 2, 3]));
+        assert_eq!(DID::transaction_nonce(alice_public), 1);
+
+        // Replaying it, even after the attribute is deleted, fails.
+        assert_ok!(DID::delete_attribute(Origin::signed(alice_public), alice_public, b"MyAttribute".to_vec()));
+        assert_noop!(
+            DID::execute(Origin::signed(account_key("Bob")), transaction),
+            Error::<Test>::BadNonce
+        );
+
+        // A tampered transaction fails.
+        let mut tampered = signed_transaction(1, 5);
+        tampered.value = [6, 6, 6].to_vec();
+        assert_noop!(
+            DID::execute(Origin::signed(account_key("Bob")), tampered),
+            Error::<Test>::BadSignature
+        );
+
+        // An expired transaction fails.
+        System::set_block_number(6);
+        assert_noop!(
+            DID::execute(Origin::signed(account_key("Bob")), signed_transaction(1, 5)),
+            Error::<Test>::TransactionExpired
+        );
+    });
+}
+
+#[test]
+fn off_chain_transactions_should_be_relayed_unsigned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_pair = account_pair("Alice");
+        let alice_public = alice_pair.public();
+        let signed_transaction = |nonce, expiry| {
+            let mut transaction = AttributeTransaction {
+                signature: Default::default(),
+                name: b"MyAttribute".to_vec(),
+                value: [1, 2, 3].to_vec(),
+                validity: 100,
+                signer: alice_public,
+                identity: alice_public,
+                nonce,
+                expiry,
+                key_signature: None,
+            };
+            transaction.signature = alice_pair.sign(&DID::signing_payload(&transaction));
+            transaction
+        };
+
+        let valid = DID::validate_unsigned(
+            TransactionSource::External,
+            &Call::execute_unsigned(signed_transaction(0, 10)),
+        )
+        .unwrap();
+        assert_eq!(valid.priority, 100);
+        assert_eq!(valid.longevity, 10);
+        assert_eq!(valid.provides, vec![("DidExecute", (alice_public, 0u64)).encode()]);
+        assert!(valid.requires.is_empty());
+
+        // A future nonce requires the previous one.
+        let valid = DID::validate_unsigned(
+            TransactionSource::External,
+            &Call::execute_unsigned(signed_transaction(1, 10)),
+        )
+        .unwrap();
+        assert_eq!(valid.requires, vec![("DidExecute", (alice_public, 0u64)).encode()]);
+
+        assert_ok!(DID::execute_unsigned(Origin::none(), signed_transaction(0, 10)));
+        assert_ok!(DID::valid_attribute(&alice_public, b"MyAttribute", &[1, 2, 3]));
+
+        assert_eq!(
+            DID::validate_unsigned(
+                TransactionSource::External,
+                &Call::execute_unsigned(signed_transaction(0, 10)),
+            ),
+            InvalidTransaction::Stale.into()
+        );
+        let mut forged = signed_transaction(1, 10);
+        forged.signer = account_key("Bob");
+        assert_eq!(
+            DID::validate_unsigned(TransactionSource::External, &Call::execute_unsigned(forged)),
+            InvalidTransaction::BadProof.into()
+        );
+
+        // An authenticated transaction that fails to apply still consumes its nonce, so it
+        // cannot be included again.
+        let mut oversized = AttributeTransaction {
+            signature: Default::default(),
+            name: vec![b'a'; 65],
+            value: [1, 2, 3].to_vec(),
+            validity: 100,
+            signer: alice_public,
+            identity: alice_public,
+            nonce: 1,
+            expiry: 10,
+            key_signature: None,
+        };
+        oversized.signature = alice_pair.sign(&DID::signing_payload(&oversized));
+        assert_eq!(
+            DID::execute_unsigned(Origin::none(), oversized.clone()),
+            Err(Error::<Test>::BadTransaction.into())
+        );
+        assert_eq!(DID::transaction_nonce(alice_public), 2);
+        assert_eq!(
+            DID::validate_unsigned(TransactionSource::External, &Call::execute_unsigned(oversized)),
+            InvalidTransaction::Stale.into()
+        );
+    });
+}
+
+#[test]
+fn keys_should_verify_signatures_by_declared_type() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let sr25519_pair = account_pair("AliceKey");
+        let ed25519_pair = ed25519::Pair::from_string("//AliceKey", None).unwrap();
+        let msg = b"I am Alice".to_vec();
+
+        assert_noop!(
+            DID::add_key(Origin::signed(alice_public), alice_public, VerificationKeyType::EcdsaSecp256k1, vec![2; 32], None),
+            Error::<Test>::InvalidKey
+        );
+        assert_ok!(DID::add_key(
+            Origin::signed(alice_public),
+            alice_public,
+            VerificationKeyType::Sr25519,
+            sr25519_pair.public().encode(),
+            None
+        ));
+        assert_ok!(DID::add_key(
+            Origin::signed(alice_public),
+            alice_public,
+            VerificationKeyType::Ed25519,
+            ed25519_pair.public().encode(),
+            Some(5)
+        ));
+        assert_ok!(DID::add_key(
+            Origin::signed(alice_public),
+            alice_public,
+            VerificationKeyType::X25519,
+            vec![9; 32],
+            None
+        ));
+        assert_noop!(
+            DID::add_key(Origin::signed(alice_public), alice_public, VerificationKeyType::X25519, vec![9; 32], None),
+            Error::<Test>::KeyExists
+        );
+        assert_eq!(DID::did_record(&alice_public).verification_methods.len(), 3);
+
+        let sr25519_id = DID::key_id(VerificationKeyType::Sr25519, sr25519_pair.public().as_ref());
+        let ed25519_id = DID::key_id(VerificationKeyType::Ed25519, ed25519_pair.public().as_ref());
+        let x25519_id = DID::key_id(VerificationKeyType::X25519, &[9; 32]);
+        let sr25519_sig = sr25519_pair.sign(&msg);
+        let ed25519_sig = ed25519_pair.sign(&msg);
+
+        assert_ok!(DID::check_key_signature(&alice_public, &sr25519_id, sr25519_sig.as_ref(), &msg));
+        assert_ok!(DID::check_key_signature(&alice_public, &ed25519_id, ed25519_sig.as_ref(), &msg));
+        // A signature is only checked against the declared type of the key.
+        assert_noop!(
+            DID::check_key_signature(&alice_public, &ed25519_id, sr25519_sig.as_ref(), &msg),
+            Error::<Test>::BadSignature
+        );
+        assert_noop!(
+            DID::check_key_signature(&alice_public, &x25519_id, sr25519_sig.as_ref(), &msg),
+            Error::<Test>::KeyCannotSign
+        );
+
+        // The Ed25519 key expired.
+        System::set_block_number(6);
+        assert_noop!(
+            DID::check_key_signature(&alice_public, &ed25519_id, ed25519_sig.as_ref(), &msg),
+            Error::<Test>::UnknownKey
+        );
+    });
+}
+
+#[test]
+fn off_chain_transactions_should_be_verified_with_named_keys() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let key_pair = ed25519::Pair::from_string("//AliceKey", None).unwrap();
+        assert_ok!(DID::add_key(
+            Origin::signed(alice_public),
+            alice_public,
+            VerificationKeyType::Ed25519,
+            key_pair.public().encode(),
+            None
+        ));
+        let key_id = DID::key_id(VerificationKeyType::Ed25519, key_pair.public().as_ref());
+        let key_signed_transaction = |key_id: [u8; 32], nonce| {
+            let mut transaction = AttributeTransaction {
+                signature: Default::default(),
+                name: b"MyAttribute".to_vec(),
+                value: [1, 2, 3].to_vec(),
+                validity: 100,
+                signer: alice_public,
+                identity: alice_public,
+                nonce,
+                expiry: 10,
+                key_signature: Some((key_id, vec![])),
+            };
+            let signature = key_pair.sign(&DID::signing_payload(&transaction));
+            transaction.key_signature = Some((key_id, signature.as_ref().to_vec()));
+            transaction
+        };
+
+        // The transaction is not signed by the account of the signer.
+        assert_ok!(DID::execute(Origin::signed(account_key("Bob")), key_signed_transaction(key_id, 0)));
+        assert_ok!(DID::valid_attribute(&alice_public, b"MyAttribute", &[1, 2, 3]));
+
+        let mut tampered = key_signed_transaction(key_id, 1);
+        tampered.value = [6, 6, 6].to_vec();
+        assert_noop!(
+            DID::execute(Origin::signed(account_key("Bob")), tampered),
+            Error::<Test>::BadSignature
+        );
+        assert_eq!(
+            DID::validate_unsigned(
+                TransactionSource::External,
+                &Call::execute_unsigned(key_signed_transaction([0; 32], 1)),
+            ),
+            InvalidTransaction::BadProof.into()
+        );
+
+        // A revoked key no longer signs.
+        assert_ok!(DID::revoke_key(Origin::signed(alice_public), alice_public, key_id));
+        assert_noop!(
+            DID::execute(Origin::signed(account_key("Bob")), key_signed_transaction(key_id, 1)),
+            Error::<Test>::UnknownKey
+        );
+    });
+}
+
+#[test]
+fn keys_should_be_rotated_atomically() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let old_key = account_key("OldKey");
+        let new_key = account_key("NewKey");
+        let old_id = DID::key_id(VerificationKeyType::Sr25519, old_key.as_ref());
+        let new_id = DID::key_id(VerificationKeyType::Sr25519, new_key.as_ref());
+
+        assert_ok!(DID::add_key(
+            Origin::signed(alice_public),
+            alice_public,
+            VerificationKeyType::Sr25519,
+            old_key.encode(),
+            None
+        ));
+        // 10 + 32 bytes.
+        assert_eq!(Balances::reserved_balance(alice_public), 42);
+
+        assert_noop!(
+            DID::rotate_key(Origin::signed(account_key("Bob")), alice_public, old_id, VerificationKeyType::Sr25519, new_key.encode(), None),
+            Error::<Test>::NotOwner
+        );
+        assert_noop!(
+            DID::rotate_key(Origin::signed(alice_public), alice_public, new_id, VerificationKeyType::Sr25519, new_key.encode(), None),
+            Error::<Test>::UnknownKey
+        );
+        // A failed rotation leaves the old key in place.
+        assert_noop!(
+            DID::rotate_key(Origin::signed(alice_public), alice_public, old_id, VerificationKeyType::Sr25519, vec![1; 31], None),
+            Error::<Test>::InvalidKey
+        );
+
+        assert_ok!(DID::rotate_key(
+            Origin::signed(alice_public),
+            alice_public,
+            old_id,
+            VerificationKeyType::Sr25519,
+            new_key.encode(),
+            None
+        ));
+        assert!(DID::verification_method_of(&alice_public, &old_id).is_none());
+        assert!(DID::verification_method_of(&alice_public, &new_id).is_some());
+        assert_eq!(Balances::reserved_balance(alice_public), 42);
+
+        assert_ok!(DID::revoke_key(Origin::signed(alice_public), alice_public, new_id));
+        assert!(DID::did_record(&alice_public).verification_methods.is_empty());
+        assert_eq!(Balances::reserved_balance(alice_public), 0);
+    });
+}
+
+#[test]
+fn expired_entries_should_be_removed_and_refunded() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        let bob_public = account_key("Bob");
+
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            bob_public,
+            b"veriKey".to_vec(),
+            Some(2)
+        ));
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"MyAttribute".to_vec(),
+            [1, 2, 3].to_vec(),
+            Some(2)
+        ));
+        // Neither expires nor is queued.
+        assert_ok!(DID::add_attribute(
+            Origin::signed(alice_public),
+            alice_public,
+            b"Forever".to_vec(),
+            [1].to_vec(),
+            None
+        ));
+        assert_eq!(DID::expiry_queue(3).len(), 2);
+        assert_eq!(Balances::reserved_balance(alice_public), 17 + 24 + 18);
+
+        System::set_block_number(3);
+        DID::on_initialize(3);
+        assert!(DID::delegate_of(&alice_public, (b"veriKey".to_vec(), bob_public)).is_none());
+        assert!(DID::attribute_and_id(&alice_public, b"MyAttribute").is_none());
+        assert!(DID::attribute_and_id(&alice_public, b"Forever").is_some());
+        assert!(!crate::ExpiryQueue::<Test>::contains_key(3));
+        assert_eq!(Balances::reserved_balance(alice_public), 18);
+    });
+}
+
+#[test]
+fn expiries_should_be_bounded_per_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice_public = account_key("Alice");
+        for name in &["A", "B", "C"] {
+            assert_ok!(DID::add_attribute(
+                Origin::signed(alice_public),
+                alice_public,
+                name.as_bytes().to_vec(),
+                [1].to_vec(),
+                Some(2)
+            ));
+        }
+
+        // A renewed delegate is skipped when its first expiry comes.
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            account_key("Bob"),
+            b"veriKey".to_vec(),
+            Some(1)
+        ));
+        System::set_block_number(2);
+        assert_ok!(DID::add_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            account_key("Bob"),
+            b"veriKey".to_vec(),
+            None
+        ));
+        DID::on_initialize(2);
+        assert!(DID::delegate_of(&alice_public, (b"veriKey".to_vec(), account_key("Bob"))).is_some());
+
+        // Only two of the three attributes are removed, the third is postponed.
+        DID::on_initialize(3);
+        assert_eq!(DID::attributes(&alice_public, 0, 10).len(), 1);
+        assert_eq!(DID::expiry_queue(4).len(), 1);
+        DID::on_initialize(4);
+        assert!(DID::attributes(&alice_public, 0, 10).is_empty());
+
+        // Revoked entries are removed from the next block.
+        assert_ok!(DID::revoke_delegate(
+            Origin::signed(alice_public),
+            alice_public,
+            b"veriKey".to_vec(),
+            account_key("Bob")
+        ));
+        assert_eq!(DID::delegates(&alice_public, 0, 10).len(), 1);
+        DID::on_initialize(3);
+        assert!(DID::delegates(&alice_public, 0, 10).is_empty());
+    });
+}
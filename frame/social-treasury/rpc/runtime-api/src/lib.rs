@@ -0,0 +1,162 @@
+//! Runtime API definition for social treasury module.
+//!
+//! Exposes the pots of every mission together with the proposals, tips and bounties of the
+//! social treasury, so that clients need not iterate the pallet storage themselves.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// The pot of a mission.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct MissionPot<MissionTokenId, Balance, BlockNumber> {
+	/// The mission the pot belongs to.
+	pub mission_token_id: MissionTokenId,
+	/// The amount available for spending.
+	pub pot: Balance,
+	/// The block at which approved spends are next paid out of the pot.
+	pub next_spend_at: BlockNumber,
+}
+
+/// A spending proposal, pending or approved.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ProposalInfo<AccountId, Balance, MissionTokenId> {
+	/// The index of the proposal.
+	pub index: u32,
+	/// The account proposing it.
+	pub proposer: AccountId,
+	/// The amount that should be paid if the proposal is accepted.
+	pub value: Balance,
+	/// The account to whom the payment should be made.
+	pub beneficiary: AccountId,
+	/// The amount held on deposit for making this proposal.
+	pub bond: Balance,
+	/// Whether the proposal is approved and waiting for the next spend period.
+	pub approved: bool,
+	/// The mission whose pot pays the proposal.
+	pub mission_token_id: MissionTokenId,
+}
+
+/// An open tip.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct TipInfo<AccountId, Balance, BlockNumber, Hash, MissionTokenId> {
+	/// The hash identifying the tip.
+	pub hash: Hash,
+	/// The reason for the tip.
+	pub reason: Vec<u8>,
+	/// The account to be tipped.
+	pub who: AccountId,
+	/// The account who began this tip.
+	pub finder: AccountId,
+	/// The amount held on deposit for this tip.
+	pub deposit: Balance,
+	/// The block at which this tip closes, if scheduled.
+	pub closes: Option<BlockNumber>,
+	/// The number of current tippers who have voted for this tip.
+	pub tippers: u32,
+	/// The median of the tips given so far, if any.
+	pub median: Option<Balance>,
+	/// The mission whose pot pays the tip.
+	pub mission_token_id: MissionTokenId,
+}
+
+/// The status of a bounty.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum BountyStatusInfo<AccountId, BlockNumber> {
+	/// The bounty is proposed and waiting for approval.
+	Proposed,
+	/// The bounty is approved and waiting to become active at next spend period.
+	Approved,
+	/// The bounty is funded and waiting for curator assignment.
+	Funded,
+	/// A curator has been proposed. Waiting for acceptance from the curator.
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	CuratorProposed {
+		/// The assigned curator of this bounty.
+		curator: AccountId,
+	},
+	/// The bounty is active and waiting to be awarded.
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	Active {
+		/// The curator of this bounty.
+		curator: AccountId,
+		/// An update from the curator is due by this block.
+		update_due: BlockNumber,
+	},
+	/// The bounty is awarded and waiting to released after a delay.
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	PendingPayout {
+		/// The curator of this bounty.
+		curator: AccountId,
+		/// The beneficiary of the bounty.
+		beneficiary: AccountId,
+		/// When the bounty can be claimed.
+		unlock_at: BlockNumber,
+	},
+}
+
+/// A bounty with its description.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct BountyInfo<AccountId, Balance, BlockNumber, MissionTokenId> {
+	/// The index of the bounty.
+	pub index: u32,
+	/// The account proposing it.
+	pub proposer: AccountId,
+	/// The amount that should be paid if the bounty is rewarded.
+	pub value: Balance,
+	/// The curator fee. Included in value.
+	pub fee: Balance,
+	/// The deposit of curator.
+	pub curator_deposit: Balance,
+	/// The amount held on deposit for making this proposal.
+	pub bond: Balance,
+	/// The status of this bounty.
+	pub status: BountyStatusInfo<AccountId, BlockNumber>,
+	/// The description of this bounty.
+	pub description: Vec<u8>,
+	/// The mission whose pot funds the bounty.
+	pub mission_token_id: MissionTokenId,
+}
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the social treasury.
+	pub trait SocialTreasuryApi<AccountId, Balance, BlockNumber, Hash, MissionTokenId> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+		Hash: Codec,
+		MissionTokenId: Codec,
+	{
+		/// The pot and next spend block of every mission.
+		fn pots() -> Vec<MissionPot<MissionTokenId, Balance, BlockNumber>>;
+
+		/// Pending and approved proposals of the given mission, or of every mission if `None`.
+		fn proposals(
+			mission_token_id: Option<MissionTokenId>,
+		) -> Vec<ProposalInfo<AccountId, Balance, MissionTokenId>>;
+
+		/// Open tips of the given mission, or of every mission if `None`.
+		fn tips(
+			mission_token_id: Option<MissionTokenId>,
+		) -> Vec<TipInfo<AccountId, Balance, BlockNumber, Hash, MissionTokenId>>;
+
+		/// Bounties of the given mission, or of every mission if `None`.
+		fn bounties(
+			mission_token_id: Option<MissionTokenId>,
+		) -> Vec<BountyInfo<AccountId, Balance, BlockNumber, MissionTokenId>>;
+	}
+}
@@ -0,0 +1,150 @@
+//! RPC interface for the social treasury module.
+
+use std::sync::Arc;
+use codec::Codec;
+use sp_blockchain::HeaderBackend;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_api::ProvideRuntimeApi;
+use pallet_social_treasury_rpc_runtime_api::{BountyInfo, MissionPot, ProposalInfo, TipInfo};
+pub use pallet_social_treasury_rpc_runtime_api::SocialTreasuryApi as SocialTreasuryRuntimeApi;
+pub use self::gen_client::Client as SocialTreasuryClient;
+
+#[rpc]
+pub trait SocialTreasuryApi<BlockHash, AccountId, Balance, BlockNumber, Hash, MissionTokenId> {
+	/// The pot and next spend block of every mission.
+	#[rpc(name = "socialTreasury_pots")]
+	fn pots(
+		&self,
+		at: Option<BlockHash>
+	) -> Result<Vec<MissionPot<MissionTokenId, Balance, BlockNumber>>>;
+
+	/// Pending and approved proposals of the given mission, or of every mission.
+	#[rpc(name = "socialTreasury_proposals")]
+	fn proposals(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<BlockHash>
+	) -> Result<Vec<ProposalInfo<AccountId, Balance, MissionTokenId>>>;
+
+	/// Open tips of the given mission, or of every mission, with their median so far.
+	#[rpc(name = "socialTreasury_tips")]
+	fn tips(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<BlockHash>
+	) -> Result<Vec<TipInfo<AccountId, Balance, BlockNumber, Hash, MissionTokenId>>>;
+
+	/// Bounties of the given mission, or of every mission, with their status and description.
+	#[rpc(name = "socialTreasury_bounties")]
+	fn bounties(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<BlockHash>
+	) -> Result<Vec<BountyInfo<AccountId, Balance, BlockNumber, MissionTokenId>>>;
+}
+
+/// A struct that implements the [`SocialTreasuryApi`].
+pub struct SocialTreasury<C, P> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> SocialTreasury<C, P> {
+	/// Create new `SocialTreasury` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		SocialTreasury { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: "Unable to query social treasury.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber, Hash, MissionTokenId>
+	SocialTreasuryApi<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber, Hash, MissionTokenId>
+	for SocialTreasury<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: SocialTreasuryRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash, MissionTokenId>,
+	AccountId: Codec,
+	Balance: Codec,
+	BlockNumber: Codec,
+	Hash: Codec,
+	MissionTokenId: Codec,
+{
+	fn pots(
+		&self,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<MissionPot<MissionTokenId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.pots(&at).map_err(runtime_error)
+	}
+
+	fn proposals(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<ProposalInfo<AccountId, Balance, MissionTokenId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.proposals(&at, mission_token_id).map_err(runtime_error)
+	}
+
+	fn tips(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<TipInfo<AccountId, Balance, BlockNumber, Hash, MissionTokenId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.tips(&at, mission_token_id).map_err(runtime_error)
+	}
+
+	fn bounties(
+		&self,
+		mission_token_id: Option<MissionTokenId>,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<BountyInfo<AccountId, Balance, BlockNumber, MissionTokenId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.bounties(&at, mission_token_id).map_err(runtime_error)
+	}
+}
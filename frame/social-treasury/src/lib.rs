@@ -2,23 +2,33 @@
 
 use codec::{Decode, Encode};
 use frame_support::dispatch::DispatchResultWithPostInfo;
-use frame_support::traits::{Contains, ContainsLengthBound, EnsureOrigin};
+use frame_support::traits::{Contains, ContainsLengthBound, EnsureOrigin, Filter};
 use frame_support::traits::{
     ExistenceRequirement::{AllowDeath, KeepAlive},
     Get, Imbalance, OnUnbalanced, WithdrawReason,
 };
 use frame_support::weights::{DispatchClass, Weight};
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, print, Parameter};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, print,
+    storage::{IterableStorageDoubleMap, IterableStorageMap},
+    transactional, Parameter,
+};
 use frame_system::{self as system, ensure_signed};
-use pallet_staking::EraIndex;
+use pallet_social_treasury_rpc_runtime_api::{
+    BountyInfo, BountyStatusInfo, MissionPot, ProposalInfo, TipInfo,
+};
+use pallet_staking::{EraIndex, RewardPoint};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_runtime::traits::UniqueSaturatedInto;
+use sp_runtime::traits::{AtLeast32BitUnsigned, UniqueSaturatedInto};
 use sp_runtime::{
-    traits::{BadOrigin, Hash, Saturating, StaticLookup, Zero},
-    DispatchResult, Percent, Permill, RuntimeDebug,
+    traits::{
+        AccountIdConversion, BadOrigin, Convert, Hash, IntegerSquareRoot, One, Saturating,
+        StaticLookup, Zero,
+    },
+    DispatchError, DispatchResult, Perbill, Percent, Permill, RuntimeDebug,
 };
-use sp_std::prelude::*;
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
 #[cfg(test)]
 mod mock;
@@ -52,8 +62,32 @@ pub trait WeightInfo {
     fn close_bounty_proposed() -> Weight;
     fn close_bounty_active() -> Weight;
     fn extend_bounty_expiry() -> Weight;
+    fn set_bounty_milestones(m: u32) -> Weight;
+    fn release_milestone() -> Weight;
+    fn add_child_bounty(d: u32) -> Weight;
+    fn propose_child_curator() -> Weight;
+    fn accept_child_curator() -> Weight;
+    fn award_child_bounty() -> Weight;
+    fn claim_child_bounty() -> Weight;
+    fn close_child_bounty() -> Weight;
+    fn propose_stream() -> Weight;
+    fn approve_stream() -> Weight;
+    fn claim_stream() -> Weight;
+    fn cancel_stream_proposed() -> Weight;
+    fn cancel_stream_active() -> Weight;
+    fn open_round() -> Weight;
+    fn register_project() -> Weight;
+    fn contribute() -> Weight;
+    fn close_round(p: u32, c: u32) -> Weight;
     fn on_initialize_proposals(p: u32) -> Weight;
+    fn escalate_proposal() -> Weight;
+    fn open_mission_vote() -> Weight;
+    fn vote_on_proposal() -> Weight;
+    fn close_mission_vote(v: u32) -> Weight;
     fn on_initialize_bounties(b: u32) -> Weight;
+    fn on_initialize_streams(s: u32) -> Weight;
+    fn on_initialize_era_rewards(v: u32, n: u32) -> Weight;
+    fn on_initialize_era_minting(m: u32) -> Weight;
 }
 
 pub trait Trait:
@@ -123,13 +157,85 @@ pub trait Trait:
     /// Maximum acceptable reason length.
     type MaximumReasonLength: Get<u32>;
 
+    /// Maximum number of milestones of a bounty.
+    type MaxBountyMilestones: Get<u32>;
+
+    /// Maximum number of child bounties of a bounty that are not yet claimed or closed.
+    type MaxActiveChildBounties: Get<u32>;
+
     /// Handler for the unbalanced decrease when treasury funds are burned.
     type BurnDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
+    /// Converts the reward points a mission earned in an era into the amount of mission tokens
+    /// minted to its pot.
+    type MissionRewardCurve: Convert<RewardPoint, BalanceOf<Self>>;
+
+    /// The maximum number of validators, rewarded nominators and missions processed per block
+    /// when minting era rewards.
+    type MaxEraRewardsPerBlock: Get<u32>;
+
+    /// The maximum number of projects registered in a funding round.
+    type MaxRoundProjects: Get<u32>;
+
+    /// The maximum number of contributions an account can make in a funding round.
+    type MaxRoundContributions: Get<u32>;
+
+    /// The maximum number of accounts contributing to a funding round.
+    type MaxRoundContributors: Get<u32>;
+
+    /// Filter for the accounts allowed to contribute to funding rounds.
+    type ContributorFilter: Filter<Self::AccountId>;
+
+    /// The period after which a spend proposal the approving origin has not acted on can be
+    /// escalated to a referendum.
+    type EscalationPeriod: Get<Self::BlockNumber>;
+
+    /// Starts the referendum on an escalated spend proposal.
+    type Escalate: EscalateProposal<Self::AccountId>;
+
+    /// The duration of a mission-token-holder vote on a spend proposal.
+    type MissionVotingPeriod: Get<Self::BlockNumber>;
+
+    /// The minimum turnout of a mission vote, as a fraction of the issuance of the mission token.
+    type MissionVoteQuorum: Get<Perbill>;
+
+    /// The maximum number of voters in a mission vote.
+    type MaxMissionVoters: Get<u32>;
+
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
 
+/// An index of a referendum, as in `pallet_democracy`.
+pub type ReferendumIndex = u32;
+
+/// Starts a public referendum approving a spend proposal the approving origin did not act on.
+pub trait EscalateProposal<AccountId> {
+    /// Start a referendum whose enactment approves `proposal_id`, returning its index. Any
+    /// deposit the referendum requires is paid by `who`, the account escalating the proposal.
+    fn escalate(who: &AccountId, proposal_id: ProposalIndex) -> Result<ReferendumIndex, DispatchError>;
+}
+
+impl<AccountId> EscalateProposal<AccountId> for () {
+    fn escalate(_who: &AccountId, _proposal_id: ProposalIndex) -> Result<ReferendumIndex, DispatchError> {
+        Err(DispatchError::Other("Escalation is not supported"))
+    }
+}
+
+/// A `MissionRewardCurve` minting `Rate` of `Unit` mission tokens per reward point.
+pub struct RewardPointsRate<Rate, Unit>(PhantomData<(Rate, Unit)>);
+
+impl<Balance, Rate, Unit> Convert<RewardPoint, Balance> for RewardPointsRate<Rate, Unit>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    Rate: Get<Perbill>,
+    Unit: Get<Balance>,
+{
+    fn convert(points: RewardPoint) -> Balance {
+        Rate::get() * Unit::get().saturating_mul(points.into())
+    }
+}
+
 /// An index of a proposal. Just a `u32`.
 pub type ProposalIndex = u32;
 
@@ -148,6 +254,21 @@ pub struct Proposal<AccountId, Balance, MissionTokenId> {
     mission_token_id: MissionTokenId,
 }
 
+/// A vote of the holders of a mission token on a spend proposal from the mission's pot.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MissionVote<Balance, BlockNumber, MissionTokenId> {
+    /// The mission whose token holders vote.
+    mission_token_id: MissionTokenId,
+    /// The block at which the vote ends.
+    end: BlockNumber,
+    /// The mission tokens locked in favour of the proposal.
+    ayes: Balance,
+    /// The mission tokens locked against the proposal.
+    nays: Balance,
+    /// The number of voters.
+    voters: u32,
+}
+
 /// An open tipping "motion". Retains all details of a tip including information on the finder
 /// and the members who have voted.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
@@ -230,11 +351,151 @@ pub enum BountyStatus<AccountId, BlockNumber> {
     },
 }
 
+/// An index of a stream. Just a `u32`.
+pub type StreamIndex = u32;
+
+/// A recurring grant, paying a fixed amount per interval for a number of periods.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Stream<AccountId, Balance, BlockNumber, MissionTokenId> {
+    /// The account proposing it.
+    proposer: AccountId,
+    /// The account to whom the payments should be made.
+    beneficiary: AccountId,
+    /// The amount paid per interval.
+    value: Balance,
+    /// The length of each period.
+    interval: StreamInterval,
+    /// The number of periods the stream pays for.
+    periods: u32,
+    /// The amount held on deposit (reserved) for making this proposal.
+    bond: Balance,
+    /// The amount already claimed by the beneficiary.
+    claimed: Balance,
+    /// The status of this stream.
+    status: StreamStatus<BlockNumber>,
+    mission_token_id: MissionTokenId,
+}
+
+/// The length of a stream period.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum StreamInterval {
+    /// One payment per `SpendPeriod`.
+    SpendPeriod,
+    /// One payment per block.
+    Block,
+}
+
+/// The status of a stream.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum StreamStatus<BlockNumber> {
+    /// The stream is proposed and waiting for approval.
+    Proposed,
+    /// The stream is approved and waiting to be funded at next spend period.
+    Approved,
+    /// The stream is funded and accruing payments.
+    Active {
+        /// The block at which the first period started.
+        start: BlockNumber,
+    },
+}
+
+/// An index of a funding round. Just a `u32`.
+pub type RoundIndex = u32;
+
+/// An index of a project within a funding round. Just a `u32`.
+pub type ProjectIndex = u32;
+
+/// A quadratic-funding round distributing matching funds from a mission pot.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct FundingRound<Balance, BlockNumber, MissionTokenId> {
+    /// The matching amount taken from the mission pot.
+    matching: Balance,
+    /// The block at which the round stops accepting projects and contributions.
+    end: BlockNumber,
+    /// The number of projects registered in this round.
+    project_count: ProjectIndex,
+    /// The number of accounts that contributed to this round.
+    contributors: u32,
+    mission_token_id: MissionTokenId,
+}
+
+/// A project registered in a funding round.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RoundProject<AccountId, Balance> {
+    /// The account receiving the contributions and matching funds.
+    owner: AccountId,
+    /// The sum of all contributions.
+    contributed: Balance,
+    /// The sum of the square roots of each account's total contribution.
+    sqrt_sum: Balance,
+}
+
+/// A milestone of an active bounty.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Milestone<Balance, BlockNumber> {
+    /// The amount paid when the milestone is released.
+    value: Balance,
+    /// The block by which the milestone is due. The curator is considered inactive if the
+    /// milestone is not released by then.
+    due: BlockNumber,
+    /// Whether the milestone has been released.
+    released: bool,
+}
+
+/// A child bounty, carved out of an active parent bounty by its curator.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ChildBounty<AccountId, Balance, BlockNumber> {
+    /// The (total) amount that should be paid if the child bounty is rewarded.
+    value: Balance,
+    /// The child curator fee. Included in value and taken from the parent curator fee.
+    fee: Balance,
+    /// The deposit of the child curator.
+    curator_deposit: Balance,
+    /// The status of this child bounty.
+    status: ChildBountyStatus<AccountId, BlockNumber>,
+}
+
+/// The status of a child bounty.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum ChildBountyStatus<AccountId, BlockNumber> {
+    /// The child bounty is funded and waiting for curator assignment.
+    Added,
+    /// A curator has been proposed by the parent curator. Waiting for acceptance from the curator.
+    CuratorProposed {
+        /// The assigned curator of this child bounty.
+        curator: AccountId,
+    },
+    /// The child bounty is active and waiting to be awarded.
+    Active {
+        /// The curator of this child bounty.
+        curator: AccountId,
+    },
+    /// The child bounty is awarded and waiting to released after a delay.
+    PendingPayout {
+        /// The curator of this child bounty.
+        curator: AccountId,
+        /// The beneficiary of the child bounty.
+        beneficiary: AccountId,
+        /// When the child bounty can be claimed.
+        unlock_at: BlockNumber,
+    },
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as SocialTreasury {
         Something get(fn something): Option<u32>;
+        /// The next era whose reward points are minted to the mission pots.
         NextEraForProcessing get(fn next_era_for_processing): Option<EraIndex>;
 
+        /// Number of validators of `NextEraForProcessing` whose reward points have already been
+        /// split between missions.
+        EraRewardsProcessed get(fn era_rewards_processed): u32;
+
+        /// Reward points accumulated by each mission so far in `NextEraForProcessing`.
+        EraMissionPoints get(fn era_mission_points):
+            map hasher(twox_64_concat) TokenId<T> => RewardPoint;
+
         /// Number of proposals that have been made.
         ProposalCount get(fn proposal_count): ProposalIndex;
 
@@ -246,6 +507,24 @@ decl_storage! {
         /// Proposal indices that have been approved but not yet awarded.
         Approvals get(fn approvals): Vec<ProposalIndex>;
 
+        /// The block at which each pending proposal was made.
+        ProposalSubmittedAt get(fn proposal_submitted_at):
+            map hasher(twox_64_concat) ProposalIndex => Option<T::BlockNumber>;
+
+        /// The referendum started for each escalated proposal.
+        pub EscalatedProposals get(fn escalated_proposals):
+            map hasher(twox_64_concat) ProposalIndex => Option<ReferendumIndex>;
+
+        /// Mission-token-holder votes on spend proposals.
+        pub MissionVotes get(fn mission_votes):
+            map hasher(twox_64_concat) ProposalIndex
+            => Option<MissionVote<BalanceOf<T>, T::BlockNumber, TokenId<T>>>;
+
+        /// Whether each voter in a mission vote is in favour, and the mission tokens it locked.
+        pub MissionVoteOf get(fn mission_vote_of):
+            double_map hasher(twox_64_concat) ProposalIndex, hasher(blake2_128_concat) T::AccountId
+            => Option<(bool, BalanceOf<T>)>;
+
         /// Tips that are not yet completed. Keyed by the hash of `(reason, who)` from the value.
         /// This has the insecure enumerable hash function since the key itself is already
         /// guaranteed to be a secure hash.
@@ -270,6 +549,61 @@ decl_storage! {
 
         /// Bounty indices that have been approved but not yet funded.
         pub BountyApprovals get(fn bounty_approvals): Vec<BountyIndex>;
+
+        /// Milestones of each active bounty, released by the curator in order.
+        pub BountyMilestones get(fn bounty_milestones):
+            map hasher(twox_64_concat) BountyIndex => Vec<Milestone<BalanceOf<T>, T::BlockNumber>>;
+
+        /// Number of child bounties that have been added to each parent bounty.
+        pub ChildBountyCount get(fn child_bounty_count): map hasher(twox_64_concat) BountyIndex => BountyIndex;
+
+        /// Number of child bounties of each parent bounty that are not yet claimed or closed.
+        pub ActiveChildBountyCount get(fn active_child_bounty_count):
+            map hasher(twox_64_concat) BountyIndex => u32;
+
+        /// Child bounties of each parent bounty.
+        pub ChildBounties get(fn child_bounties):
+            double_map hasher(twox_64_concat) BountyIndex, hasher(twox_64_concat) BountyIndex
+            => Option<ChildBounty<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+        /// The description of each child bounty.
+        pub ChildBountyDescriptions get(fn child_bounty_descriptions):
+            double_map hasher(twox_64_concat) BountyIndex, hasher(twox_64_concat) BountyIndex
+            => Option<Vec<u8>>;
+
+        /// Number of streams that have been proposed.
+        pub StreamCount get(fn stream_count): StreamIndex;
+
+        /// Streams that have been proposed and are not yet fully paid or cancelled.
+        pub Streams get(fn streams):
+            map hasher(twox_64_concat) StreamIndex
+            => Option<Stream<T::AccountId, BalanceOf<T>, T::BlockNumber, TokenId<T>>>;
+
+        /// Stream indices that have been approved but not yet funded.
+        pub StreamApprovals get(fn stream_approvals): Vec<StreamIndex>;
+
+        /// Number of funding rounds that have been opened.
+        pub RoundCount get(fn round_count): RoundIndex;
+
+        /// Funding rounds that have not been closed yet.
+        pub Rounds get(fn rounds):
+            map hasher(twox_64_concat) RoundIndex
+            => Option<FundingRound<BalanceOf<T>, T::BlockNumber, TokenId<T>>>;
+
+        /// Projects registered in each open funding round.
+        pub RoundProjects get(fn round_projects):
+            double_map hasher(twox_64_concat) RoundIndex, hasher(twox_64_concat) ProjectIndex
+            => Option<RoundProject<T::AccountId, BalanceOf<T>>>;
+
+        /// Total contribution of an account to a project of an open funding round.
+        pub RoundContributions get(fn round_contributions):
+            double_map hasher(twox_64_concat) RoundIndex, hasher(blake2_128_concat) (ProjectIndex, T::AccountId)
+            => BalanceOf<T>;
+
+        /// Number of contributions an account made in an open funding round.
+        pub RoundContributionCount get(fn round_contribution_count):
+            double_map hasher(twox_64_concat) RoundIndex, hasher(blake2_128_concat) T::AccountId
+            => u32;
     }
 }
 
@@ -289,6 +623,14 @@ decl_event!(
         Awarded(ProposalIndex, TokenId, Balance, AccountId),
         /// A proposal was rejected; funds were slashed. \[proposal_index, slashed\]
         Rejected(ProposalIndex, TokenId, Balance),
+        /// A proposal was escalated to a referendum. \[proposal_index, referendum_index\]
+        ProposalEscalated(ProposalIndex, ReferendumIndex),
+        /// Mission token holders started voting on a proposal. \[proposal_index, mission\]
+        MissionVoteStarted(ProposalIndex, TokenId),
+        /// A mission token holder voted on a proposal. \[proposal_index, voter, aye, locked\]
+        MissionVoted(ProposalIndex, AccountId, bool, Balance),
+        /// A mission vote was closed. \[proposal_index, approved\]
+        MissionVoteClosed(ProposalIndex, bool),
         /// Some of our funds have been burnt. \[burn\]
         Burnt(TokenId, Balance),
         /// Spending has finished; this is the amount that rolls over until next spend.
@@ -318,6 +660,41 @@ decl_event!(
         BountyCanceled(BountyIndex),
         /// A bounty expiry is extended. [index]
         BountyExtended(BountyIndex),
+        /// The milestones of a bounty were set. [index]
+        BountyMilestonesSet(BountyIndex),
+        /// A bounty milestone was released. [index, milestone, payout, beneficiary]
+        MilestoneReleased(BountyIndex, u32, TokenId, Balance, AccountId),
+        /// A child bounty was added. [parent_index, child_index]
+        ChildBountyAdded(BountyIndex, BountyIndex),
+        /// A child bounty is awarded to a beneficiary. [parent_index, child_index, beneficiary]
+        ChildBountyAwarded(BountyIndex, BountyIndex, AccountId),
+        /// A child bounty is claimed by beneficiary. [parent_index, child_index, payout, beneficiary]
+        ChildBountyClaimed(BountyIndex, BountyIndex, TokenId, Balance, AccountId),
+        /// A child bounty is cancelled; its funds returned to the parent. [parent_index, child_index]
+        ChildBountyCanceled(BountyIndex, BountyIndex),
+        /// New stream proposal. [index]
+        StreamProposed(StreamIndex),
+        /// A stream proposal was rejected; funds were slashed. [index, bond]
+        StreamRejected(StreamIndex, TokenId, Balance),
+        /// A stream is funded and started accruing payments. [index]
+        StreamBecameActive(StreamIndex),
+        /// Accrued stream payments were claimed. [index, payout, beneficiary]
+        StreamClaimed(StreamIndex, TokenId, Balance, AccountId),
+        /// A stream is cancelled; the unpaid remainder returned to the pot. [index, remainder]
+        StreamCanceled(StreamIndex, TokenId, Balance),
+        /// A funding round was opened. [index, matching]
+        RoundOpened(RoundIndex, TokenId, Balance),
+        /// A project was registered in a funding round. [round, project, owner]
+        ProjectRegistered(RoundIndex, ProjectIndex, AccountId),
+        /// A contribution was made to a project. [round, project, contributor, value]
+        Contributed(RoundIndex, ProjectIndex, AccountId, Balance),
+        /// A project received its contributions and matching funds. [round, project, payout, owner]
+        ProjectFunded(RoundIndex, ProjectIndex, TokenId, Balance, AccountId),
+        /// A funding round was closed; unused matching funds returned to the pot.
+        /// [index, remainder]
+        RoundClosed(RoundIndex, TokenId, Balance),
+        /// Era rewards have been minted to a mission pot. [era, mission, amount]
+        MissionMinted(EraIndex, TokenId, Balance),
     }
 );
 
@@ -332,6 +709,20 @@ decl_error! {
         InsufficientProposersBalance,
         /// No proposal or bounty at that index.
         InvalidIndex,
+        /// The proposal is already approved.
+        AlreadyApproved,
+        /// The proposal was already escalated to a referendum.
+        AlreadyEscalated,
+        /// The proposal was made before escalation was introduced, so it cannot be escalated.
+        NotEscalatable,
+        /// Mission token holders are already voting on the proposal.
+        VoteInProgress,
+        /// There is no mission vote on the proposal.
+        NoMissionVote,
+        /// The mission vote has ended.
+        VoteEnded,
+        /// The mission vote has `MaxMissionVoters` voters.
+        TooManyVoters,
         /// The reason given is just too big.
         ReasonTooBig,
         /// The tip was already found/started.
@@ -355,11 +746,43 @@ decl_error! {
         /// A bounty payout is pending.
         /// To cancel the bounty, you must unassign and slash the curator.
         PendingPayout,
+        /// The bounty has more than `MaxBountyMilestones` milestones.
+        TooManyMilestones,
+        /// Milestones are already being released.
+        MilestonesReleased,
+        /// Milestone due blocks must be in the future and strictly ascending.
+        InvalidMilestoneDue,
+        /// There is no milestone left to release.
+        NoMilestone,
+        /// The bounty still has child bounties that are not claimed or closed.
+        HasActiveChildBounties,
+        /// The bounty already has `MaxActiveChildBounties` active child bounties.
+        TooManyChildBounties,
+        /// A stream must pay a non-zero amount for at least one period.
+        InvalidStream,
+        /// No stream payments have accrued since the last claim.
+        NothingToClaim,
+        /// The mission pot cannot cover the matching amount.
+        InsufficientPot,
+        /// The funding round has ended.
+        RoundEnded,
+        /// The funding round already has `MaxRoundProjects` projects.
+        TooManyProjects,
+        /// The account already made `MaxRoundContributions` contributions in the round.
+        TooManyContributions,
+        /// The funding round already has `MaxRoundContributors` contributors.
+        TooManyContributors,
+        /// The account is not allowed to contribute to funding rounds.
+        ContributorNotAllowed,
     }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        /// The maximum number of validators, rewarded nominators and missions processed per block
+        /// when minting era rewards.
+        const MaxEraRewardsPerBlock: u32 = <T as Trait>::MaxEraRewardsPerBlock::get();
+
         type Error = Error<T>;
 
         fn deposit_event() = default;
@@ -391,6 +814,7 @@ decl_module! {
             let c = Self::proposal_count();
             <ProposalCount>::put(c + 1);
             <Proposals<T>>::insert(c, Proposal { proposer, value, beneficiary, bond, mission_token_id: token_id });
+            <ProposalSubmittedAt<T>>::insert(c, system::Module::<T>::block_number());
 
             Self::deposit_event(RawEvent::Proposed(c));
         }
@@ -409,6 +833,8 @@ decl_module! {
             <T as Trait>::RejectOrigin::ensure_origin(origin)?;
 
             let proposal = <Proposals<T>>::take(&proposal_id).ok_or(Error::<T>::InvalidIndex)?;
+            <ProposalSubmittedAt<T>>::remove(proposal_id);
+            EscalatedProposals::remove(proposal_id);
             let value = proposal.bond;
             let imbalance = <pallet_mission_tokens::Module<T>>::slash_reserved(
                 &proposal.proposer,
@@ -435,9 +861,146 @@ decl_module! {
             <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
 
             ensure!(<Proposals<T>>::contains_key(proposal_id), Error::<T>::InvalidIndex);
+            ensure!(!Self::approvals().contains(&proposal_id), Error::<T>::AlreadyApproved);
             Approvals::append(proposal_id);
         }
 
+        /// Escalate a spend proposal the approving origin has not acted on within
+        /// `EscalationPeriod` to a public referendum, whose enactment approves it. Proposals made
+        /// before escalation was introduced cannot be escalated.
+        ///
+        /// The dispatch origin for this call must be _Signed_. It pays any deposit the
+        /// referendum requires.
+        ///
+        /// - `proposal_id`: The index of the pending proposal.
+        #[weight = <T as Trait>::WeightInfo::escalate_proposal()]
+        fn escalate_proposal(origin, #[compact] proposal_id: ProposalIndex) {
+            let who = ensure_signed(origin)?;
+
+            ensure!(<Proposals<T>>::contains_key(proposal_id), Error::<T>::InvalidIndex);
+            ensure!(!Self::approvals().contains(&proposal_id), Error::<T>::AlreadyApproved);
+            ensure!(!EscalatedProposals::contains_key(proposal_id), Error::<T>::AlreadyEscalated);
+
+            let submitted_at = Self::proposal_submitted_at(proposal_id)
+                .ok_or(Error::<T>::NotEscalatable)?;
+            ensure!(
+                system::Module::<T>::block_number() >= submitted_at + <T as Trait>::EscalationPeriod::get(),
+                Error::<T>::Premature
+            );
+
+            let referendum_index = <T as Trait>::Escalate::escalate(&who, proposal_id)?;
+            EscalatedProposals::insert(proposal_id, referendum_index);
+
+            Self::deposit_event(RawEvent::ProposalEscalated(proposal_id, referendum_index));
+        }
+
+        /// Start a vote of the holders of a proposal's mission token on approving it. The vote
+        /// can only approve spends from the pot of that mission.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `proposal_id`: The index of the pending proposal.
+        #[weight = <T as Trait>::WeightInfo::open_mission_vote()]
+        fn open_mission_vote(origin, #[compact] proposal_id: ProposalIndex) {
+            let _ = ensure_signed(origin)?;
+
+            let proposal = Self::proposals(proposal_id).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(!Self::approvals().contains(&proposal_id), Error::<T>::AlreadyApproved);
+            ensure!(!<MissionVotes<T>>::contains_key(proposal_id), Error::<T>::VoteInProgress);
+
+            <MissionVotes<T>>::insert(proposal_id, MissionVote {
+                mission_token_id: proposal.mission_token_id,
+                end: system::Module::<T>::block_number() + <T as Trait>::MissionVotingPeriod::get(),
+                ayes: Zero::zero(),
+                nays: Zero::zero(),
+                voters: 0,
+            });
+
+            Self::deposit_event(RawEvent::MissionVoteStarted(proposal_id, proposal.mission_token_id));
+        }
+
+        /// Vote on a proposal in a mission vote, locking a non-zero `value` of the mission token
+        /// until the vote is closed. A previous vote of the origin is replaced.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `proposal_id`: The index of the proposal voted on.
+        /// - `aye`: Whether the vote is in favour of the proposal.
+        /// - `value`: The amount of mission tokens locked for the vote.
+        #[weight = <T as Trait>::WeightInfo::vote_on_proposal()]
+        #[transactional]
+        fn vote_on_proposal(
+            origin,
+            #[compact] proposal_id: ProposalIndex,
+            aye: bool,
+            #[compact] value: BalanceOf<T>,
+        ) {
+            let voter = ensure_signed(origin)?;
+            ensure!(!value.is_zero(), Error::<T>::InvalidValue);
+
+            let mut vote = Self::mission_votes(proposal_id).ok_or(Error::<T>::NoMissionVote)?;
+            ensure!(system::Module::<T>::block_number() < vote.end, Error::<T>::VoteEnded);
+
+            match Self::mission_vote_of(proposal_id, &voter) {
+                Some((previous_aye, previous_value)) => {
+                    let _ = <pallet_mission_tokens::Module<T>>::unreserve(&voter, vote.mission_token_id, previous_value);
+                    if previous_aye {
+                        vote.ayes = vote.ayes.saturating_sub(previous_value);
+                    } else {
+                        vote.nays = vote.nays.saturating_sub(previous_value);
+                    }
+                },
+                None => {
+                    ensure!(vote.voters < <T as Trait>::MaxMissionVoters::get(), Error::<T>::TooManyVoters);
+                    vote.voters += 1;
+                },
+            }
+
+            <pallet_mission_tokens::Module<T>>::reserve(&voter, vote.mission_token_id, value)?;
+            if aye {
+                vote.ayes = vote.ayes.saturating_add(value);
+            } else {
+                vote.nays = vote.nays.saturating_add(value);
+            }
+
+            <MissionVoteOf<T>>::insert(proposal_id, &voter, (aye, value));
+            <MissionVotes<T>>::insert(proposal_id, vote);
+
+            Self::deposit_event(RawEvent::MissionVoted(proposal_id, voter, aye, value));
+        }
+
+        /// Close an ended mission vote, unlocking the tokens of every voter. The proposal is
+        /// approved if it is still pending, the turnout reaches `MissionVoteQuorum` of the
+        /// mission token issuance and more tokens are in favour than against.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `proposal_id`: The index of the proposal voted on.
+        #[weight = <T as Trait>::WeightInfo::close_mission_vote(<T as Trait>::MaxMissionVoters::get())]
+        fn close_mission_vote(origin, #[compact] proposal_id: ProposalIndex) -> DispatchResultWithPostInfo {
+            let _ = ensure_signed(origin)?;
+
+            let vote = Self::mission_votes(proposal_id).ok_or(Error::<T>::NoMissionVote)?;
+            ensure!(system::Module::<T>::block_number() >= vote.end, Error::<T>::Premature);
+
+            for (voter, (_, value)) in <MissionVoteOf<T>>::drain_prefix(proposal_id) {
+                let _ = <pallet_mission_tokens::Module<T>>::unreserve(&voter, vote.mission_token_id, value);
+            }
+            <MissionVotes<T>>::remove(proposal_id);
+
+            let issuance = pallet_mission_tokens::TotalIssuance::<T>::get(vote.mission_token_id);
+            let approved = <Proposals<T>>::contains_key(proposal_id)
+                && !Self::approvals().contains(&proposal_id)
+                && vote.ayes > vote.nays
+                && vote.ayes.saturating_add(vote.nays) >= <T as Trait>::MissionVoteQuorum::get() * issuance;
+            if approved {
+                Approvals::append(proposal_id);
+            }
+
+            Self::deposit_event(RawEvent::MissionVoteClosed(proposal_id, approved));
+            Ok(Some(<T as Trait>::WeightInfo::close_mission_vote(vote.voters)).into())
+        }
+
         /// Report something `reason` that deserves a tip and claim any eventual the finder's fee.
         ///
         /// The dispatch origin for this call must be _Signed_.
@@ -858,6 +1421,7 @@ decl_module! {
         fn award_bounty(origin, #[compact] bounty_id: ProposalIndex, beneficiary: <T::Lookup as StaticLookup>::Source) {
             let signer = ensure_signed(origin)?;
             let beneficiary = T::Lookup::lookup(beneficiary)?;
+            ensure!(Self::active_child_bounty_count(bounty_id) == 0, Error::<T>::HasActiveChildBounties);
 
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResult {
                 let mut bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
@@ -905,6 +1469,8 @@ decl_module! {
                     *maybe_bounty = None;
 
                     BountyDescriptions::remove(bounty_id);
+                    BountyMilestones::<T>::remove(bounty_id);
+                    ChildBountyCount::remove(bounty_id);
 
                     Self::deposit_event(Event::<T>::BountyClaimed(bounty_id, bounty.mission_token_id, payout, beneficiary));
                     Ok(())
@@ -923,6 +1489,7 @@ decl_module! {
         #[weight = <T as Trait>::WeightInfo::close_bounty_proposed().max(<T as Trait>::WeightInfo::close_bounty_active())]
         fn close_bounty(origin, #[compact] bounty_id: BountyIndex) -> DispatchResultWithPostInfo {
             <T as Trait>::RejectOrigin::ensure_origin(origin)?;
+            ensure!(Self::active_child_bounty_count(bounty_id) == 0, Error::<T>::HasActiveChildBounties);
 
             Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResultWithPostInfo {
                 let bounty = maybe_bounty.as_ref().ok_or(Error::<T>::InvalidIndex)?;
@@ -967,6 +1534,8 @@ decl_module! {
                 let bounty_account = Self::bounty_account_id(bounty_id);
 
                 BountyDescriptions::remove(bounty_id);
+                BountyMilestones::<T>::remove(bounty_id);
+                ChildBountyCount::remove(bounty_id);
 
                 let balance = <pallet_mission_tokens::Module<T>>::free_balance(&bounty_account, bounty.mission_token_id);
                 let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
@@ -1010,102 +1579,868 @@ decl_module! {
             Self::deposit_event(Event::<T>::BountyExtended(bounty_id));
         }
 
-        /// # <weight>
-        /// - Complexity: `O(A)` where `A` is the number of approvals
-        /// - Db reads and writes: `Approvals`, `pot account data`
-        /// - Db reads and writes per approval:
-        ///   `Proposals`, `proposer account data`, `beneficiary account data`
-        /// - The weight is overestimated if some approvals got missed.
-        /// # </weight>
-        fn on_initialize(n: T::BlockNumber) -> Weight {
-            // Check to see if we should spend some funds!
-            if (n % <T as Trait>::SpendPeriod::get()).is_zero() {
-                Self::spend_funds()
-            } else {
-                0
-            }
-        }
+        /// Set the milestones of an active bounty as `(value, due)` pairs, in release order.
+        ///
+        /// The dispatch origin for this call must be the curator of this bounty, and no milestone
+        /// may have been released yet. The total value of the milestones must not exceed the
+        /// bounty value net of the curator fee. Due blocks must be in the future and strictly
+        /// ascending. The curator is expected to give an update by the due block of the next
+        /// milestone.
+        ///
+        /// - `bounty_id`: Bounty ID to set milestones for.
+        /// - `milestones`: The value and due block of each milestone.
+        #[weight = <T as Trait>::WeightInfo::set_bounty_milestones(milestones.len() as u32)]
+        fn set_bounty_milestones(
+            origin,
+            #[compact] bounty_id: BountyIndex,
+            milestones: Vec<(BalanceOf<T>, T::BlockNumber)>,
+        ) {
+            let signer = ensure_signed(origin)?;
+            ensure!(
+                milestones.len() <= <T as Trait>::MaxBountyMilestones::get() as usize,
+                Error::<T>::TooManyMilestones
+            );
+            ensure!(
+                !Self::bounty_milestones(bounty_id).iter().any(|m| m.released),
+                Error::<T>::MilestonesReleased
+            );
+            let now = system::Module::<T>::block_number();
+            ensure!(
+                milestones.iter().try_fold(now, |prev, (_, due)| if *due > prev { Some(*due) } else { None }).is_some(),
+                Error::<T>::InvalidMilestoneDue
+            );
 
-        fn on_finalize() {
-            match <pallet_staking::Module<T>>::current_era() {
-                None => (),
-                Some(current_era) => {
-                    let era = NextEraForProcessing::get().unwrap_or(0);
-                    if era < current_era {
-                        let reward_points = <pallet_staking::Module<T>>::eras_reward_points(era);
-                        let treasury_account_id = Self::account_id();
-
-                        for (account_id, points) in reward_points.individual {
-                            let mission_token_id = <pallet_validator_registry::Module<T>>::mission_of(account_id);
-                            if mission_token_id > 0.into() {
-                                <pallet_mission_tokens::Module<T>>::mint(
-                                    treasury_account_id.clone(),
-                                    mission_token_id,
-                                    points.into()
-                                );
-                            }
-                        }
+            Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> DispatchResult {
+                let bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
 
-                        let next_era = era + 1;
-                        NextEraForProcessing::put(next_era);
-                    }
+                match bounty.status {
+                    BountyStatus::Active { ref curator, ref mut update_due } => {
+                        ensure!(*curator == signer, Error::<T>::RequireCurator);
+
+                        let total = milestones.iter()
+                            .fold(Zero::zero(), |acc: BalanceOf<T>, (value, _)| acc.saturating_add(*value));
+                        ensure!(total <= bounty.value.saturating_sub(bounty.fee), Error::<T>::InvalidValue);
+
+                        if let Some((_, due)) = milestones.first() {
+                            *update_due = *due;
+                        }
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
                 }
-            }
+
+                Ok(())
+            })?;
+
+            BountyMilestones::<T>::insert(
+                bounty_id,
+                milestones.into_iter()
+                    .map(|(value, due)| Milestone { value, due, released: false })
+                    .collect::<Vec<_>>(),
+            );
+
+            Self::deposit_event(Event::<T>::BountyMilestonesSet(bounty_id));
         }
-    }
-}
 
-impl<T: Trait> Module<T> {
-    // Add public immutables and private mutables.
+        /// Release the next milestone of an active bounty, paying its value to `beneficiary`.
+        ///
+        /// The dispatch origin for this call must be the curator of this bounty.
+        ///
+        /// - `bounty_id`: Bounty ID whose milestone is released.
+        /// - `beneficiary`: The account receiving the milestone payout.
+        #[weight = <T as Trait>::WeightInfo::release_milestone()]
+        fn release_milestone(
+            origin,
+            #[compact] bounty_id: BountyIndex,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+        ) {
+            let signer = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
 
-    /// The account ID of the treasury pot.
-    ///
-    /// This actually does computation. If you need to keep using it, then make sure you cache the
-    /// value and only call this once.
-    pub fn account_id() -> T::AccountId {
-        <pallet_treasury::Module<T>>::account_id()
-    }
+            let mut milestones = Self::bounty_milestones(bounty_id);
+            let index = milestones.iter().position(|m| !m.released).ok_or(Error::<T>::NoMilestone)?;
+            let value = milestones[index].value;
 
-    /// The account ID of a bounty account
-    pub fn bounty_account_id(id: BountyIndex) -> T::AccountId {
-        <pallet_treasury::Module<T>>::bounty_account_id(id)
-    }
+            let token_id = Bounties::<T>::try_mutate_exists(bounty_id, |maybe_bounty| -> Result<TokenId<T>, DispatchError> {
+                let bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
 
-    /// The needed bond for a proposal whose spend is `value`.
-    fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
-        <T as Trait>::ProposalBondMinimum::get().max(<T as Trait>::ProposalBond::get() * value)
-    }
+                match bounty.status {
+                    BountyStatus::Active { ref curator, ref mut update_due } => {
+                        ensure!(*curator == signer, Error::<T>::RequireCurator);
 
-    /// Given a mutable reference to an `OpenTip`, insert the tip into it and check whether it
-    /// closes, if so, then deposit the relevant event and set closing accordingly.
-    ///
-    /// `O(T)` and one storage access.
-    fn insert_tip_and_check_closing(
-        tip: &mut OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash, TokenId<T>>,
-        tipper: T::AccountId,
-        tip_value: BalanceOf<T>,
-    ) -> bool {
-        match tip.tips.binary_search_by_key(&&tipper, |x| &x.0) {
-            Ok(pos) => tip.tips[pos] = (tipper, tip_value),
-            Err(pos) => tip.tips.insert(pos, (tipper, tip_value)),
-        }
-        Self::retain_active_tips(&mut tip.tips);
-        let threshold = (<T as Trait>::Tippers::count() + 1) / 2;
-        if tip.tips.len() >= threshold && tip.closes.is_none() {
-            tip.closes =
-                Some(system::Module::<T>::block_number() + <T as Trait>::TipCountdown::get());
-            true
-        } else {
-            false
+                        <pallet_mission_tokens::Module<T>>::do_transfer(
+                            &Self::bounty_account_id(bounty_id),
+                            &beneficiary,
+                            bounty.mission_token_id,
+                            value,
+                            AllowDeath,
+                        )?;
+
+                        *update_due = milestones.get(index + 1).map(|m| m.due).unwrap_or_else(|| {
+                            system::Module::<T>::block_number() + <T as Trait>::BountyUpdatePeriod::get()
+                        });
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                }
+                bounty.value = bounty.value.saturating_sub(value);
+
+                Ok(bounty.mission_token_id)
+            })?;
+
+            milestones[index].released = true;
+            BountyMilestones::<T>::insert(bounty_id, milestones);
+
+            Self::deposit_event(Event::<T>::MilestoneReleased(bounty_id, index as u32, token_id, value, beneficiary));
         }
-    }
 
-    /// Remove any non-members of `Tippers` from a `tips` vector. `O(T)`.
-    fn retain_active_tips(tips: &mut Vec<(T::AccountId, BalanceOf<T>)>) {
-        let members = <T as Trait>::Tippers::sorted_members();
-        let mut members_iter = members.iter();
-        let mut member = members_iter.next();
-        tips.retain(|(ref a, _)| loop {
+        /// Add a child bounty to an active parent bounty, moving `value` from the parent bounty to
+        /// the child bounty.
+        ///
+        /// The dispatch origin for this call must be the curator of the parent bounty. The value
+        /// must not exceed the parent value net of the curator fee and unreleased milestones.
+        ///
+        /// - `parent_bounty_id`: Index of the parent bounty.
+        /// - `value`: The total payment amount of the child bounty, child curator fee included.
+        /// - `description`: The description of the child bounty.
+        #[weight = <T as Trait>::WeightInfo::add_child_bounty(description.len() as u32)]
+        fn add_child_bounty(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] value: BalanceOf<T>,
+            description: Vec<u8>,
+        ) {
+            let signer = ensure_signed(origin)?;
+            ensure!(
+                description.len() <= <T as Trait>::MaximumReasonLength::get() as usize,
+                Error::<T>::ReasonTooBig
+            );
+            ensure!(value >= <T as Trait>::BountyValueMinimum::get(), Error::<T>::InvalidValue);
+            ensure!(
+                Self::active_child_bounty_count(parent_bounty_id) < <T as Trait>::MaxActiveChildBounties::get(),
+                Error::<T>::TooManyChildBounties
+            );
+
+            let unreleased = Self::bounty_milestones(parent_bounty_id).iter()
+                .filter(|m| !m.released)
+                .fold(Zero::zero(), |acc: BalanceOf<T>, m| acc.saturating_add(m.value));
+            let child_bounty_id = Self::child_bounty_count(parent_bounty_id);
+
+            Bounties::<T>::try_mutate_exists(parent_bounty_id, |maybe_bounty| -> DispatchResult {
+                let bounty = maybe_bounty.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+
+                match bounty.status {
+                    BountyStatus::Active { ref curator, .. } => {
+                        ensure!(*curator == signer, Error::<T>::RequireCurator);
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                }
+
+                let remaining = bounty.value.saturating_sub(bounty.fee).saturating_sub(unreleased);
+                ensure!(value <= remaining, Error::<T>::InvalidValue);
+
+                <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &Self::bounty_account_id(parent_bounty_id),
+                    &Self::child_bounty_account_id(parent_bounty_id, child_bounty_id),
+                    bounty.mission_token_id,
+                    value,
+                    AllowDeath,
+                )?;
+                bounty.value -= value;
+
+                Ok(())
+            })?;
+
+            ChildBountyCount::insert(parent_bounty_id, child_bounty_id + 1);
+            ActiveChildBountyCount::mutate(parent_bounty_id, |count| *count += 1);
+            ChildBounties::<T>::insert(parent_bounty_id, child_bounty_id, ChildBounty {
+                value,
+                fee: Zero::zero(),
+                curator_deposit: Zero::zero(),
+                status: ChildBountyStatus::Added,
+            });
+            ChildBountyDescriptions::insert(parent_bounty_id, child_bounty_id, description);
+
+            Self::deposit_event(Event::<T>::ChildBountyAdded(parent_bounty_id, child_bounty_id));
+        }
+
+        /// Assign a curator to a child bounty. The child curator fee is taken from the curator
+        /// fee of the parent bounty.
+        ///
+        /// The dispatch origin for this call must be the curator of the parent bounty.
+        #[weight = <T as Trait>::WeightInfo::propose_child_curator()]
+        fn propose_child_curator(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] child_bounty_id: BountyIndex,
+            curator: <T::Lookup as StaticLookup>::Source,
+            #[compact] fee: BalanceOf<T>,
+        ) {
+            let signer = ensure_signed(origin)?;
+            let curator = T::Lookup::lookup(curator)?;
+
+            let mut parent = Self::bounties(parent_bounty_id).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_parent_curator(&parent, &signer)?;
+
+            ChildBounties::<T>::try_mutate_exists(parent_bounty_id, child_bounty_id, |maybe_child| -> DispatchResult {
+                let child = maybe_child.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                match child.status {
+                    ChildBountyStatus::Added | ChildBountyStatus::CuratorProposed { .. } => {},
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                };
+
+                let available_fee = parent.fee.saturating_add(child.fee);
+                ensure!(fee < child.value && fee <= available_fee, Error::<T>::InvalidFee);
+
+                parent.fee = available_fee - fee;
+                child.fee = fee;
+                child.status = ChildBountyStatus::CuratorProposed { curator };
+
+                Ok(())
+            })?;
+
+            Bounties::<T>::insert(parent_bounty_id, parent);
+        }
+
+        /// Accept the curator role for a child bounty.
+        /// A deposit will be reserved from the curator and refunded upon successful payout.
+        ///
+        /// May only be called from the proposed child curator.
+        #[weight = <T as Trait>::WeightInfo::accept_child_curator()]
+        fn accept_child_curator(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] child_bounty_id: BountyIndex,
+        ) {
+            let signer = ensure_signed(origin)?;
+            let parent = Self::bounties(parent_bounty_id).ok_or(Error::<T>::InvalidIndex)?;
+
+            ChildBounties::<T>::try_mutate_exists(parent_bounty_id, child_bounty_id, |maybe_child| -> DispatchResult {
+                let child = maybe_child.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+
+                match &child.status {
+                    ChildBountyStatus::CuratorProposed { curator } => {
+                        ensure!(signer == *curator, Error::<T>::RequireCurator);
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                }
+
+                let deposit = <T as Trait>::BountyCuratorDeposit::get() * child.fee;
+                <pallet_mission_tokens::Module<T>>::reserve(&signer, parent.mission_token_id, deposit)?;
+                child.curator_deposit = deposit;
+                child.status = ChildBountyStatus::Active { curator: signer };
+
+                Ok(())
+            })?;
+        }
+
+        /// Award a child bounty to a beneficiary account. The beneficiary will be able to claim
+        /// the funds after a delay.
+        ///
+        /// The dispatch origin for this call must be the curator of this child bounty.
+        #[weight = <T as Trait>::WeightInfo::award_child_bounty()]
+        fn award_child_bounty(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] child_bounty_id: BountyIndex,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+        ) {
+            let signer = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+
+            ChildBounties::<T>::try_mutate_exists(parent_bounty_id, child_bounty_id, |maybe_child| -> DispatchResult {
+                let child = maybe_child.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                match &child.status {
+                    ChildBountyStatus::Active { curator } => {
+                        ensure!(signer == *curator, Error::<T>::RequireCurator);
+                    },
+                    _ => return Err(Error::<T>::UnexpectedStatus.into()),
+                }
+                child.status = ChildBountyStatus::PendingPayout {
+                    curator: signer,
+                    beneficiary: beneficiary.clone(),
+                    unlock_at: system::Module::<T>::block_number() + <T as Trait>::BountyDepositPayoutDelay::get(),
+                };
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::<T>::ChildBountyAwarded(parent_bounty_id, child_bounty_id, beneficiary));
+        }
+
+        /// Claim the payout from an awarded child bounty after payout delay.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        #[weight = <T as Trait>::WeightInfo::claim_child_bounty()]
+        fn claim_child_bounty(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] child_bounty_id: BountyIndex,
+        ) {
+            let _ = ensure_signed(origin)?; // anyone can trigger claim
+            let token_id = Self::bounties(parent_bounty_id).ok_or(Error::<T>::InvalidIndex)?.mission_token_id;
+
+            ChildBounties::<T>::try_mutate_exists(parent_bounty_id, child_bounty_id, |maybe_child| -> DispatchResult {
+                let child = maybe_child.take().ok_or(Error::<T>::InvalidIndex)?;
+                if let ChildBountyStatus::PendingPayout { curator, beneficiary, unlock_at } = child.status {
+                    ensure!(system::Module::<T>::block_number() >= unlock_at, Error::<T>::Premature);
+                    let child_account = Self::child_bounty_account_id(parent_bounty_id, child_bounty_id);
+                    let balance = <pallet_mission_tokens::Module<T>>::free_balance(&child_account, token_id);
+                    let fee = child.fee.min(balance); // just to be safe
+                    let payout = balance.saturating_sub(fee);
+                    let _ = <pallet_mission_tokens::Module<T>>::unreserve(&curator, token_id, child.curator_deposit);
+                    let _ = <pallet_mission_tokens::Module<T>>::do_transfer(&child_account, &curator, token_id, fee, AllowDeath); // should not fail
+                    let _ = <pallet_mission_tokens::Module<T>>::do_transfer(&child_account, &beneficiary, token_id, payout, AllowDeath); // should not fail
+                    *maybe_child = None;
+
+                    ChildBountyDescriptions::remove(parent_bounty_id, child_bounty_id);
+                    ActiveChildBountyCount::mutate(parent_bounty_id, |count| *count = count.saturating_sub(1));
+
+                    Self::deposit_event(Event::<T>::ChildBountyClaimed(parent_bounty_id, child_bounty_id, token_id, payout, beneficiary));
+                    Ok(())
+                } else {
+                    Err(Error::<T>::UnexpectedStatus.into())
+                }
+            })?;
+        }
+
+        /// Cancel a child bounty that is not pending payout. Its funds and the child curator fee
+        /// are returned to the parent bounty, and the child curator deposit is unreserved.
+        ///
+        /// The dispatch origin for this call must be the curator of the parent bounty or
+        /// `T::RejectOrigin`.
+        #[weight = <T as Trait>::WeightInfo::close_child_bounty()]
+        fn close_child_bounty(
+            origin,
+            #[compact] parent_bounty_id: BountyIndex,
+            #[compact] child_bounty_id: BountyIndex,
+        ) {
+            let maybe_sender = ensure_signed(origin.clone())
+                .map(Some)
+                .or_else(|_| <T as Trait>::RejectOrigin::ensure_origin(origin).map(|_| None))?;
+
+            let mut parent = Self::bounties(parent_bounty_id).ok_or(Error::<T>::InvalidIndex)?;
+            if let Some(sender) = maybe_sender {
+                Self::ensure_parent_curator(&parent, &sender)?;
+            }
+            let child = Self::child_bounties(parent_bounty_id, child_bounty_id).ok_or(Error::<T>::InvalidIndex)?;
+            let token_id = parent.mission_token_id;
+
+            match child.status {
+                ChildBountyStatus::Added | ChildBountyStatus::CuratorProposed { .. } => {},
+                ChildBountyStatus::Active { ref curator } => {
+                    let _ = <pallet_mission_tokens::Module<T>>::unreserve(curator, token_id, child.curator_deposit);
+                },
+                ChildBountyStatus::PendingPayout { .. } => return Err(Error::<T>::PendingPayout.into()),
+            }
+
+            let child_account = Self::child_bounty_account_id(parent_bounty_id, child_bounty_id);
+            let balance = <pallet_mission_tokens::Module<T>>::free_balance(&child_account, token_id);
+            let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                &child_account,
+                &Self::bounty_account_id(parent_bounty_id),
+                token_id,
+                balance,
+                AllowDeath
+            ); // should not fail
+
+            parent.value = parent.value.saturating_add(balance);
+            parent.fee = parent.fee.saturating_add(child.fee);
+            Bounties::<T>::insert(parent_bounty_id, parent);
+
+            ChildBounties::<T>::remove(parent_bounty_id, child_bounty_id);
+            ChildBountyDescriptions::remove(parent_bounty_id, child_bounty_id);
+            ActiveChildBountyCount::mutate(parent_bounty_id, |count| *count = count.saturating_sub(1));
+
+            Self::deposit_event(Event::<T>::ChildBountyCanceled(parent_bounty_id, child_bounty_id));
+        }
+
+        /// Put forward a stream paying `value` to `beneficiary` every `interval` for `periods`
+        /// periods. A deposit proportional to the total value is reserved and slashed if the
+        /// stream is rejected. It is returned once the stream is funded.
+        ///
+        /// # <weight>
+        /// - Complexity: O(1)
+        /// - DbReads: `StreamCount`, `origin account`
+        /// - DbWrites: `StreamCount`, `Streams`, `origin account`
+        /// # </weight>
+        #[weight = <T as Trait>::WeightInfo::propose_stream()]
+        fn propose_stream(
+            origin,
+            #[compact] value: BalanceOf<T>,
+            interval: StreamInterval,
+            #[compact] periods: u32,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+            token_id: TokenId<T>,
+        ) {
+            let proposer = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+            ensure!(!value.is_zero() && periods > 0, Error::<T>::InvalidStream);
+
+            let bond = Self::calculate_bond(value.saturating_mul(periods.into()));
+            <pallet_mission_tokens::Module<T>>::reserve(&proposer, token_id, bond)
+                .map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+
+            let index = Self::stream_count();
+            StreamCount::put(index + 1);
+            <Streams<T>>::insert(index, Stream {
+                proposer,
+                beneficiary,
+                value,
+                interval,
+                periods,
+                bond,
+                claimed: Zero::zero(),
+                status: StreamStatus::Proposed,
+                mission_token_id: token_id,
+            });
+
+            Self::deposit_event(RawEvent::StreamProposed(index));
+        }
+
+        /// Approve a stream proposal. At a later time, the stream will be funded from the
+        /// mission pot and start accruing payments, and the original deposit will be returned.
+        ///
+        /// May only be called from `T::ApproveOrigin`.
+        ///
+        /// # <weight>
+        /// - O(1).
+        /// - DbReads: `Streams`, `StreamApprovals`
+        /// - DbWrites: `Streams`, `StreamApprovals`
+        /// # </weight>
+        #[weight = (<T as Trait>::WeightInfo::approve_stream(), DispatchClass::Operational)]
+        fn approve_stream(origin, #[compact] stream_id: StreamIndex) {
+            <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
+
+            Streams::<T>::try_mutate_exists(stream_id, |maybe_stream| -> DispatchResult {
+                let mut stream = maybe_stream.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                ensure!(stream.status == StreamStatus::Proposed, Error::<T>::UnexpectedStatus);
+
+                stream.status = StreamStatus::Approved;
+                StreamApprovals::append(stream_id);
+
+                Ok(())
+            })?;
+        }
+
+        /// Pay out the accrued payments of an active stream to its beneficiary.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `stream_id`: Stream ID to claim.
+        #[weight = <T as Trait>::WeightInfo::claim_stream()]
+        fn claim_stream(origin, #[compact] stream_id: StreamIndex) {
+            let _ = ensure_signed(origin)?; // anyone can trigger claim
+
+            Streams::<T>::try_mutate_exists(stream_id, |maybe_stream| -> DispatchResult {
+                let stream = maybe_stream.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                let accrued = Self::stream_accrued(stream).ok_or(Error::<T>::UnexpectedStatus)?;
+                let payout = accrued.saturating_sub(stream.claimed);
+                ensure!(!payout.is_zero(), Error::<T>::NothingToClaim);
+
+                <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &Self::stream_account_id(stream_id),
+                    &stream.beneficiary,
+                    stream.mission_token_id,
+                    payout,
+                    AllowDeath
+                )?;
+                stream.claimed = accrued;
+
+                Self::deposit_event(Event::<T>::StreamClaimed(
+                    stream_id,
+                    stream.mission_token_id,
+                    payout,
+                    stream.beneficiary.clone()
+                ));
+
+                if stream.claimed >= stream.value.saturating_mul(stream.periods.into()) {
+                    *maybe_stream = None;
+                }
+                Ok(())
+            })?;
+        }
+
+        /// Cancel a proposed or active stream.
+        ///
+        /// The bond of a proposed stream is slashed. For an active stream, payments accrued so
+        /// far are paid to the beneficiary and the unpaid remainder is returned to the pot.
+        ///
+        /// Only `T::RejectOrigin` is able to cancel a stream.
+        ///
+        /// - `stream_id`: Stream ID to cancel.
+        #[weight = <T as Trait>::WeightInfo::cancel_stream_proposed().max(<T as Trait>::WeightInfo::cancel_stream_active())]
+        fn cancel_stream(origin, #[compact] stream_id: StreamIndex) -> DispatchResultWithPostInfo {
+            <T as Trait>::RejectOrigin::ensure_origin(origin)?;
+
+            Streams::<T>::try_mutate_exists(stream_id, |maybe_stream| -> DispatchResultWithPostInfo {
+                let stream = maybe_stream.as_ref().ok_or(Error::<T>::InvalidIndex)?;
+                let token_id = stream.mission_token_id;
+
+                match stream.status {
+                    StreamStatus::Proposed => {
+                        let value = stream.bond;
+                        let imbalance = <pallet_mission_tokens::Module<T>>::slash_reserved(&stream.proposer, token_id, value).0;
+                        <T as Trait>::OnSlash::on_unbalanced(imbalance);
+                        *maybe_stream = None;
+
+                        Self::deposit_event(Event::<T>::StreamRejected(stream_id, token_id, value));
+                        return Ok(Some(<T as Trait>::WeightInfo::cancel_stream_proposed()).into())
+                    },
+                    StreamStatus::Approved => {
+                        // For weight reasons, we don't allow a council to cancel in this phase.
+                        // We ask for them to wait until it is funded before they can cancel.
+                        return Err(Error::<T>::UnexpectedStatus.into())
+                    },
+                    StreamStatus::Active { .. } => {},
+                }
+
+                let stream_account = Self::stream_account_id(stream_id);
+                let accrued = Self::stream_accrued(stream).unwrap_or_else(Zero::zero);
+                let payout = accrued.saturating_sub(stream.claimed);
+                let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &stream_account,
+                    &stream.beneficiary,
+                    token_id,
+                    payout,
+                    AllowDeath
+                ); // should not fail
+
+                let remainder = <pallet_mission_tokens::Module<T>>::free_balance(&stream_account, token_id);
+                let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &stream_account,
+                    &Self::account_id(),
+                    token_id,
+                    remainder,
+                    AllowDeath
+                ); // should not fail
+                *maybe_stream = None;
+
+                Self::deposit_event(Event::<T>::StreamCanceled(stream_id, token_id, remainder));
+                Ok(Some(<T as Trait>::WeightInfo::cancel_stream_active()).into())
+            })
+        }
+
+        /// Open a quadratic-funding round, moving `matching` from the mission pot to the round.
+        ///
+        /// May only be called from `T::ApproveOrigin`.
+        ///
+        /// - `token_id`: The mission whose pot funds the matching.
+        /// - `matching`: The matching amount distributed between the projects at close.
+        /// - `duration`: The number of blocks during which projects register and contributions
+        ///   are accepted.
+        ///
+        /// Emits `RoundOpened` if successful.
+        #[weight = (<T as Trait>::WeightInfo::open_round(), DispatchClass::Operational)]
+        fn open_round(
+            origin,
+            token_id: TokenId<T>,
+            #[compact] matching: BalanceOf<T>,
+            #[compact] duration: T::BlockNumber,
+        ) {
+            <T as Trait>::ApproveOrigin::ensure_origin(origin)?;
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(token_id)?;
+            ensure!(matching <= Self::pot(token_id), Error::<T>::InsufficientPot);
+
+            let index = Self::round_count();
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &Self::account_id(),
+                &Self::round_account_id(index),
+                token_id,
+                matching,
+                KeepAlive,
+            )?;
+
+            RoundCount::put(index + 1);
+            <Rounds<T>>::insert(index, FundingRound {
+                matching,
+                end: system::Module::<T>::block_number() + duration,
+                project_count: 0,
+                contributors: 0,
+                mission_token_id: token_id,
+            });
+
+            Self::deposit_event(RawEvent::RoundOpened(index, token_id, matching));
+        }
+
+        /// Register a project in an open funding round. The origin becomes the owner of the
+        /// project and receives its contributions and matching funds.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// Emits `ProjectRegistered` if successful.
+        #[weight = <T as Trait>::WeightInfo::register_project()]
+        fn register_project(origin, #[compact] round_id: RoundIndex) {
+            let owner = ensure_signed(origin)?;
+
+            let project_id = Rounds::<T>::try_mutate(round_id, |maybe_round| -> Result<ProjectIndex, DispatchError> {
+                let round = maybe_round.as_mut().ok_or(Error::<T>::InvalidIndex)?;
+                ensure!(system::Module::<T>::block_number() < round.end, Error::<T>::RoundEnded);
+                ensure!(round.project_count < <T as Trait>::MaxRoundProjects::get(), Error::<T>::TooManyProjects);
+
+                round.project_count += 1;
+                Ok(round.project_count - 1)
+            })?;
+
+            RoundProjects::<T>::insert(round_id, project_id, RoundProject {
+                owner: owner.clone(),
+                contributed: Zero::zero(),
+                sqrt_sum: Zero::zero(),
+            });
+
+            Self::deposit_event(RawEvent::ProjectRegistered(round_id, project_id, owner));
+        }
+
+        /// Contribute `value` of the round's mission token to a project of an open funding round.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and pass `T::ContributorFilter`.
+        /// An account can make at most `MaxRoundContributions` contributions per round, and a
+        /// round accepts at most `MaxRoundContributors` contributing accounts.
+        ///
+        /// Emits `Contributed` if successful.
+        #[weight = <T as Trait>::WeightInfo::contribute()]
+        fn contribute(
+            origin,
+            #[compact] round_id: RoundIndex,
+            #[compact] project_id: ProjectIndex,
+            #[compact] value: BalanceOf<T>,
+        ) {
+            let who = ensure_signed(origin)?;
+            ensure!(<T as Trait>::ContributorFilter::filter(&who), Error::<T>::ContributorNotAllowed);
+            ensure!(!value.is_zero(), Error::<T>::InvalidValue);
+
+            let mut round = Self::rounds(round_id).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(system::Module::<T>::block_number() < round.end, Error::<T>::RoundEnded);
+            let mut project = Self::round_projects(round_id, project_id).ok_or(Error::<T>::InvalidIndex)?;
+            let count = Self::round_contribution_count(round_id, &who);
+            ensure!(count < <T as Trait>::MaxRoundContributions::get(), Error::<T>::TooManyContributions);
+            if count == 0 {
+                ensure!(
+                    round.contributors < <T as Trait>::MaxRoundContributors::get(),
+                    Error::<T>::TooManyContributors
+                );
+                round.contributors += 1;
+            }
+
+            <pallet_mission_tokens::Module<T>>::do_transfer(
+                &who,
+                &Self::round_account_id(round_id),
+                round.mission_token_id,
+                value,
+                KeepAlive,
+            )?;
+
+            let previous = Self::round_contributions(round_id, (project_id, &who));
+            let total = previous.saturating_add(value);
+            project.contributed = project.contributed.saturating_add(value);
+            project.sqrt_sum = project.sqrt_sum
+                .saturating_sub(previous.integer_sqrt())
+                .saturating_add(total.integer_sqrt());
+
+            RoundContributions::<T>::insert(round_id, (project_id, &who), total);
+            RoundContributionCount::<T>::insert(round_id, &who, count + 1);
+            RoundProjects::<T>::insert(round_id, project_id, project);
+            <Rounds<T>>::insert(round_id, round);
+
+            Self::deposit_event(RawEvent::Contributed(round_id, project_id, who, value));
+        }
+
+        /// Close a funding round after its end and pay out the projects.
+        ///
+        /// Each project receives its contributions plus a share of the matching funds. The
+        /// matching share of a project is `(Σ √c)² - Σ c` over the contributions `c` of each
+        /// account, scaled down proportionally if the matching funds cannot cover all projects.
+        /// Unused matching funds are returned to the mission pot.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// Emits `ProjectFunded` for each project and `RoundClosed` if successful.
+        ///
+        /// # <weight>
+        /// - Complexity: `O(P + C)` where `P` is the number of projects, bounded by
+        ///   `MaxRoundProjects`, and `C` the number of contributors, bounded by
+        ///   `MaxRoundContributors`. Each contributor has at most `MaxRoundContributions`
+        ///   contributions to remove.
+        /// # </weight>
+        #[weight = <T as Trait>::WeightInfo::close_round(
+            <T as Trait>::MaxRoundProjects::get(),
+            <T as Trait>::MaxRoundContributors::get(),
+        )]
+        fn close_round(origin, #[compact] round_id: RoundIndex) -> DispatchResultWithPostInfo {
+            ensure_signed(origin)?;
+
+            let round = Self::rounds(round_id).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(system::Module::<T>::block_number() >= round.end, Error::<T>::Premature);
+
+            let projects = RoundProjects::<T>::drain_prefix(round_id).collect::<Vec<_>>();
+            let subsidies = projects.iter().map(|(_, project)| {
+                project.sqrt_sum.saturating_mul(project.sqrt_sum).saturating_sub(project.contributed)
+            }).collect::<Vec<_>>();
+            let total_subsidy = subsidies.iter().fold(Zero::zero(), |acc: BalanceOf<T>, s| acc.saturating_add(*s));
+
+            let round_account = Self::round_account_id(round_id);
+            let token_id = round.mission_token_id;
+            for ((project_id, project), subsidy) in projects.iter().zip(subsidies.into_iter()) {
+                let matched = if total_subsidy <= round.matching {
+                    subsidy
+                } else {
+                    Perbill::from_rational_approximation(subsidy, total_subsidy) * round.matching
+                };
+                let payout = project.contributed.saturating_add(matched);
+                let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                    &round_account,
+                    &project.owner,
+                    token_id,
+                    payout,
+                    AllowDeath,
+                ); // should not fail
+                Self::deposit_event(RawEvent::ProjectFunded(round_id, *project_id, token_id, payout, project.owner.clone()));
+            }
+
+            let remainder = <pallet_mission_tokens::Module<T>>::free_balance(&round_account, token_id);
+            let _ = <pallet_mission_tokens::Module<T>>::do_transfer(
+                &round_account,
+                &Self::account_id(),
+                token_id,
+                remainder,
+                AllowDeath,
+            ); // should not fail
+
+            RoundContributions::<T>::remove_prefix(round_id);
+            RoundContributionCount::<T>::remove_prefix(round_id);
+            <Rounds<T>>::remove(round_id);
+
+            Self::deposit_event(RawEvent::RoundClosed(round_id, token_id, remainder));
+            Ok(Some(<T as Trait>::WeightInfo::close_round(projects.len() as u32, round.contributors)).into())
+        }
+
+        /// # <weight>
+        /// - Complexity: `O(A + V + N)` where `A` is the number of approvals, `V` the number of
+        ///   validators and `N` the number of their nominators processed for era rewards
+        /// - Db reads and writes: `Approvals`, `pot account data`
+        /// - Db reads and writes per approval:
+        ///   `Proposals`, `proposer account data`, `beneficiary account data`
+        /// - Db reads and writes per stream approval:
+        ///   `Streams`, `proposer account data`, `stream account data`
+        /// - Db reads per validator: `ErasStakersClipped`, `ErasMissionOf`
+        /// - Db reads per nominator: `ErasNominatorMissionsOf`
+        /// - Db reads and writes per mission: `EraMissionPoints`, `treasury account data`
+        /// - The weight is overestimated if some approvals got missed.
+        /// # </weight>
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let mut weight = Self::process_era_rewards();
+
+            // Check to see if we should spend some funds!
+            if (n % <T as Trait>::SpendPeriod::get()).is_zero() {
+                weight = weight.saturating_add(Self::spend_funds());
+            }
+
+            weight
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    // Add public immutables and private mutables.
+
+    /// The account ID of the treasury pot.
+    ///
+    /// This actually does computation. If you need to keep using it, then make sure you cache the
+    /// value and only call this once.
+    pub fn account_id() -> T::AccountId {
+        <pallet_treasury::Module<T>>::account_id()
+    }
+
+    /// The account ID of a bounty account
+    pub fn bounty_account_id(id: BountyIndex) -> T::AccountId {
+        <pallet_treasury::Module<T>>::bounty_account_id(id)
+    }
+
+    /// The account ID of a child bounty account.
+    pub fn child_bounty_account_id(parent_id: BountyIndex, id: BountyIndex) -> T::AccountId {
+        <T as pallet_treasury::Trait>::ModuleId::get().into_sub_account(("cb", parent_id, id))
+    }
+
+    /// Ensure `who` is the curator of the active parent bounty `parent`.
+    fn ensure_parent_curator(
+        parent: &Bounty<T::AccountId, BalanceOf<T>, T::BlockNumber, TokenId<T>>,
+        who: &T::AccountId,
+    ) -> DispatchResult {
+        match parent.status {
+            BountyStatus::Active { ref curator, .. } => {
+                ensure!(curator == who, Error::<T>::RequireCurator);
+                Ok(())
+            }
+            _ => Err(Error::<T>::UnexpectedStatus.into()),
+        }
+    }
+
+    /// The account ID of a stream account.
+    pub fn stream_account_id(id: StreamIndex) -> T::AccountId {
+        // only use two byte prefix to support 16 byte account id (used by test)
+        <T as pallet_treasury::Trait>::ModuleId::get().into_sub_account(("st", id))
+    }
+
+    /// The account ID of a funding round account.
+    pub fn round_account_id(id: RoundIndex) -> T::AccountId {
+        // only use two byte prefix to support 16 byte account id (used by test)
+        <T as pallet_treasury::Trait>::ModuleId::get().into_sub_account(("qf", id))
+    }
+
+    /// The total amount accrued by an active stream since it started, including claimed
+    /// payments. `None` if the stream is not active.
+    fn stream_accrued(
+        stream: &Stream<T::AccountId, BalanceOf<T>, T::BlockNumber, TokenId<T>>,
+    ) -> Option<BalanceOf<T>> {
+        match stream.status {
+            StreamStatus::Active { start } => {
+                let interval = match stream.interval {
+                    StreamInterval::SpendPeriod => <T as Trait>::SpendPeriod::get(),
+                    StreamInterval::Block => One::one(),
+                };
+                let elapsed: u32 = (system::Module::<T>::block_number().saturating_sub(start)
+                    / interval)
+                    .unique_saturated_into();
+                Some(stream.value.saturating_mul(elapsed.min(stream.periods).into()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The needed bond for a proposal whose spend is `value`.
+    fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
+        <T as Trait>::ProposalBondMinimum::get().max(<T as Trait>::ProposalBond::get() * value)
+    }
+
+    /// Given a mutable reference to an `OpenTip`, insert the tip into it and check whether it
+    /// closes, if so, then deposit the relevant event and set closing accordingly.
+    ///
+    /// `O(T)` and one storage access.
+    fn insert_tip_and_check_closing(
+        tip: &mut OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash, TokenId<T>>,
+        tipper: T::AccountId,
+        tip_value: BalanceOf<T>,
+    ) -> bool {
+        match tip.tips.binary_search_by_key(&&tipper, |x| &x.0) {
+            Ok(pos) => tip.tips[pos] = (tipper, tip_value),
+            Err(pos) => tip.tips.insert(pos, (tipper, tip_value)),
+        }
+        Self::retain_active_tips(&mut tip.tips);
+        let threshold = (<T as Trait>::Tippers::count() + 1) / 2;
+        if tip.tips.len() >= threshold && tip.closes.is_none() {
+            tip.closes =
+                Some(system::Module::<T>::block_number() + <T as Trait>::TipCountdown::get());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove any non-members of `Tippers` from a `tips` vector. `O(T)`.
+    fn retain_active_tips(tips: &mut Vec<(T::AccountId, BalanceOf<T>)>) {
+        let members = <T as Trait>::Tippers::sorted_members();
+        let mut members_iter = members.iter();
+        let mut member = members_iter.next();
+        tips.retain(|(ref a, _)| loop {
             match member {
                 None => break false,
                 Some(m) if m > a => break false,
@@ -1211,6 +2546,8 @@ impl<T: Trait> Module<T> {
                     if p.value <= budgets_remaining[id] {
                         budgets_remaining[id] -= p.value;
                         <Proposals<T>>::remove(index);
+                        <ProposalSubmittedAt<T>>::remove(index);
+                        EscalatedProposals::remove(index);
 
                         // return their deposit.
                         let _ = <pallet_mission_tokens::Module<T>>::unreserve(
@@ -1293,6 +2630,53 @@ impl<T: Trait> Module<T> {
 
         total_weight += <T as Trait>::WeightInfo::on_initialize_bounties(bounties_len);
 
+        let now = system::Module::<T>::block_number();
+        let streams_len = StreamApprovals::mutate(|v| {
+            let streams_approval_len = v.len() as u32;
+            v.retain(|&index| {
+                Streams::<T>::mutate(index, |stream| {
+                    // Should always be true, but shouldn't panic if false or we're screwed.
+                    if let Some(stream) = stream {
+                        let id: usize =
+                            (stream.mission_token_id.unique_saturated_into() as usize).into();
+                        let total = stream.value.saturating_mul(stream.periods.into());
+                        if total <= budgets_remaining[id] {
+                            budgets_remaining[id] -= total;
+
+                            stream.status = StreamStatus::Active { start: now };
+
+                            // return their deposit.
+                            let _ = <pallet_mission_tokens::Module<T>>::unreserve(
+                                &stream.proposer,
+                                stream.mission_token_id,
+                                stream.bond,
+                            );
+
+                            // fund the stream account
+                            imbalances[id].subsume(
+                                <pallet_mission_tokens::Module<T>>::deposit_creating(
+                                    &Self::stream_account_id(index),
+                                    stream.mission_token_id,
+                                    total,
+                                ),
+                            );
+
+                            Self::deposit_event(RawEvent::StreamBecameActive(index));
+                            false
+                        } else {
+                            missed_any[id] = true;
+                            true
+                        }
+                    } else {
+                        false
+                    }
+                })
+            });
+            streams_approval_len
+        });
+
+        total_weight += <T as Trait>::WeightInfo::on_initialize_streams(streams_len);
+
         token_id = min_token_id;
         while token_id <= max_token_id {
             let id: usize = (token_id.unique_saturated_into() as usize).into();
@@ -1332,6 +2716,259 @@ impl<T: Trait> Module<T> {
         total_weight
     }
 
+    /// Split the reward points of finished eras between missions and mint them to the mission
+    /// pots, processing at most `MaxEraRewardsPerBlock` validators, rewarded nominators and
+    /// missions. Returns the weight consumed.
+    ///
+    /// An era is only processed once it is no longer active, so its reward points are final. The
+    /// validators of an era are processed first, then the accumulated mission points are minted.
+    /// Work that does not fit in the budget is resumed in the next block, and budget left over
+    /// after an era is finished carries over to the next one. At least one validator is processed
+    /// per block, even if its nominators exceed the budget, so processing always makes progress.
+    fn process_era_rewards() -> Weight {
+        let mut weight = T::DbWeight::get().reads(3);
+        let active_era = match <pallet_staking::Module<T>>::active_era() {
+            Some(active_era) => active_era.index,
+            None => return weight,
+        };
+
+        let mut budget = <T as Trait>::MaxEraRewardsPerBlock::get();
+        let mut progressed = false;
+        let mut era = NextEraForProcessing::get().unwrap_or(0);
+        while era < active_era && budget > 0 {
+            let reward_points = <pallet_staking::Module<T>>::eras_reward_points(era);
+            let validators_len = reward_points.individual.len() as u32;
+            let processed = EraRewardsProcessed::get();
+
+            let mut mission_points = BTreeMap::new();
+            let mut validators = 0u32;
+            let mut nominators = 0u32;
+            for (validator, points) in reward_points
+                .individual
+                .into_iter()
+                .skip(processed as usize)
+            {
+                let exposure = <pallet_staking::Module<T>>::eras_stakers_clipped(era, &validator);
+                let exposure_nominators = exposure.others.len() as u32;
+                let cost = exposure_nominators.saturating_add(1);
+                if progressed && cost > budget {
+                    break;
+                }
+
+                Self::split_validator_points(era, &validator, points, exposure, &mut mission_points);
+                validators += 1;
+                nominators = nominators.saturating_add(exposure_nominators);
+                budget = budget.saturating_sub(cost);
+                progressed = true;
+                if budget == 0 {
+                    break;
+                }
+            }
+            for (mission_token_id, points) in mission_points {
+                EraMissionPoints::<T>::mutate(mission_token_id, |total| {
+                    *total = total.saturating_add(points)
+                });
+            }
+
+            if validators > 0 {
+                EraRewardsProcessed::put(processed + validators);
+                weight = weight.saturating_add(
+                    <T as Trait>::WeightInfo::on_initialize_era_rewards(validators, nominators),
+                );
+            }
+            if processed + validators < validators_len || budget == 0 {
+                break;
+            }
+
+            let (missions, remaining) = Self::mint_era_rewards(era, budget);
+            // Every era costs at least one unit of budget, so catching up on eras without
+            // reward points stays bounded too.
+            budget = budget.saturating_sub(missions.max(1));
+            progressed = true;
+            weight = weight
+                .saturating_add(<T as Trait>::WeightInfo::on_initialize_era_minting(missions));
+            if remaining {
+                break;
+            }
+
+            EraRewardsProcessed::kill();
+            era += 1;
+            NextEraForProcessing::put(era);
+        }
+
+        weight
+    }
+
+    /// Split the reward points `points` of `validator` in `era` between missions, adding them to
+    /// `mission_points`.
+    ///
+    /// The share of the points backed by each rewarded nominator in the clipped `exposure` with
+    /// a mission allocation is split between the nominator's missions in proportion to their
+    /// weights. The rest, including the share backed by the validator's own stake, goes to the
+    /// validator's mission.
+    fn split_validator_points(
+        era: EraIndex,
+        validator: &T::AccountId,
+        points: RewardPoint,
+        exposure: pallet_staking::Exposure<T::AccountId, pallet_staking::BalanceOf<T>>,
+        mission_points: &mut BTreeMap<TokenId<T>, RewardPoint>,
+    ) {
+        let mut validator_points = points;
+
+        if !exposure.total.is_zero() {
+            for nominator in exposure.others {
+                let missions = <pallet_validator_registry::Module<T>>::eras_nominator_missions_of(
+                    era,
+                    &nominator.who,
+                );
+                let total_weight = missions
+                    .iter()
+                    .fold(0u32, |acc, (_, weight)| acc.saturating_add(*weight));
+                if total_weight == 0 {
+                    continue;
+                }
+
+                let nominator_points =
+                    Perbill::from_rational_approximation(nominator.value, exposure.total) * points;
+                for (mission_token_id, weight) in missions {
+                    let mission_share =
+                        Perbill::from_rational_approximation(weight, total_weight) * nominator_points;
+                    validator_points = validator_points.saturating_sub(mission_share);
+                    let entry = mission_points.entry(mission_token_id).or_insert(0);
+                    *entry = entry.saturating_add(mission_share);
+                }
+            }
+        }
+
+        let mission_token_id = <pallet_validator_registry::Module<T>>::eras_mission_of(era, validator);
+        if !mission_token_id.is_zero() {
+            let entry = mission_points.entry(mission_token_id).or_insert(0);
+            *entry = entry.saturating_add(validator_points);
+        }
+    }
+
+    /// Mint the reward points accumulated by at most `max` missions in `era` to the mission pots.
+    /// Returns the number of missions minted to, and whether any mission is left to mint.
+    fn mint_era_rewards(era: EraIndex, max: u32) -> (u32, bool) {
+        let treasury_account_id = Self::account_id();
+        let mut minted = EraMissionPoints::<T>::iter()
+            .take(max as usize + 1)
+            .collect::<Vec<_>>();
+        let remaining = minted.len() > max as usize;
+        minted.truncate(max as usize);
+
+        for (mission_token_id, points) in minted.iter() {
+            EraMissionPoints::<T>::remove(mission_token_id);
+            let amount = <T as Trait>::MissionRewardCurve::convert(*points);
+            <pallet_mission_tokens::Module<T>>::mint(
+                treasury_account_id.clone(),
+                *mission_token_id,
+                amount,
+            );
+            Self::deposit_event(RawEvent::MissionMinted(era, *mission_token_id, amount));
+        }
+
+        (minted.len() as u32, remaining)
+    }
+
+    /// The pot and next spend block of every mission.
+    pub fn pots_info() -> Vec<MissionPot<TokenId<T>, BalanceOf<T>, T::BlockNumber>> {
+        let now = system::Module::<T>::block_number();
+        let spend_period = <T as Trait>::SpendPeriod::get();
+        let next_spend_at = now - now % spend_period + spend_period;
+
+        let (min_token_id, max_token_id) = <pallet_mission_tokens::Module<T>>::mission_token_ids();
+        let mut pots = Vec::new();
+        let mut token_id = min_token_id;
+        while token_id <= max_token_id {
+            pots.push(MissionPot {
+                mission_token_id: token_id,
+                pot: Self::pot(token_id),
+                next_spend_at,
+            });
+            token_id += 1.into();
+        }
+        pots
+    }
+
+    /// Pending and approved proposals of `mission_token_id`, or of every mission if `None`.
+    pub fn proposals_info(
+        mission_token_id: Option<TokenId<T>>,
+    ) -> Vec<ProposalInfo<T::AccountId, BalanceOf<T>, TokenId<T>>> {
+        let approvals = Self::approvals();
+        <Proposals<T>>::iter()
+            .filter(|(_, p)| mission_token_id.map_or(true, |id| id == p.mission_token_id))
+            .map(|(index, p)| ProposalInfo {
+                index,
+                proposer: p.proposer,
+                value: p.value,
+                beneficiary: p.beneficiary,
+                bond: p.bond,
+                approved: approvals.contains(&index),
+                mission_token_id: p.mission_token_id,
+            })
+            .collect()
+    }
+
+    /// Open tips of `mission_token_id`, or of every mission if `None`, with the median of the
+    /// tips of current tippers so far.
+    pub fn tips_info(
+        mission_token_id: Option<TokenId<T>>,
+    ) -> Vec<TipInfo<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash, TokenId<T>>> {
+        <Tips<T>>::iter()
+            .filter(|(_, t)| mission_token_id.map_or(true, |id| id == t.mission_token_id))
+            .map(|(hash, t)| {
+                let mut tips = t.tips;
+                Self::retain_active_tips(&mut tips);
+                tips.sort_by_key(|i| i.1);
+                TipInfo {
+                    hash,
+                    reason: Self::reasons(&t.reason).unwrap_or_default(),
+                    who: t.who,
+                    finder: t.finder,
+                    deposit: t.deposit,
+                    closes: t.closes,
+                    tippers: tips.len() as u32,
+                    median: tips.get(tips.len() / 2).map(|i| i.1),
+                    mission_token_id: t.mission_token_id,
+                }
+            })
+            .collect()
+    }
+
+    /// Bounties of `mission_token_id`, or of every mission if `None`, with their description.
+    pub fn bounties_info(
+        mission_token_id: Option<TokenId<T>>,
+    ) -> Vec<BountyInfo<T::AccountId, BalanceOf<T>, T::BlockNumber, TokenId<T>>> {
+        <Bounties<T>>::iter()
+            .filter(|(_, b)| mission_token_id.map_or(true, |id| id == b.mission_token_id))
+            .map(|(index, b)| BountyInfo {
+                index,
+                proposer: b.proposer,
+                value: b.value,
+                fee: b.fee,
+                curator_deposit: b.curator_deposit,
+                bond: b.bond,
+                status: match b.status {
+                    BountyStatus::Proposed => BountyStatusInfo::Proposed,
+                    BountyStatus::Approved => BountyStatusInfo::Approved,
+                    BountyStatus::Funded => BountyStatusInfo::Funded,
+                    BountyStatus::CuratorProposed { curator } => {
+                        BountyStatusInfo::CuratorProposed { curator }
+                    }
+                    BountyStatus::Active { curator, update_due } => {
+                        BountyStatusInfo::Active { curator, update_due }
+                    }
+                    BountyStatus::PendingPayout { curator, beneficiary, unlock_at } => {
+                        BountyStatusInfo::PendingPayout { curator, beneficiary, unlock_at }
+                    }
+                },
+                description: Self::bounty_descriptions(index).unwrap_or_default(),
+                mission_token_id: b.mission_token_id,
+            })
+            .collect()
+    }
+
     /// Return the amount of money in the pot.
     // The existential deposit is not part of the pot so treasury account never gets deleted.
     fn pot(token_id: TokenId<T>) -> BalanceOf<T> {
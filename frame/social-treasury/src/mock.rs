@@ -1,18 +1,16 @@
-use crate::{Module, Trait};
+use crate::{EscalateProposal, Module, ProposalIndex, ReferendumIndex, Trait};
 use frame_support::{
     impl_outer_dispatch, impl_outer_origin, parameter_types,
-    traits::{Contains, ContainsLengthBound},
+    traits::{Contains, ContainsLengthBound, Filter, ReservableCurrency},
     weights::Weight,
 };
-use pallet_session::{SessionHandler, SessionManager, ShouldEndSession};
 use pallet_staking::EraIndex;
 use sp_core::{crypto::key_types::DUMMY, H256};
 use sp_runtime::{
     curve::PiecewiseLinear,
-    impl_opaque_keys,
     testing::{Header, TestXt, UintAuthorityId},
     traits::{BlakeTwo256, Convert, IdentityLookup, OpaqueKeys},
-    ModuleId, Perbill, Percent, Permill, RuntimeAppPublic, SaturatedConversion,
+    DispatchError, KeyTypeId, ModuleId, Perbill, Percent, Permill, SaturatedConversion,
 };
 use sp_staking::SessionIndex;
 use std::cell::RefCell;
@@ -25,7 +23,7 @@ impl_outer_origin! {
 
 impl_outer_dispatch! {
     pub enum Call for Test where origin: Origin {
-        staking::Staking,
+        pallet_staking::Staking,
     }
 }
 
@@ -36,7 +34,6 @@ parameter_types! {
     pub const MaximumBlockWeight: Weight = 1024;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(16);
 }
 
 impl system::Trait for Test {
@@ -67,42 +64,22 @@ impl system::Trait for Test {
     type SystemWeightInfo = ();
 }
 
-parameter_types! {
-    pub const ExistentialDepositC: u64 = 1;
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+    Call: From<C>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = TestXt<Call, ()>;
 }
-thread_local! {
-    static TEN_TO_FOURTEEN: RefCell<Vec<u128>> = RefCell::new(vec![10,11,12,13,14]);
-
-    pub static VALIDATORS: RefCell<Vec<u64>> = RefCell::new(vec![1, 2, 3]);
-    pub static NEXT_VALIDATORS: RefCell<Vec<u64>> = RefCell::new(vec![1, 2, 3]);
-    pub static AUTHORITIES: RefCell<Vec<UintAuthorityId>> =
-        RefCell::new(vec![UintAuthorityId(1), UintAuthorityId(2), UintAuthorityId(3)]);
-    pub static FORCE_SESSION_END: RefCell<bool> = RefCell::new(false);
-    pub static SESSION_LENGTH: RefCell<u64> = RefCell::new(2);
-    pub static SESSION_CHANGED: RefCell<bool> = RefCell::new(false);
-    pub static TEST_SESSION_CHANGED: RefCell<bool> = RefCell::new(false);
-    pub static DISABLED: RefCell<bool> = RefCell::new(false);
-    // Stores if `on_before_session_end` was called
-    pub static BEFORE_SESSION_END_CALLED: RefCell<bool> = RefCell::new(false);
 
+thread_local! {
+    static TEN_TO_FOURTEEN: RefCell<Vec<u64>> = RefCell::new(vec![10, 11, 12, 13, 14]);
 }
+
 pub struct TenToFourteen;
-impl Contains<u128> for TenToFourteen {
-    fn sorted_members() -> Vec<u128> {
-        TEN_TO_FOURTEEN.with(|v| v.borrow().clone())
-    }
-    #[cfg(feature = "runtime-benchmarks")]
-    fn add(new: &u128) {
-        TEN_TO_FOURTEEN.with(|v| {
-            let mut members = v.borrow_mut();
-            members.push(*new);
-            members.sort();
-        })
-    }
-}
 impl Contains<u64> for TenToFourteen {
     fn sorted_members() -> Vec<u64> {
-        TEN_TO_FOURTEEN.with(|v| v.borrow().clone().iter().map(|x| (*x as u64)).collect())
+        TEN_TO_FOURTEEN.with(|v| v.borrow().clone())
     }
     #[cfg(feature = "runtime-benchmarks")]
     fn add(new: &u64) {
@@ -121,6 +98,36 @@ impl ContainsLengthBound for TenToFourteen {
         0
     }
 }
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = MissionTokens;
+    type AccountData = pallet_mission_tokens::AccountData<u64>;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+}
+
 parameter_types! {
     pub const ProposalBond: Permill = Permill::from_percent(5);
     pub const ProposalBondMinimum: u64 = 1;
@@ -133,14 +140,15 @@ parameter_types! {
     pub const BountyDepositBase: u64 = 80;
     pub const BountyDepositPayoutDelay: u64 = 3;
     pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
-    pub const BountyUpdatePeriod: u32 = 20;
+    pub const BountyUpdatePeriod: u64 = 20;
     pub const MaximumReasonLength: u32 = 16384;
     pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
     pub const BountyValueMinimum: u64 = 1;
 }
+
 impl pallet_treasury::Trait for Test {
     type ModuleId = TreasuryModuleId;
-    type Currency = pallet_balances::Module<Test>;
+    type Currency = Balances;
     type ApproveOrigin = frame_system::EnsureRoot<u64>;
     type RejectOrigin = frame_system::EnsureRoot<u64>;
     type Tippers = TenToFourteen;
@@ -163,93 +171,35 @@ impl pallet_treasury::Trait for Test {
     type BurnDestination = (); // Just gets burned.
     type WeightInfo = ();
 }
-type System = system::Module<Test>;
-type Balances = pallet_balances::Module<Test>;
-type Session = pallet_session::Module<Test>;
-type Staking = pallet_staking::Module<Test>;
-
-impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
-where
-    Call: From<C>,
-{
-    type OverarchingCall = Call;
-    type Extrinsic = TestXt<Call, ()>;
-}
-
-impl_opaque_keys! {
-    pub struct MockSessionKeys {
-        pub dummy: UintAuthorityId,
-    }
-}
-
-pub struct TestShouldEndSession;
-impl ShouldEndSession<u64> for TestShouldEndSession {
-    fn should_end_session(now: u64) -> bool {
-        let l = SESSION_LENGTH.with(|l| *l.borrow());
-        now % l == 0
-            || FORCE_SESSION_END.with(|l| {
-                let r = *l.borrow();
-                *l.borrow_mut() = false;
-                r
-            })
-    }
-}
 
 pub struct TestSessionHandler;
-impl SessionHandler<u64> for TestSessionHandler {
-    const KEY_TYPE_IDS: &'static [sp_runtime::KeyTypeId] = &[UintAuthorityId::ID];
-    fn on_genesis_session<T: OpaqueKeys>(_validators: &[(u64, T)]) {}
-    fn on_new_session<T: OpaqueKeys>(
-        changed: bool,
-        validators: &[(u64, T)],
-        _queued_validators: &[(u64, T)],
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+    const KEY_TYPE_IDS: &'static [KeyTypeId] = &[DUMMY];
+    fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(u64, Ks)]) {}
+    fn on_new_session<Ks: OpaqueKeys>(
+        _changed: bool,
+        _validators: &[(u64, Ks)],
+        _queued_validators: &[(u64, Ks)],
     ) {
-        SESSION_CHANGED.with(|l| *l.borrow_mut() = changed);
-        AUTHORITIES.with(|l| {
-            *l.borrow_mut() = validators
-                .iter()
-                .map(|(_, id)| id.get::<UintAuthorityId>(DUMMY).unwrap_or_default())
-                .collect()
-        });
-    }
-    fn on_disabled(_validator_index: usize) {
-        DISABLED.with(|l| *l.borrow_mut() = true)
-    }
-    fn on_before_session_ending() {
-        BEFORE_SESSION_END_CALLED.with(|b| *b.borrow_mut() = true);
     }
+    fn on_disabled(_validator_index: usize) {}
 }
 
-pub struct TestSessionManager;
-impl SessionManager<u64> for TestSessionManager {
-    fn end_session(_: SessionIndex) {}
-    fn start_session(_: SessionIndex) {}
-    fn new_session(_: SessionIndex) -> Option<Vec<u64>> {
-        if !TEST_SESSION_CHANGED.with(|l| *l.borrow()) {
-            VALIDATORS.with(|v| {
-                let mut v = v.borrow_mut();
-                *v = NEXT_VALIDATORS.with(|l| l.borrow().clone());
-                Some(v.clone())
-            })
-        } else if DISABLED.with(|l| std::mem::replace(&mut *l.borrow_mut(), false)) {
-            // If there was a disabled validator, underlying conditions have changed
-            // so we return `Some`.
-            Some(VALIDATORS.with(|v| v.borrow().clone()))
-        } else {
-            None
-        }
-    }
+parameter_types! {
+    pub const Period: u64 = 1;
+    pub const Offset: u64 = 0;
+    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(16);
 }
 
 impl pallet_session::Trait for Test {
     type Event = ();
     type ValidatorId = <Self as frame_system::Trait>::AccountId;
     type ValidatorIdOf = pallet_staking::StashOf<Self>;
-    type ShouldEndSession = TestShouldEndSession; //Babe;
-    type NextSessionRotation = ();
-    type SessionManager = TestSessionManager; //pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
-    type SessionHandler = TestSessionHandler; //<MockSessionKeys as OpaqueKeys>::KeyTypeIdProviders;
-    type Keys = MockSessionKeys;
+    type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+    type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+    type SessionManager = ();
+    type SessionHandler = TestSessionHandler;
+    type Keys = UintAuthorityId;
     type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
     type WeightInfo = ();
 }
@@ -259,10 +209,6 @@ impl pallet_session::historical::Trait for Test {
     type FullIdentificationOf = pallet_staking::ExposureOf<Self>;
 }
 
-parameter_types! {
-    pub const UncleGenerations: u64 = 0;
-}
-
 parameter_types! {
     pub const MinimumPeriod: u64 = 1;
 }
@@ -274,20 +220,6 @@ impl pallet_timestamp::Trait for Test {
     type WeightInfo = ();
 }
 
-parameter_types! {
-    pub const ExistentialDeposit: u64 = 1;
-}
-
-impl pallet_balances::Trait for Test {
-    type MaxLocks = ();
-    type Balance = u64;
-    type DustRemoval = ();
-    type Event = ();
-    type ExistentialDeposit = ExistentialDeposit;
-    type AccountStore = System;
-    type WeightInfo = ();
-}
-
 pallet_staking_reward_curve::build! {
     const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
         min_inflation: 0_025_000u64,
@@ -303,7 +235,6 @@ parameter_types! {
     pub const SessionsPerEra: SessionIndex = 3;
     pub const BondingDuration: EraIndex = 3;
     pub const SlashDeferDuration: EraIndex = 0;
-    pub const AttestationPeriod: u64 = 100;
     pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
     pub const MaxNominatorRewardedPerValidator: u32 = 64;
     pub const ElectionLookahead: u64 = 0;
@@ -312,12 +243,6 @@ parameter_types! {
 
 pub struct CurrencyToVoteHandler;
 
-impl Convert<u128, u128> for CurrencyToVoteHandler {
-    fn convert(x: u128) -> u128 {
-        x
-    }
-}
-
 impl Convert<u128, u64> for CurrencyToVoteHandler {
     fn convert(x: u128) -> u64 {
         x.saturated_into()
@@ -355,37 +280,115 @@ impl pallet_staking::Trait for Test {
 }
 
 parameter_types! {
-    pub OffencesWeightSoftLimit: Weight = Perbill::from_percent(60) * MaximumBlockWeight::get();
+    pub const MaxNominatorMissions: u32 = 4;
 }
 
-impl pallet_offences::Trait for Test {
+impl pallet_validator_registry::Trait for Test {
     type Event = ();
-    type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
-    type OnOffenceHandler = Staking;
-    type WeightSoftLimit = OffencesWeightSoftLimit;
+    type MaxNominatorMissions = MaxNominatorMissions;
 }
 
-impl pallet_mission_tokens::Trait for Test {
-    type Event = ();
-    type Balance = u64;
-    type MissionTokenId = u32;
+parameter_types! {
+    pub const MissionRewardRate: Perbill = Perbill::from_percent(50);
+    pub const MissionRewardPerPoint: u64 = 10;
+    pub const MaxEraRewardsPerBlock: u32 = 4;
+    pub const MaxRoundProjects: u32 = 3;
+    pub const MaxRoundContributions: u32 = 2;
+    pub const MaxRoundContributors: u32 = 2;
+    pub const MaxBountyMilestones: u32 = 3;
+    pub const MaxActiveChildBounties: u32 = 2;
+    pub const EscalationPeriod: u64 = 10;
+    pub const MissionVotingPeriod: u64 = 5;
+    pub const MissionVoteQuorum: Perbill = Perbill::from_percent(10);
+    pub const MaxMissionVoters: u32 = 3;
 }
 
-impl pallet_validator_registry::Trait for Test {
-    type Event = ();
-    type MissionId = u32;
+thread_local! {
+    static BLOCKED_CONTRIBUTORS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static REFERENDUM_COUNT: RefCell<ReferendumIndex> = RefCell::new(0);
+}
+
+/// Allows every account to contribute, except those blocked by `block_contributor`.
+pub struct TestContributorFilter;
+impl Filter<u64> for TestContributorFilter {
+    fn filter(who: &u64) -> bool {
+        BLOCKED_CONTRIBUTORS.with(|v| !v.borrow().contains(who))
+    }
+}
+
+pub fn block_contributor(who: u64) {
+    BLOCKED_CONTRIBUTORS.with(|v| v.borrow_mut().push(who));
+}
+
+/// Starts referenda by counting them, reserving a deposit of 1 from the escalating account.
+pub struct TestEscalate;
+impl EscalateProposal<u64> for TestEscalate {
+    fn escalate(who: &u64, _proposal_id: ProposalIndex) -> Result<ReferendumIndex, DispatchError> {
+        Balances::reserve(who, 1)?;
+        Ok(REFERENDUM_COUNT.with(|c| {
+            *c.borrow_mut() += 1;
+            *c.borrow() - 1
+        }))
+    }
 }
 
 impl Trait for Test {
+    type ApproveOrigin = frame_system::EnsureRoot<u64>;
+    type RejectOrigin = frame_system::EnsureRoot<u64>;
+    type Tippers = TenToFourteen;
+    type TipCountdown = TipCountdown;
+    type TipFindersFee = TipFindersFee;
+    type TipReportDepositBase = TipReportDepositBase;
+    type DataDepositPerByte = DataDepositPerByte;
     type Event = ();
+    type OnSlash = ();
+    type ProposalBond = ProposalBond;
+    type ProposalBondMinimum = ProposalBondMinimum;
+    type SpendPeriod = SpendPeriod;
+    type Burn = Burn;
+    type BountyDepositBase = BountyDepositBase;
+    type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+    type BountyUpdatePeriod = BountyUpdatePeriod;
+    type BountyCuratorDeposit = BountyCuratorDeposit;
+    type BountyValueMinimum = BountyValueMinimum;
+    type MaximumReasonLength = MaximumReasonLength;
+    type MaxBountyMilestones = MaxBountyMilestones;
+    type MaxActiveChildBounties = MaxActiveChildBounties;
+    type BurnDestination = ();
+    type MissionRewardCurve = crate::RewardPointsRate<MissionRewardRate, MissionRewardPerPoint>;
+    type MaxEraRewardsPerBlock = MaxEraRewardsPerBlock;
+    type MaxRoundProjects = MaxRoundProjects;
+    type MaxRoundContributions = MaxRoundContributions;
+    type MaxRoundContributors = MaxRoundContributors;
+    type ContributorFilter = TestContributorFilter;
+    type EscalationPeriod = EscalationPeriod;
+    type Escalate = TestEscalate;
+    type MissionVotingPeriod = MissionVotingPeriod;
+    type MissionVoteQuorum = MissionVoteQuorum;
+    type MaxMissionVoters = MaxMissionVoters;
+    type WeightInfo = ();
 }
 
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type MissionTokens = pallet_mission_tokens::Module<Test>;
+pub type Session = pallet_session::Module<Test>;
+pub type Staking = pallet_staking::Module<Test>;
+pub type ValidatorRegistry = pallet_validator_registry::Module<Test>;
 pub type SocialTreasury = Module<Test>;
 
-// Build genesis storage according to the mock runtime.
+// Build genesis storage according to the mock runtime, with 100 of mission token 1 for accounts
+// 1 to 4.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let t = system::GenesisConfig::default()
         .build_storage::<Test>()
-        .unwrap()
-        .into()
+        .unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        for who in 1..=4 {
+            MissionTokens::mint(who, 1, 100);
+        }
+    });
+    ext
 }
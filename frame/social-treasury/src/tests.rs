@@ -1,20 +1,890 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+    mock::*, BountyStatus, EraMissionPoints, Error, ProposalSubmittedAt, StreamInterval, StreamStatus,
+};
+use frame_support::{
+    assert_noop, assert_ok, storage::IterableStorageMap, traits::OnInitialize, StorageDoubleMap,
+    StorageMap, StorageValue,
+};
+use pallet_staking::{ActiveEraInfo, EraIndex, Exposure, IndividualExposure};
+
+fn set_active_era(era: EraIndex) {
+    pallet_staking::ActiveEra::put(ActiveEraInfo {
+        index: era,
+        start: None,
+    });
+}
+
+fn expose(era: EraIndex, validator: u64, own: u64, nominators: &[(u64, u64)]) {
+    pallet_staking::ErasStakersClipped::<Test>::insert(
+        era,
+        validator,
+        Exposure {
+            total: own + nominators.iter().map(|(_, value)| value).sum::<u64>(),
+            own,
+            others: nominators
+                .iter()
+                .map(|(who, value)| IndividualExposure {
+                    who: *who,
+                    value: *value,
+                })
+                .collect(),
+        },
+    );
+}
+
+fn pot(mission: u32) -> u64 {
+    MissionTokens::free_balance(SocialTreasury::account_id(), mission)
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        SocialTreasury::on_initialize(System::block_number());
+    }
+}
+
+fn fund_treasury(mission: u32, value: u64) {
+    MissionTokens::mint(SocialTreasury::account_id(), mission, value);
+}
+
+/// Create bounty 0 of mission 1, funded at block 2 and curated by account 4.
+fn create_active_bounty(value: u64, fee: u64) {
+    fund_treasury(1, 1000);
+    assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), value, vec![], 1));
+    assert_ok!(SocialTreasury::approve_bounty(Origin::root(), 0));
+    run_to_block(2);
+    assert_ok!(SocialTreasury::propose_curator(Origin::root(), 0, 4, fee));
+    assert_ok!(SocialTreasury::accept_curator(Origin::signed(4), 0));
+}
+
+#[test]
+fn reward_points_rate_should_work() {
+    use sp_runtime::traits::Convert;
+
+    type Curve = crate::RewardPointsRate<MissionRewardRate, MissionRewardPerPoint>;
+    assert_eq!(<Curve as Convert<u32, u64>>::convert(0), 0);
+    assert_eq!(<Curve as Convert<u32, u64>>::convert(20), 100);
+}
+
+#[test]
+fn era_rewards_should_be_minted_to_missions() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorRegistry::register(Origin::signed(21), 2));
+        assert_ok!(ValidatorRegistry::register(Origin::signed(31), 3));
+        assert_ok!(ValidatorRegistry::set_nominator_missions(
+            Origin::signed(41),
+            vec![(4, 1)]
+        ));
+        ValidatorRegistry::note_era_missions(0, &[21, 31], 84);
+        ValidatorRegistry::note_era_nominator_missions(0, &[41]);
+        expose(0, 21, 50, &[(41, 50)]);
+        expose(0, 31, 100, &[]);
+
+        set_active_era(0);
+        Staking::reward_by_ids(vec![(21, 20), (31, 30)]);
+
+        // The active era is not processed.
+        SocialTreasury::on_initialize(1);
+        assert_eq!(SocialTreasury::next_era_for_processing(), None);
+        assert_eq!(SocialTreasury::era_rewards_processed(), 0);
+
+        set_active_era(1);
+        // Both validators and the nominator use 3 of the 4 units of budget, so only one of the
+        // three missions is minted to in this block.
+        SocialTreasury::on_initialize(3);
+        assert_eq!(SocialTreasury::era_rewards_processed(), 2);
+        assert_eq!(EraMissionPoints::<Test>::iter().count(), 2);
+        assert_eq!(SocialTreasury::next_era_for_processing(), None);
+
+        SocialTreasury::on_initialize(5);
+        assert_eq!(EraMissionPoints::<Test>::iter().count(), 0);
+        assert_eq!(SocialTreasury::era_rewards_processed(), 0);
+        assert_eq!(SocialTreasury::next_era_for_processing(), Some(1));
+
+        // The nominator's half of the validator points goes to its own mission.
+        assert_eq!(pot(2), 50);
+        assert_eq!(pot(4), 50);
+        assert_eq!(pot(3), 150);
+    });
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn era_rewards_budget_should_count_nominators() {
     new_test_ext().execute_with(|| {
-        assert_ok!(SocialTreasury::do_something(Origin::signed(1), 42));
-        assert_eq!(SocialTreasury::something(), Some(42));
+        assert_ok!(ValidatorRegistry::register(Origin::signed(21), 2));
+        assert_ok!(ValidatorRegistry::register(Origin::signed(31), 3));
+        ValidatorRegistry::note_era_missions(0, &[21, 31], 84);
+        expose(0, 21, 10, &[(41, 10), (42, 10), (43, 10), (44, 10), (45, 10)]);
+        expose(0, 31, 10, &[]);
+
+        set_active_era(0);
+        Staking::reward_by_ids(vec![(21, 20), (31, 20)]);
+        set_active_era(1);
+
+        // The first validator exceeds the budget on its own, but is still processed so that
+        // processing makes progress.
+        SocialTreasury::on_initialize(1);
+        assert_eq!(SocialTreasury::era_rewards_processed(), 1);
+        assert_eq!(pot(2), 0);
+
+        SocialTreasury::on_initialize(3);
+        assert_eq!(SocialTreasury::next_era_for_processing(), Some(1));
+        assert_eq!(pot(2), 100);
+        assert_eq!(pot(3), 100);
     });
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn era_rewards_budget_should_carry_over_between_eras() {
     new_test_ext().execute_with(|| {
+        set_active_era(6);
+
+        // Eras without reward points cost one unit of budget each.
+        SocialTreasury::on_initialize(1);
+        assert_eq!(SocialTreasury::next_era_for_processing(), Some(4));
+
+        SocialTreasury::on_initialize(3);
+        assert_eq!(SocialTreasury::next_era_for_processing(), Some(6));
+    });
+}
+
+#[test]
+fn propose_stream_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::propose_stream(Origin::signed(1), 0, StreamInterval::Block, 2, 5, 1),
+            Error::<Test>::InvalidStream
+        );
+        assert_noop!(
+            SocialTreasury::propose_stream(Origin::signed(1), 10, StreamInterval::Block, 0, 5, 1),
+            Error::<Test>::InvalidStream
+        );
+        assert_noop!(
+            SocialTreasury::propose_stream(Origin::signed(9), 10, StreamInterval::Block, 2, 5, 1),
+            Error::<Test>::InsufficientProposersBalance
+        );
+
+        assert_ok!(SocialTreasury::propose_stream(
+            Origin::signed(1),
+            10,
+            StreamInterval::Block,
+            2,
+            5,
+            1
+        ));
+        assert_eq!(SocialTreasury::stream_count(), 1);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 1);
+        assert_eq!(MissionTokens::free_balance(1, 1), 99);
+    });
+}
+
+#[test]
+fn stream_should_pay_out_accrued_periods() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_ok!(SocialTreasury::propose_stream(
+            Origin::signed(1),
+            10,
+            StreamInterval::Block,
+            2,
+            5,
+            1
+        ));
+        assert_ok!(SocialTreasury::approve_stream(Origin::root(), 0));
+        assert_noop!(
+            SocialTreasury::approve_stream(Origin::root(), 0),
+            Error::<Test>::UnexpectedStatus
+        );
+        assert_noop!(
+            SocialTreasury::claim_stream(Origin::signed(7), 0),
+            Error::<Test>::UnexpectedStatus
+        );
+
+        // Funded at the next spend period, returning the bond.
+        run_to_block(2);
+        assert_eq!(
+            SocialTreasury::streams(0).unwrap().status,
+            StreamStatus::Active { start: 2 }
+        );
+        assert_eq!(MissionTokens::free_balance(SocialTreasury::stream_account_id(0), 1), 20);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+        assert_eq!(MissionTokens::free_balance(1, 1), 100);
+        assert_noop!(
+            SocialTreasury::claim_stream(Origin::signed(7), 0),
+            Error::<Test>::NothingToClaim
+        );
+
+        run_to_block(3);
+        assert_ok!(SocialTreasury::claim_stream(Origin::signed(7), 0));
+        assert_eq!(MissionTokens::free_balance(5, 1), 10);
+        assert_noop!(
+            SocialTreasury::claim_stream(Origin::signed(7), 0),
+            Error::<Test>::NothingToClaim
+        );
+
+        // Payments stop after the last period and the fully paid stream is removed.
+        run_to_block(7);
+        assert_ok!(SocialTreasury::claim_stream(Origin::signed(7), 0));
+        assert_eq!(MissionTokens::free_balance(5, 1), 20);
+        assert_eq!(SocialTreasury::streams(0), None);
+    });
+}
+
+#[test]
+fn claim_stream_should_fail_if_the_stream_account_cannot_pay() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_ok!(SocialTreasury::propose_stream(
+            Origin::signed(1),
+            10,
+            StreamInterval::Block,
+            2,
+            5,
+            1
+        ));
+        assert_ok!(SocialTreasury::approve_stream(Origin::root(), 0));
+        run_to_block(3);
+
+        let stream_account = SocialTreasury::stream_account_id(0);
+        assert_ok!(MissionTokens::reserve(&stream_account, 1, 15));
+        assert_noop!(
+            SocialTreasury::claim_stream(Origin::signed(7), 0),
+            pallet_mission_tokens::Error::<Test>::InsufficientBalance
+        );
+        assert_eq!(SocialTreasury::streams(0).unwrap().claimed, 0);
+    });
+}
+
+#[test]
+fn cancel_stream_should_work() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+
+        // A proposed stream is rejected, slashing the bond.
+        assert_ok!(SocialTreasury::propose_stream(
+            Origin::signed(1),
+            10,
+            StreamInterval::Block,
+            2,
+            5,
+            1
+        ));
         assert_noop!(
-            SocialTreasury::cause_error(Origin::signed(1)),
-            Error::<Test>::NoneValue
+            SocialTreasury::cancel_stream(Origin::signed(1), 0),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(SocialTreasury::cancel_stream(Origin::root(), 0));
+        assert_eq!(SocialTreasury::streams(0), None);
+        assert_eq!(MissionTokens::reserved_balance(1, 1), 0);
+        assert_eq!(MissionTokens::free_balance(1, 1), 99);
+
+        // An approved stream cannot be cancelled until it is funded.
+        assert_ok!(SocialTreasury::propose_stream(
+            Origin::signed(1),
+            10,
+            StreamInterval::Block,
+            2,
+            5,
+            1
+        ));
+        assert_ok!(SocialTreasury::approve_stream(Origin::root(), 1));
+        assert_noop!(
+            SocialTreasury::cancel_stream(Origin::root(), 1),
+            Error::<Test>::UnexpectedStatus
+        );
+
+        // An active stream pays what accrued and returns the remainder to the pot.
+        run_to_block(3);
+        let pot_before = pot(1);
+        assert_ok!(SocialTreasury::cancel_stream(Origin::root(), 1));
+        assert_eq!(MissionTokens::free_balance(5, 1), 10);
+        assert_eq!(pot(1), pot_before + 10);
+        assert_eq!(SocialTreasury::streams(1), None);
+    });
+}
+
+#[test]
+fn open_round_should_work() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_noop!(
+            SocialTreasury::open_round(Origin::signed(1), 1, 100, 10),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_noop!(
+            SocialTreasury::open_round(Origin::root(), 1, 1000, 10),
+            Error::<Test>::InsufficientPot
+        );
+
+        assert_ok!(SocialTreasury::open_round(Origin::root(), 1, 100, 10));
+        assert_eq!(SocialTreasury::round_count(), 1);
+        assert_eq!(pot(1), 899);
+        assert_eq!(
+            MissionTokens::free_balance(SocialTreasury::round_account_id(0), 1),
+            100
+        );
+    });
+}
+
+#[test]
+fn register_project_should_work() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_noop!(
+            SocialTreasury::register_project(Origin::signed(5), 0),
+            Error::<Test>::InvalidIndex
+        );
+        assert_ok!(SocialTreasury::open_round(Origin::root(), 1, 100, 10));
+
+        assert_ok!(SocialTreasury::register_project(Origin::signed(5), 0));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(6), 0));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(7), 0));
+        assert_noop!(
+            SocialTreasury::register_project(Origin::signed(8), 0),
+            Error::<Test>::TooManyProjects
+        );
+        assert_eq!(SocialTreasury::round_projects(0, 2).unwrap().owner, 7);
+
+        System::set_block_number(11);
+        assert_noop!(
+            SocialTreasury::register_project(Origin::signed(8), 0),
+            Error::<Test>::RoundEnded
+        );
+    });
+}
+
+#[test]
+fn contribute_should_work() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_ok!(SocialTreasury::open_round(Origin::root(), 1, 100, 10));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(5), 0));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(6), 0));
+
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(1), 0, 0, 0),
+            Error::<Test>::InvalidValue
+        );
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(1), 0, 2, 9),
+            Error::<Test>::InvalidIndex
+        );
+        block_contributor(4);
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(4), 0, 0, 9),
+            Error::<Test>::ContributorNotAllowed
+        );
+
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 5));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 4));
+        assert_eq!(SocialTreasury::round_contributions(0, (0, 1)), 9);
+        assert_eq!(SocialTreasury::round_projects(0, 0).unwrap().sqrt_sum, 3);
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(1), 0, 1, 4),
+            Error::<Test>::TooManyContributions
+        );
+
+        assert_ok!(SocialTreasury::contribute(Origin::signed(2), 0, 1, 4));
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(3), 0, 1, 4),
+            Error::<Test>::TooManyContributors
+        );
+        assert_eq!(SocialTreasury::rounds(0).unwrap().contributors, 2);
+        assert_eq!(MissionTokens::free_balance(1, 1), 91);
+
+        System::set_block_number(11);
+        assert_noop!(
+            SocialTreasury::contribute(Origin::signed(2), 0, 1, 4),
+            Error::<Test>::RoundEnded
+        );
+    });
+}
+
+#[test]
+fn close_round_should_pay_contributions_and_matching() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_ok!(SocialTreasury::open_round(Origin::root(), 1, 100, 10));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(5), 0));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(6), 0));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 9));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 1, 4));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(2), 0, 0, 16));
+
+        assert_noop!(
+            SocialTreasury::close_round(Origin::signed(7), 0),
+            Error::<Test>::Premature
+        );
+
+        System::set_block_number(11);
+        assert_ok!(SocialTreasury::close_round(Origin::signed(7), 0));
+        // (√9 + √16)² - 25 = 24 is matched to the first project; a single contributor gets no
+        // matching.
+        assert_eq!(MissionTokens::free_balance(5, 1), 49);
+        assert_eq!(MissionTokens::free_balance(6, 1), 4);
+        assert_eq!(pot(1), 975);
+
+        assert_eq!(SocialTreasury::rounds(0), None);
+        assert_eq!(SocialTreasury::round_projects(0, 0), None);
+        assert_eq!(SocialTreasury::round_contributions(0, (0, 1)), 0);
+        assert_eq!(SocialTreasury::round_contribution_count(0, 1), 0);
+        assert_noop!(
+            SocialTreasury::close_round(Origin::signed(7), 0),
+            Error::<Test>::InvalidIndex
+        );
+    });
+}
+
+#[test]
+fn close_round_should_scale_matching_to_funds() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 1000);
+        assert_ok!(SocialTreasury::open_round(Origin::root(), 1, 12, 10));
+        assert_ok!(SocialTreasury::register_project(Origin::signed(5), 0));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(1), 0, 0, 9));
+        assert_ok!(SocialTreasury::contribute(Origin::signed(2), 0, 0, 16));
+
+        System::set_block_number(11);
+        assert_ok!(SocialTreasury::close_round(Origin::signed(7), 0));
+        assert_eq!(MissionTokens::free_balance(5, 1), 37);
+        assert_eq!(pot(1), 987);
+    });
+}
+
+#[test]
+fn set_bounty_milestones_should_work() {
+    new_test_ext().execute_with(|| {
+        create_active_bounty(100, 10);
+
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(5), 0, vec![(30, 5)]),
+            Error::<Test>::RequireCurator
+        );
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(
+                Origin::signed(4),
+                0,
+                vec![(10, 5), (10, 6), (10, 7), (10, 8)]
+            ),
+            Error::<Test>::TooManyMilestones
+        );
+        // The milestones cannot pay out the curator fee.
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(50, 5), (41, 8)]),
+            Error::<Test>::InvalidValue
+        );
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(30, 2)]),
+            Error::<Test>::InvalidMilestoneDue
+        );
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(30, 8), (40, 5)]),
+            Error::<Test>::InvalidMilestoneDue
+        );
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(30, 5), (40, 5)]),
+            Error::<Test>::InvalidMilestoneDue
+        );
+
+        assert_ok!(SocialTreasury::set_bounty_milestones(
+            Origin::signed(4),
+            0,
+            vec![(30, 5), (40, 8)]
+        ));
+        assert_eq!(SocialTreasury::bounty_milestones(0).len(), 2);
+        assert_eq!(
+            SocialTreasury::bounties(0).unwrap().status,
+            BountyStatus::Active {
+                curator: 4,
+                update_due: 5
+            }
+        );
+    });
+}
+
+#[test]
+fn release_milestone_should_work() {
+    new_test_ext().execute_with(|| {
+        create_active_bounty(100, 10);
+        assert_noop!(
+            SocialTreasury::release_milestone(Origin::signed(4), 0, 6),
+            Error::<Test>::NoMilestone
+        );
+        assert_ok!(SocialTreasury::set_bounty_milestones(
+            Origin::signed(4),
+            0,
+            vec![(30, 5), (40, 8)]
+        ));
+        assert_noop!(
+            SocialTreasury::release_milestone(Origin::signed(5), 0, 6),
+            Error::<Test>::RequireCurator
+        );
+
+        assert_ok!(SocialTreasury::release_milestone(Origin::signed(4), 0, 6));
+        assert_eq!(MissionTokens::free_balance(6, 1), 30);
+        assert_eq!(SocialTreasury::bounties(0).unwrap().value, 70);
+        assert_eq!(
+            SocialTreasury::bounties(0).unwrap().status,
+            BountyStatus::Active {
+                curator: 4,
+                update_due: 8
+            }
+        );
+        assert_noop!(
+            SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(30, 9)]),
+            Error::<Test>::MilestonesReleased
+        );
+
+        // The last milestone gives the curator a regular update period.
+        assert_ok!(SocialTreasury::release_milestone(Origin::signed(4), 0, 6));
+        assert_eq!(MissionTokens::free_balance(6, 1), 70);
+        assert_eq!(
+            SocialTreasury::bounties(0).unwrap().status,
+            BountyStatus::Active {
+                curator: 4,
+                update_due: 22
+            }
+        );
+        assert_noop!(
+            SocialTreasury::release_milestone(Origin::signed(4), 0, 6),
+            Error::<Test>::NoMilestone
+        );
+    });
+}
+
+#[test]
+fn add_child_bounty_should_work() {
+    new_test_ext().execute_with(|| {
+        create_active_bounty(100, 10);
+        assert_ok!(SocialTreasury::set_bounty_milestones(Origin::signed(4), 0, vec![(50, 5)]));
+
+        assert_noop!(
+            SocialTreasury::add_child_bounty(Origin::signed(5), 0, 20, vec![]),
+            Error::<Test>::RequireCurator
+        );
+        // Only the value net of the fee and unreleased milestones can be moved.
+        assert_noop!(
+            SocialTreasury::add_child_bounty(Origin::signed(4), 0, 41, vec![]),
+            Error::<Test>::InvalidValue
+        );
+
+        assert_ok!(SocialTreasury::add_child_bounty(Origin::signed(4), 0, 20, b"a".to_vec()));
+        assert_ok!(SocialTreasury::add_child_bounty(Origin::signed(4), 0, 20, b"b".to_vec()));
+        assert_eq!(SocialTreasury::bounties(0).unwrap().value, 60);
+        assert_eq!(SocialTreasury::active_child_bounty_count(0), 2);
+        assert_eq!(
+            MissionTokens::free_balance(SocialTreasury::child_bounty_account_id(0, 1), 1),
+            20
+        );
+        assert_noop!(
+            SocialTreasury::add_child_bounty(Origin::signed(4), 0, 1, vec![]),
+            Error::<Test>::TooManyChildBounties
+        );
+    });
+}
+
+#[test]
+fn child_bounty_should_pay_out_and_close() {
+    new_test_ext().execute_with(|| {
+        create_active_bounty(100, 10);
+        assert_ok!(SocialTreasury::add_child_bounty(Origin::signed(4), 0, 20, vec![]));
+        assert_ok!(SocialTreasury::add_child_bounty(Origin::signed(4), 0, 20, vec![]));
+
+        // The child curator fee comes out of the parent curator fee.
+        assert_noop!(
+            SocialTreasury::propose_child_curator(Origin::signed(4), 0, 0, 3, 11),
+            Error::<Test>::InvalidFee
+        );
+        assert_ok!(SocialTreasury::propose_child_curator(Origin::signed(4), 0, 0, 3, 4));
+        assert_eq!(SocialTreasury::bounties(0).unwrap().fee, 6);
+        assert_noop!(
+            SocialTreasury::accept_child_curator(Origin::signed(4), 0, 0),
+            Error::<Test>::RequireCurator
+        );
+        assert_ok!(SocialTreasury::accept_child_curator(Origin::signed(3), 0, 0));
+        assert_eq!(MissionTokens::reserved_balance(3, 1), 2);
+
+        assert_ok!(SocialTreasury::award_child_bounty(Origin::signed(3), 0, 0, 8));
+        assert_noop!(
+            SocialTreasury::close_child_bounty(Origin::signed(4), 0, 0),
+            Error::<Test>::PendingPayout
+        );
+        assert_noop!(
+            SocialTreasury::claim_child_bounty(Origin::signed(9), 0, 0),
+            Error::<Test>::Premature
+        );
+
+        System::set_block_number(5);
+        assert_ok!(SocialTreasury::claim_child_bounty(Origin::signed(9), 0, 0));
+        assert_eq!(MissionTokens::free_balance(8, 1), 16);
+        assert_eq!(MissionTokens::free_balance(3, 1), 104);
+        assert_eq!(MissionTokens::reserved_balance(3, 1), 0);
+        assert_eq!(SocialTreasury::child_bounties(0, 0), None);
+
+        // Closing returns the funds to the parent bounty.
+        assert_noop!(
+            SocialTreasury::close_child_bounty(Origin::signed(5), 0, 1),
+            Error::<Test>::RequireCurator
+        );
+        assert_ok!(SocialTreasury::close_child_bounty(Origin::signed(4), 0, 1));
+        assert_eq!(SocialTreasury::bounties(0).unwrap().value, 80);
+        assert_eq!(SocialTreasury::active_child_bounty_count(0), 0);
+        assert_eq!(
+            MissionTokens::free_balance(SocialTreasury::child_bounty_account_id(0, 1), 1),
+            0
+        );
+    });
+}
+
+#[test]
+fn escalate_proposal_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::escalate_proposal(Origin::signed(2), 0),
+            Error::<Test>::InvalidIndex
+        );
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_noop!(
+            SocialTreasury::escalate_proposal(Origin::signed(2), 0),
+            Error::<Test>::Premature
+        );
+
+        System::set_block_number(11);
+        assert_ok!(SocialTreasury::escalate_proposal(Origin::signed(2), 0));
+        assert_eq!(SocialTreasury::escalated_proposals(0), Some(0));
+        // The escalating account pays the referendum deposit, not the treasury.
+        assert_eq!(Balances::reserved_balance(2), 1);
+        assert_eq!(Balances::reserved_balance(SocialTreasury::account_id()), 0);
+        assert_noop!(
+            SocialTreasury::escalate_proposal(Origin::signed(2), 0),
+            Error::<Test>::AlreadyEscalated
+        );
+
+        assert_ok!(SocialTreasury::approve_proposal(Origin::root(), 1));
+        assert_noop!(
+            SocialTreasury::escalate_proposal(Origin::signed(2), 1),
+            Error::<Test>::AlreadyApproved
+        );
+
+        // Rejecting an escalated proposal forgets its referendum.
+        assert_ok!(SocialTreasury::reject_proposal(Origin::root(), 0));
+        assert_eq!(SocialTreasury::escalated_proposals(0), None);
+    });
+}
+
+#[test]
+fn proposals_made_before_escalation_should_not_be_escalated() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        // A proposal made before escalation was introduced has no submission block.
+        <ProposalSubmittedAt<Test>>::remove(0);
+
+        System::set_block_number(100);
+        assert_noop!(
+            SocialTreasury::escalate_proposal(Origin::signed(2), 0),
+            Error::<Test>::NotEscalatable
+        );
+    });
+}
+
+#[test]
+fn open_mission_vote_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            SocialTreasury::open_mission_vote(Origin::signed(2), 0),
+            Error::<Test>::InvalidIndex
+        );
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_ok!(SocialTreasury::open_mission_vote(Origin::signed(2), 0));
+        assert_noop!(
+            SocialTreasury::open_mission_vote(Origin::signed(2), 0),
+            Error::<Test>::VoteInProgress
+        );
+
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_ok!(SocialTreasury::approve_proposal(Origin::root(), 1));
+        assert_noop!(
+            SocialTreasury::open_mission_vote(Origin::signed(2), 1),
+            Error::<Test>::AlreadyApproved
+        );
+    });
+}
+
+#[test]
+fn vote_on_proposal_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_noop!(
+            SocialTreasury::vote_on_proposal(Origin::signed(2), 0, true, 20),
+            Error::<Test>::NoMissionVote
+        );
+        assert_ok!(SocialTreasury::open_mission_vote(Origin::signed(2), 0));
+        assert_noop!(
+            SocialTreasury::vote_on_proposal(Origin::signed(2), 0, true, 0),
+            Error::<Test>::InvalidValue
+        );
+
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(2), 0, true, 20));
+        assert_eq!(MissionTokens::reserved_balance(2, 1), 20);
+
+        // A failed replacement keeps the previous vote locked.
+        assert_noop!(
+            SocialTreasury::vote_on_proposal(Origin::signed(2), 0, false, 200),
+            pallet_mission_tokens::Error::<Test>::InsufficientBalance
+        );
+        assert_eq!(MissionTokens::reserved_balance(2, 1), 20);
+        assert_eq!(SocialTreasury::mission_votes(0).unwrap().ayes, 20);
+
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(2), 0, false, 5));
+        assert_eq!(MissionTokens::reserved_balance(2, 1), 5);
+        assert_eq!(SocialTreasury::mission_vote_of(0, 2), Some((false, 5)));
+        let vote = SocialTreasury::mission_votes(0).unwrap();
+        assert_eq!((vote.ayes, vote.nays, vote.voters), (0, 5, 1));
+
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(3), 0, true, 5));
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(4), 0, true, 5));
+        assert_noop!(
+            SocialTreasury::vote_on_proposal(Origin::signed(1), 0, true, 5),
+            Error::<Test>::TooManyVoters
+        );
+
+        System::set_block_number(6);
+        assert_noop!(
+            SocialTreasury::vote_on_proposal(Origin::signed(3), 0, true, 10),
+            Error::<Test>::VoteEnded
+        );
+    });
+}
+
+#[test]
+fn close_mission_vote_should_approve_with_quorum() {
+    new_test_ext().execute_with(|| {
+        pallet_mission_tokens::TotalIssuance::<Test>::insert(1, 400);
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_ok!(SocialTreasury::open_mission_vote(Origin::signed(2), 0));
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(2), 0, true, 30));
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(3), 0, false, 10));
+
+        assert_noop!(
+            SocialTreasury::close_mission_vote(Origin::signed(4), 0),
+            Error::<Test>::Premature
+        );
+
+        System::set_block_number(6);
+        assert_ok!(SocialTreasury::close_mission_vote(Origin::signed(4), 0));
+        assert_eq!(SocialTreasury::approvals(), vec![0]);
+        assert_eq!(MissionTokens::reserved_balance(2, 1), 0);
+        assert_eq!(MissionTokens::reserved_balance(3, 1), 0);
+        assert_eq!(SocialTreasury::mission_votes(0), None);
+        assert_eq!(SocialTreasury::mission_vote_of(0, 2), None);
+        assert_noop!(
+            SocialTreasury::close_mission_vote(Origin::signed(4), 0),
+            Error::<Test>::NoMissionVote
+        );
+    });
+}
+
+#[test]
+fn close_mission_vote_should_not_approve_without_quorum() {
+    new_test_ext().execute_with(|| {
+        pallet_mission_tokens::TotalIssuance::<Test>::insert(1, 400);
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 10, 5, 1));
+        assert_ok!(SocialTreasury::open_mission_vote(Origin::signed(2), 0));
+        assert_ok!(SocialTreasury::vote_on_proposal(Origin::signed(2), 0, true, 30));
+
+        System::set_block_number(6);
+        assert_ok!(SocialTreasury::close_mission_vote(Origin::signed(4), 0));
+        assert!(SocialTreasury::approvals().is_empty());
+        assert_eq!(MissionTokens::reserved_balance(2, 1), 0);
+    });
+}
+
+#[test]
+fn runtime_api_queries_should_work() {
+    new_test_ext().execute_with(|| {
+        fund_treasury(1, 101);
+        MissionTokens::mint(1, 2, 100);
+        MissionTokens::mint(4, 2, 100);
+
+        // Spends happen at multiples of `SpendPeriod`, i.e. every second block.
+        let pots = SocialTreasury::pots_info();
+        assert_eq!(pots.len(), 12);
+        assert_eq!(pots[0], MissionPot { mission_token_id: 1, pot: 100, next_spend_at: 2 });
+        assert_eq!(pots[1], MissionPot { mission_token_id: 2, pot: 0, next_spend_at: 2 });
+        System::set_block_number(4);
+        assert_eq!(SocialTreasury::pots_info()[0].next_spend_at, 6);
+        System::set_block_number(5);
+        assert_eq!(SocialTreasury::pots_info()[0].next_spend_at, 6);
+
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 5, 1));
+        assert_ok!(SocialTreasury::propose_spend(Origin::signed(1), 100, 6, 2));
+        assert_ok!(SocialTreasury::approve_proposal(Origin::root(), 1));
+        let mut proposals = SocialTreasury::proposals_info(None);
+        proposals.sort_by_key(|p| p.index);
+        assert_eq!(
+            proposals,
+            vec![
+                ProposalInfo {
+                    index: 0,
+                    proposer: 1,
+                    value: 100,
+                    beneficiary: 5,
+                    bond: 5,
+                    approved: false,
+                    mission_token_id: 1,
+                },
+                ProposalInfo {
+                    index: 1,
+                    proposer: 1,
+                    value: 100,
+                    beneficiary: 6,
+                    bond: 5,
+                    approved: true,
+                    mission_token_id: 2,
+                },
+            ],
+        );
+        assert_eq!(SocialTreasury::proposals_info(Some(2)).len(), 1);
+        assert_eq!(SocialTreasury::proposals_info(Some(2))[0].index, 1);
+        assert!(SocialTreasury::proposals_info(Some(3)).is_empty());
+
+        // The median is taken over the tips of current tippers, rounding up.
+        assert_ok!(SocialTreasury::tip_new(Origin::signed(10), b"awesome.dot".to_vec(), 3, 1, 10));
+        assert_ok!(SocialTreasury::tip_new(Origin::signed(10), b"awesome.ksm".to_vec(), 3, 2, 40));
+        let tip = SocialTreasury::tips_info(Some(1)).pop().unwrap();
+        assert_eq!(tip.reason, b"awesome.dot".to_vec());
+        assert_eq!((tip.who, tip.finder, tip.tippers, tip.median, tip.closes), (3, 10, 1, Some(10), None));
+        assert_ok!(SocialTreasury::tip(Origin::signed(11), tip.hash, 30));
+        let tip = SocialTreasury::tips_info(Some(1)).pop().unwrap();
+        assert_eq!((tip.tippers, tip.median, tip.closes), (2, Some(30), None));
+        assert_ok!(SocialTreasury::tip(Origin::signed(12), tip.hash, 20));
+        let tip = SocialTreasury::tips_info(Some(1)).pop().unwrap();
+        assert_eq!((tip.tippers, tip.median, tip.closes), (3, Some(20), Some(6)));
+        assert_eq!(SocialTreasury::tips_info(Some(2))[0].median, Some(40));
+        assert_eq!(SocialTreasury::tips_info(None).len(), 2);
+
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(1), 50, b"docs".to_vec(), 1));
+        assert_ok!(SocialTreasury::propose_bounty(Origin::signed(4), 50, vec![], 2));
+        assert_ok!(SocialTreasury::approve_bounty(Origin::root(), 1));
+        assert_eq!(
+            SocialTreasury::bounties_info(Some(1)),
+            vec![BountyInfo {
+                index: 0,
+                proposer: 1,
+                value: 50,
+                fee: 0,
+                curator_deposit: 0,
+                bond: 84,
+                status: BountyStatusInfo::Proposed,
+                description: b"docs".to_vec(),
+                mission_token_id: 1,
+            }],
         );
+        let bounties = SocialTreasury::bounties_info(Some(2));
+        assert_eq!(bounties.len(), 1);
+        assert_eq!(bounties[0].index, 1);
+        assert_eq!(bounties[0].status, BountyStatusInfo::Approved);
+        assert_eq!(SocialTreasury::bounties_info(None).len(), 2);
     });
 }
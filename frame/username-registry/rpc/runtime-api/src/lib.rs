@@ -0,0 +1,105 @@
+//! Runtime API definition for username registry module.
+//!
+//! Resolves usernames to accounts, with the judgements of the registrars, and accounts to the
+//! usernames they hold. It also checks usernames against the registration rules, so that clients
+//! can validate input before submitting it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// The judgement of a registrar on a username.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum JudgementInfo<Balance> {
+	/// The judgement is requested, and the fee held for the registrar.
+	FeePaid(Balance),
+	/// The username is reasonable.
+	Reasonable,
+	/// The registrar knows the username to be held by who it claims.
+	KnownGood,
+	/// The username is of low quality.
+	LowQuality,
+	/// The username is misleading.
+	Erroneous,
+}
+
+/// The registration of a username.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct UsernameInfo<AccountId, Balance, BlockNumber> {
+	/// The account holding the username.
+	pub account_id: AccountId,
+	/// The account the username resolves to and that controls it: the owner of its DID identity
+	/// if linked to one, otherwise its holder.
+	pub controller: AccountId,
+	/// The judgements of the registrars, by registrar index.
+	pub judgements: Vec<(u32, JudgementInfo<Balance>)>,
+	/// The block at which the registration expires, unless renewed.
+	pub expiry: BlockNumber,
+	/// The DID identity the username is linked to, if any.
+	pub did: Option<AccountId>,
+	/// Whether the username can be used as an address, i.e. it is unexpired and approved if
+	/// required.
+	pub resolvable: bool,
+}
+
+/// The usernames held by an account.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct AccountUsernames {
+	/// The username shown for the account.
+	pub primary: Option<Vec<u8>>,
+	/// The usernames held, in order of registration.
+	pub usernames: Vec<Vec<u8>>,
+}
+
+/// Whether a username can be registered.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum UsernameValidity {
+	/// The username can be registered.
+	Available,
+	/// The username is shorter than the minimum length.
+	TooShort,
+	/// The username is longer than the maximum length.
+	TooLong,
+	/// The username contains characters other than `0-9`, `a-z`, `_`, `-` and `.`.
+	InvalidChars,
+	/// The username contains `.` and can only be created under its parent.
+	RequiresParent,
+	/// The username is registered.
+	Registered,
+	/// The username is being auctioned.
+	InAuction,
+	/// The username is reserved and can only be registered by the account nominated for it.
+	Reserved,
+	/// The username matches a blocked pattern.
+	Blocked,
+}
+
+sp_api::decl_runtime_apis! {
+	/// The API to resolve usernames of the username registry.
+	pub trait UsernameApi<AccountId, Balance, BlockNumber> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// The registration of `username`, if registered.
+		fn resolve(username: Vec<u8>) -> Option<UsernameInfo<AccountId, Balance, BlockNumber>>;
+
+		/// The usernames held by `account`.
+		fn usernames_of(account: AccountId) -> AccountUsernames;
+
+		/// Whether `username` can be registered.
+		fn validate_username(username: Vec<u8>) -> UsernameValidity;
+	}
+}
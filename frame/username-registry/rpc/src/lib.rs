@@ -0,0 +1,198 @@
+//! RPC interface for the username registry module.
+//!
+//! Besides single lookups, usernames and accounts can be resolved in batches, e.g. to render the
+//! authors of a feed with one request.
+
+use std::sync::Arc;
+use codec::Codec;
+use sp_blockchain::HeaderBackend;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_api::ProvideRuntimeApi;
+use pallet_username_registry_rpc_runtime_api::{AccountUsernames, UsernameInfo, UsernameValidity};
+pub use pallet_username_registry_rpc_runtime_api::UsernameApi as UsernameRuntimeApi;
+pub use self::gen_client::Client as UsernameClient;
+
+/// Maximum number of usernames or accounts resolved by a batch request.
+pub const MAX_BATCH_SIZE: usize = 256;
+
+#[rpc]
+pub trait UsernameApi<BlockHash, AccountId, Balance, BlockNumber> {
+	/// The registration of the username, if registered.
+	#[rpc(name = "username_resolve")]
+	fn resolve(
+		&self,
+		username: String,
+		at: Option<BlockHash>
+	) -> Result<Option<UsernameInfo<AccountId, Balance, BlockNumber>>>;
+
+	/// The registrations of the usernames, in the order given.
+	#[rpc(name = "username_resolveMany")]
+	fn resolve_many(
+		&self,
+		usernames: Vec<String>,
+		at: Option<BlockHash>
+	) -> Result<Vec<Option<UsernameInfo<AccountId, Balance, BlockNumber>>>>;
+
+	/// The usernames held by the account.
+	#[rpc(name = "username_usernamesOf")]
+	fn usernames_of(
+		&self,
+		account: AccountId,
+		at: Option<BlockHash>
+	) -> Result<AccountUsernames>;
+
+	/// The usernames held by the accounts, in the order given.
+	#[rpc(name = "username_usernamesOfMany")]
+	fn usernames_of_many(
+		&self,
+		accounts: Vec<AccountId>,
+		at: Option<BlockHash>
+	) -> Result<Vec<AccountUsernames>>;
+
+	/// Whether the username can be registered.
+	#[rpc(name = "username_validate")]
+	fn validate(
+		&self,
+		username: String,
+		at: Option<BlockHash>
+	) -> Result<UsernameValidity>;
+}
+
+/// A struct that implements the [`UsernameApi`].
+pub struct UsernameRegistry<C, P> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> UsernameRegistry<C, P> {
+	/// Create new `UsernameRegistry` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		UsernameRegistry { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+	/// The batch is larger than `MAX_BATCH_SIZE`.
+	BatchTooLarge,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::BatchTooLarge => 2,
+		}
+	}
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: "Unable to query usernames.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+fn ensure_batch_size(len: usize) -> Result<()> {
+	if len > MAX_BATCH_SIZE {
+		return Err(RpcError {
+			code: ErrorCode::ServerError(Error::BatchTooLarge.into()),
+			message: format!("At most {} items can be resolved at once.", MAX_BATCH_SIZE),
+			data: None,
+		});
+	}
+	Ok(())
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber>
+	UsernameApi<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber>
+	for UsernameRegistry<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: UsernameRuntimeApi<Block, AccountId, Balance, BlockNumber>,
+	AccountId: Codec,
+	Balance: Codec,
+	BlockNumber: Codec,
+{
+	fn resolve(
+		&self,
+		username: String,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Option<UsernameInfo<AccountId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.resolve(&at, username.into_bytes()).map_err(runtime_error)
+	}
+
+	fn resolve_many(
+		&self,
+		usernames: Vec<String>,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<Option<UsernameInfo<AccountId, Balance, BlockNumber>>>> {
+		ensure_batch_size(usernames.len())?;
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		usernames.into_iter()
+			.map(|username| api.resolve(&at, username.into_bytes()).map_err(runtime_error))
+			.collect()
+	}
+
+	fn usernames_of(
+		&self,
+		account: AccountId,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<AccountUsernames> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.usernames_of(&at, account).map_err(runtime_error)
+	}
+
+	fn usernames_of_many(
+		&self,
+		accounts: Vec<AccountId>,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<Vec<AccountUsernames>> {
+		ensure_batch_size(accounts.len())?;
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		accounts.into_iter()
+			.map(|account| api.usernames_of(&at, account).map_err(runtime_error))
+			.collect()
+	}
+
+	fn validate(
+		&self,
+		username: String,
+		at: Option<<Block as BlockT>::Hash>
+	) -> Result<UsernameValidity> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		));
+
+		api.validate_username(&at, username.into_bytes()).map_err(runtime_error)
+	}
+}
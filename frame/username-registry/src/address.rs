@@ -0,0 +1,41 @@
+//! Address type that is union of a username and an address of another lookup, e.g. an account id
+//! or index.
+
+#[cfg(feature = "std")]
+use std::fmt;
+use codec::{Decode, Encode};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// A username-aware address, which can be either an address of the account lookup wrapped or a
+/// registered username.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Hash))]
+pub enum Address<Source> {
+    /// It's an address of the account lookup, e.g. an account id or index.
+    Account(Source),
+    /// It's a registered username.
+    Username(Vec<u8>),
+}
+
+#[cfg(feature = "std")]
+impl<Source: fmt::Debug> fmt::Display for Address<Source> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Address::Account(source) => write!(f, "{:?}", source),
+            Address::Username(username) => write!(f, "@{}", String::from_utf8_lossy(username)),
+        }
+    }
+}
+
+impl<Source> From<Source> for Address<Source> {
+    fn from(source: Source) -> Self {
+        Address::Account(source)
+    }
+}
+
+impl<Source: Default> Default for Address<Source> {
+    fn default() -> Self {
+        Address::Account(Default::default())
+    }
+}
@@ -5,14 +5,27 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchResult, DispatchResultWithPostInfo},
     ensure,
-    traits::{EnsureOrigin, Get},
+    storage::{IterableStorageDoubleMap, IterableStorageMap},
+    traits::{
+        BalanceStatus, Currency, EnsureOrigin, ExistenceRequirement::{AllowDeath, KeepAlive}, Get,
+        OnUnbalanced, Randomness, ReservableCurrency,
+    },
+    transactional,
     weights::Weight,
 };
 use frame_system::ensure_signed;
-use sp_runtime::{DispatchError, RuntimeDebug};
+use pallet_username_registry_rpc_runtime_api::{AccountUsernames, JudgementInfo, UsernameInfo, UsernameValidity};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    traits::{Bounded, Hash as HashT, LookupError, SaturatedConversion, Saturating, StaticLookup, Zero},
+    DispatchError, Perbill, RuntimeDebug,
+};
 use sp_std::prelude::*;
 use sp_std::{fmt::Debug, vec::Vec};
 
+pub mod address;
+
 #[cfg(test)]
 mod mock;
 
@@ -25,35 +38,261 @@ mod default_weights;
 /// An identifier for a single name registrar/identity verification service.
 pub type RegistrarIndex = u32;
 
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+type PriceOf<T> = Price<
+    BalanceOf<T>,
+    <T as pallet_mission_tokens::Trait>::MissionTokenId,
+    <T as pallet_mission_tokens::Trait>::Balance,
+>;
+
 pub trait WeightInfo {
     fn add_registrar(r: u32) -> Weight;
-    fn register(r: u32) -> Weight;
-    fn unregister() -> Weight;
+    fn remove_registrar(r: u32) -> Weight;
+    fn set_fee(r: u32) -> Weight;
+    fn set_account_id(r: u32) -> Weight;
+    fn set_fields(r: u32) -> Weight;
+    fn register(r: u32, l: u32) -> Weight;
+    fn request_judgement(r: u32) -> Weight;
+    fn cancel_request(r: u32) -> Weight;
+    fn unregister(s: u32) -> Weight;
     fn provide_judgement(r: u32) -> Weight;
-    fn kill_username() -> Weight;
+    fn kill_username(s: u32) -> Weight;
+    fn set_primary_username() -> Weight;
+    fn renew() -> Weight;
+    fn release_expired(s: u32) -> Weight;
+    fn transfer_username() -> Weight;
+    fn list_username() -> Weight;
+    fn unlist_username() -> Weight;
+    fn buy_username() -> Weight;
+    fn make_offer() -> Weight;
+    fn withdraw_offer() -> Weight;
+    fn accept_offer() -> Weight;
+    fn create_sub_username(s: u32, l: u32) -> Weight;
+    fn accept_sub_username(s: u32) -> Weight;
+    fn revoke_sub_username(s: u32) -> Weight;
+    fn set_sub_username_config() -> Weight;
+    fn open_auction(l: u32) -> Weight;
+    fn bid() -> Weight;
+    fn commit_bid() -> Weight;
+    fn reveal_bid() -> Weight;
+    fn settle_auction(b: u32, l: u32) -> Weight;
+    fn link_did() -> Weight;
+    fn unlink_did() -> Weight;
+    fn reserve_username(l: u32) -> Weight;
+    fn unreserve_username() -> Weight;
+    fn block_pattern() -> Weight;
+    fn unblock_pattern() -> Weight;
+    fn on_initialize_auctions(a: u32) -> Weight;
 }
 
+/// An attestation of a registrar over a username. Once given, only the registrar can remove or
+/// replace it.
 #[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
-pub enum Judgement {
+pub enum Judgement<Balance> {
+    /// No judgement is yet in place, but a fee is reserved as payment for providing one.
+    FeePaid(Balance),
+    /// The holder appears to be reasonably entitled to the username, however no in depth checks
+    /// have been conducted.
+    Reasonable,
+    /// The holder is known directly by the registrar, which can fully attest to its entitlement
+    /// to the username.
+    KnownGood,
+    /// The username is of sufficiently low quality to be problematic, e.g. misleading. It is not
+    /// indicative of malicious intent.
+    LowQuality,
+    /// The username is erroneous, e.g. impersonating someone else. This may be indicative of
+    /// malicious intent. This cannot be removed except by the registrar.
+    Erroneous,
+}
+
+impl<Balance> Judgement<Balance> {
+    /// Returns `true` if this judgement is indicative of a fee being currently held. This means
+    /// it should not be cleared or replaced except by an operation which utilizes the fee.
+    fn has_deposit(&self) -> bool {
+        match self {
+            Judgement::FeePaid(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if this judgement should not be replaced by a new request, i.e. it holds a
+    /// fee or is malicious.
+    fn is_sticky(&self) -> bool {
+        match self {
+            Judgement::FeePaid(_) | Judgement::Erroneous => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the registrar vouches for the holder of the username.
+    pub fn is_approved(&self) -> bool {
+        match self {
+            Judgement::Reasonable | Judgement::KnownGood => true,
+            _ => false,
+        }
+    }
+
+    /// The judgement as exposed by the runtime API.
+    fn info(self) -> JudgementInfo<Balance> {
+        match self {
+            Judgement::FeePaid(fee) => JudgementInfo::FeePaid(fee),
+            Judgement::Reasonable => JudgementInfo::Reasonable,
+            Judgement::KnownGood => JudgementInfo::KnownGood,
+            Judgement::LowQuality => JudgementInfo::LowQuality,
+            Judgement::Erroneous => JudgementInfo::Erroneous,
+        }
+    }
+}
+
+/// The judgements of `Releases::V3_0_0` and before.
+#[derive(Encode, Decode)]
+enum OldJudgement {
     Requested,
     Approved,
 }
 
+impl OldJudgement {
+    /// Requests are kept without a fee, approvals become `KnownGood`.
+    fn upgrade<Balance: Zero>(self) -> Judgement<Balance> {
+        match self {
+            OldJudgement::Requested => Judgement::FeePaid(Zero::zero()),
+            OldJudgement::Approved => Judgement::KnownGood,
+        }
+    }
+}
+
+/// Information concerning a registrar.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct RegistrarInfo<Balance, AccountId> {
+    /// The account of the registrar.
+    pub account: AccountId,
+
+    /// Amount required to be given to the registrar for them to provide judgement.
+    pub fee: Balance,
+
+    /// What the registrar attests to, as a bit set published by the registrar off-chain, e.g.
+    /// that the holder is a person, an organisation or the owner of a trademark.
+    pub fields: u64,
+}
+
+// A value placed in storage that represents the current version of the registry storage. This
+// value is used by the `on_runtime_upgrade` logic to determine whether we run storage migration
+// logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    /// Registrations by username only.
+    V1_0_0,
+    /// Registrations indexed by account too.
+    V2_0_0,
+    /// Registrations holding a deposit and expiring.
+    V3_0_0,
+    /// Registrars charging fees for judgements.
+    V4_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 /// Information concerning the username registration of the controller of an account.
 ///
 /// NOTE: This is stored separately primarily to facilitate the addition of extra fields in a
 /// backwards compatible way through a specialized `Decode` impl.
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
-pub struct Registration<AccountId: Encode + Decode + Clone + Debug + Eq + PartialEq> {
+pub struct Registration<AccountId: Encode + Decode + Clone + Debug + Eq + PartialEq, Balance, BlockNumber> {
     /// Judgements from the registrars on this identity. Stored ordered by `RegistrarIndex`. There
     /// may be only a single judgement from each registrar.
-    pub judgements: Vec<(RegistrarIndex, Judgement)>,
+    pub judgements: Vec<(RegistrarIndex, Judgement<Balance>)>,
 
     /// Account Id.
     pub account_id: AccountId,
+
+    /// Amount held on deposit for this registration.
+    pub deposit: Balance,
+
+    /// The block at which the registration expires, unless renewed.
+    pub expiry: BlockNumber,
+}
+
+/// The price of a username, in native or mission tokens.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum Price<Balance, MissionTokenId, MissionBalance> {
+    /// An amount of the native currency.
+    Native(Balance),
+    /// An amount of a mission token.
+    MissionToken(MissionTokenId, MissionBalance),
+}
+
+/// How the holder of a username manages its sub-usernames.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct SubUsernameConfig<AccountId> {
+    /// An account allowed to create sub-usernames, besides the holder.
+    pub registrar: Option<AccountId>,
+
+    /// Whether sub-usernames are kept, as ordinary usernames, when the username is removed.
+    /// Otherwise they are removed with it.
+    pub orphan_on_removal: bool,
+}
+
+/// How the winner of a username auction is decided.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum AuctionKind {
+    /// Bids are committed as hashes while bidding, and revealed during the `RevealPeriod` after
+    /// it. The highest revealed bid wins.
+    SealedBid,
+    /// Bids are open. The auction actually ends at a random block of the `EndingPeriod` before
+    /// its end, drawn at its end, and the highest bid at that block wins.
+    Candle,
+}
+
+/// How a blocked pattern matches usernames.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum BlockKind {
+    /// The pattern matches the username equal to it.
+    Exact,
+    /// The pattern matches all usernames starting with it.
+    Prefix,
+}
+
+/// An auction of a username that cannot be registered otherwise.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+    /// How the winner is decided.
+    pub kind: AuctionKind,
+
+    /// The lowest bid accepted.
+    pub reserve_price: Balance,
+
+    /// The block at which bidding ends.
+    pub end: BlockNumber,
+
+    /// The highest bid so far, revealed for a sealed-bid auction.
+    pub leader: Option<(AccountId, Balance)>,
+
+    /// The number of accounts bidding.
+    pub bid_count: u32,
+
+    /// The number of blocks of the ending period at which the leader of a candle auction is
+    /// recorded.
+    pub ending_leaders: u32,
+}
+
+/// The bid of an account in an auction.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct Bid<Hash, Balance> {
+    /// The hash of the bidder, amount and salt of a sealed bid, until revealed.
+    pub commitment: Option<Hash>,
+
+    /// The amount held in reserve for the bid. For a sealed bid, it is at least the amount
+    /// committed to.
+    pub reserved: Balance,
 }
 
-pub trait Trait: frame_system::Trait {
+pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait + pallet_did::Trait {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 
@@ -67,21 +306,153 @@ pub trait Trait: frame_system::Trait {
     /// Maximum username length
     type MaxUsernameLength: Get<u32>;
 
+    /// Maximum number of usernames an account can hold.
+    type MaxUsernamesPerAccount: Get<u32>;
+
+    /// The currency trait.
+    type Currency: ReservableCurrency<Self::AccountId>;
+
+    /// The amount held on deposit for a registered username.
+    type BaseDeposit: Get<BalanceOf<Self>>;
+
+    /// Usernames shorter than this pay `ShortUsernameDeposit` for each character they lack.
+    type ShortUsernameLength: Get<u32>;
+
+    /// The additional deposit per character a username is shorter than `ShortUsernameLength`.
+    type ShortUsernameDeposit: Get<BalanceOf<Self>>;
+
+    /// The number of blocks a registration or renewal lasts.
+    type RegistrationPeriod: Get<Self::BlockNumber>;
+
+    /// The number of blocks after expiry during which only the holder can renew a username,
+    /// before anyone can release it.
+    type GracePeriod: Get<Self::BlockNumber>;
+
+    /// The part of the deposit paid to whoever releases a username past its grace period. The
+    /// rest is returned to the holder.
+    type ReleaseReward: Get<Perbill>;
+
+    /// What to do with slashed funds.
+    type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Maximum number of sub-usernames under a username.
+    type MaxSubUsernames: Get<u32>;
+
     /// The origin which may forcibly set or remove a name. Root can always do this.
     type ForceOrigin: EnsureOrigin<Self::Origin>;
 
     /// The origin which may add or remove registrars. Root can always do this.
     type RegistrarOrigin: EnsureOrigin<Self::Origin>;
 
+    /// The origin which may open auctions of usernames. Root can always do this.
+    type AuctionOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Something providing randomness, used to draw the end of candle auctions.
+    type Randomness: Randomness<Self::Hash>;
+
+    /// The number of blocks before the end of a candle auction among which its actual end is
+    /// drawn.
+    type EndingPeriod: Get<Self::BlockNumber>;
+
+    /// The number of blocks after bidding ends during which sealed bids are revealed.
+    type RevealPeriod: Get<Self::BlockNumber>;
+
+    /// Maximum number of accounts bidding in an auction.
+    type MaxBidsPerAuction: Get<u32>;
+
+    /// What to do with the winning bids of auctions.
+    type AuctionProceeds: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// The lookup of addresses which are not usernames, e.g. account ids or indices.
+    type AccountLookup: StaticLookup<Target = Self::AccountId>;
+
+    /// Whether only usernames with an approving judgement can be looked up.
+    type ApprovedUsernamesOnly: Get<bool>;
+
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as ValidatorRegistry {
-        pub RegistrationOf get(fn registration_of): map hasher(twox_64_concat) Vec<u8> => Option<Registration<T::AccountId>>;
+        pub RegistrationOf get(fn registration_of):
+            map hasher(twox_64_concat) Vec<u8> => Option<Registration<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+        /// The set of registrars. Not expected to get very big as can only be added through a
+        /// special origin (likely a council motion).
+        ///
+        /// The index into this can be cast to `RegistrarIndex` to get a valid value.
+        pub Registrars get(fn registrars): Vec<Option<RegistrarInfo<BalanceOf<T>, T::AccountId>>>;
+
+        /// The usernames held by an account, in order of registration.
+        pub UsernamesOf get(fn usernames_of): map hasher(twox_64_concat) T::AccountId => Vec<Vec<u8>>;
+
+        /// The username shown for an account.
+        pub PrimaryUsernameOf get(fn primary_username_of): map hasher(twox_64_concat) T::AccountId => Option<Vec<u8>>;
+
+        /// The asking price of usernames listed for sale.
+        pub ListingOf get(fn listing_of): map hasher(twox_64_concat) Vec<u8> => Option<PriceOf<T>>;
+
+        /// Offers on a username, held in reserve from the offering account until accepted or
+        /// withdrawn.
+        pub OfferOf get(fn offer_of):
+            double_map hasher(twox_64_concat) Vec<u8>, hasher(twox_64_concat) T::AccountId => Option<PriceOf<T>>;
 
-        pub Registrars get(fn registrars): Vec<Option<T::AccountId>>;
+        /// The sub-usernames controlled by a username, e.g. `alice.team` under `team`.
+        pub SubUsernamesOf get(fn sub_usernames_of): map hasher(twox_64_concat) Vec<u8> => Vec<Vec<u8>>;
+
+        /// Sub-usernames offered to an account other than their creator, which are only created
+        /// once that account accepts them, with the creator and the account offered to.
+        pub SubUsernameOfferOf get(fn sub_username_offer_of):
+            map hasher(twox_64_concat) Vec<u8> => Option<(T::AccountId, T::AccountId)>;
+
+        /// How the holder of a username manages its sub-usernames.
+        pub SubUsernameConfigOf get(fn sub_username_config_of):
+            map hasher(twox_64_concat) Vec<u8> => SubUsernameConfig<T::AccountId>;
+
+        /// Open auctions of usernames.
+        pub AuctionOf get(fn auction_of):
+            map hasher(twox_64_concat) Vec<u8> => Option<Auction<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+        /// The bids in an auction, held in reserve from the bidders until it is settled.
+        pub BidOf get(fn bid_of):
+            double_map hasher(twox_64_concat) Vec<u8>, hasher(twox_64_concat) T::AccountId
+            => Option<Bid<T::Hash, BalanceOf<T>>>;
+
+        /// The candle auctions ending at a block, whose randomness is drawn at that block.
+        pub CandleAuctionsEndingAt get(fn candle_auctions_ending_at):
+            map hasher(twox_64_concat) T::BlockNumber => Vec<Vec<u8>>;
+
+        /// The randomness drawn at the end of a candle auction, which decides its actual end.
+        pub CandleRandomnessOf get(fn candle_randomness_of): map hasher(twox_64_concat) Vec<u8> => Option<T::Hash>;
+
+        /// The leader of a candle auction at the blocks of its ending period where it changed,
+        /// keyed by offset into the ending period.
+        pub CandleLeaderOf get(fn candle_leader_of):
+            double_map hasher(twox_64_concat) Vec<u8>, hasher(twox_64_concat) u32
+            => Option<(T::AccountId, BalanceOf<T>)>;
+
+        /// The DID identity a username is linked to. A linked username resolves to the owner of
+        /// the identity, who acts in place of the holder wherever calls require the sender to
+        /// hold it. Judgements are still requested by the holder, who pays the fees.
+        pub DidOf get(fn did_of): map hasher(twox_64_concat) Vec<u8> => Option<T::AccountId>;
+
+        /// The usernames linked to a DID identity, in order of linking.
+        pub UsernamesOfDid get(fn usernames_of_did): map hasher(twox_64_concat) T::AccountId => Vec<Vec<u8>>;
+
+        /// Usernames which can only be registered by the account nominated for them, e.g. to
+        /// protect brand names.
+        pub ReservedUsernameOf get(fn reserved_username_of) config(reserved_usernames):
+            map hasher(twox_64_concat) Vec<u8> => Option<T::AccountId>;
+
+        /// Patterns of usernames which cannot be registered, e.g. abusive names.
+        pub BlockedPatternOf get(fn blocked_pattern_of) config(blocked_patterns):
+            map hasher(twox_64_concat) Vec<u8> => Option<BlockKind>;
+
+        /// Storage version of the pallet.
+        ///
+        /// New networks start at `Releases::V4_0_0`, so there is nothing to migrate.
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V4_0_0): Releases;
     }
 }
 
@@ -89,13 +460,66 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
     {
         UsernameRegistered(AccountId),
         UsernameUnregistered(AccountId),
         UsernameKilled(AccountId),
         JudgementRequested(AccountId, RegistrarIndex),
+        /// A judgement request was cancelled and its fee returned. \[who, registrar_index\]
+        JudgementUnrequested(AccountId, RegistrarIndex),
         JudgementGiven(AccountId, RegistrarIndex),
         RegistrarAdded(RegistrarIndex),
+        /// A registrar was removed. \[registrar_index\]
+        RegistrarRemoved(RegistrarIndex),
+        PrimaryUsernameSet(AccountId),
+        UsernameRenewed(AccountId),
+        /// A username past its grace period was released. \[holder, releaser\]
+        UsernameReleased(AccountId, AccountId),
+        /// A username was transferred. \[from, to\]
+        UsernameTransferred(AccountId, AccountId),
+        /// A username was listed for sale. \[holder\]
+        UsernameListed(AccountId),
+        /// A username was withdrawn from sale. \[holder\]
+        UsernameUnlisted(AccountId),
+        /// A username was sold. \[seller, buyer\]
+        UsernameSold(AccountId, AccountId),
+        /// An offer was made on a username. \[who\]
+        OfferMade(AccountId),
+        /// An offer on a username was withdrawn. \[who\]
+        OfferWithdrawn(AccountId),
+        /// A sub-username was created. \[creator, holder\]
+        SubUsernameCreated(AccountId, AccountId),
+        /// A sub-username was offered to an account. \[creator, account\]
+        SubUsernameOffered(AccountId, AccountId),
+        /// A sub-username was revoked by the holder of its parent. \[holder\]
+        SubUsernameRevoked(AccountId),
+        /// The sub-username configuration of a username was set. \[holder\]
+        SubUsernameConfigSet(AccountId),
+        /// An auction of a username was opened. \[username\]
+        AuctionOpened(Vec<u8>),
+        /// A bid was placed in a candle auction. \[who, amount\]
+        BidPlaced(AccountId, Balance),
+        /// A sealed bid was committed. \[who\]
+        BidCommitted(AccountId),
+        /// A sealed bid was revealed. \[who, amount\]
+        BidRevealed(AccountId, Balance),
+        /// An auction was won and the username registered to the winner. \[winner, amount\]
+        AuctionWon(AccountId, Balance),
+        /// An auction ended without a winner. \[username\]
+        AuctionFailed(Vec<u8>),
+        /// A username was linked to a DID identity. \[who, identity\]
+        UsernameLinked(AccountId, AccountId),
+        /// A username was unlinked from its DID identity. \[identity\]
+        UsernameUnlinked(AccountId),
+        /// A username was reserved for an account. \[username, account\]
+        UsernameReserved(Vec<u8>, AccountId),
+        /// A username is no longer reserved. \[username\]
+        UsernameUnreserved(Vec<u8>),
+        /// A pattern of usernames was blocked. \[pattern, kind\]
+        PatternBlocked(Vec<u8>, BlockKind),
+        /// A pattern of usernames was unblocked. \[pattern\]
+        PatternUnblocked(Vec<u8>),
     }
 );
 
@@ -110,6 +534,82 @@ decl_error! {
         UnregisterForbidden,
         UsernameNotFound,
         UsernameHasInvalidChars,
+        /// The account holds the maximum number of usernames.
+        TooManyUsernames,
+        /// The username is not held by the sender.
+        NotUsernameOwner,
+        /// The username is past its grace period and can no longer be renewed.
+        UsernameExpired,
+        /// The username is not past its grace period yet.
+        GracePeriodNotOver,
+        /// A username cannot be transferred to its holder.
+        SelfTransfer,
+        /// The username is not listed for sale.
+        NotListed,
+        /// The price does not match the asking price.
+        PriceMismatch,
+        /// There is no such offer.
+        NoOffer,
+        /// Usernames containing `.` can only be created under their parent.
+        SubUsernameRequiresParent,
+        /// Sub-usernames cannot have sub-usernames of their own.
+        NestedSubUsername,
+        /// The label of a sub-username must be non-empty and contain no `.`.
+        InvalidSubUsernameLabel,
+        /// The sender may not create sub-usernames under the parent.
+        NotSubUsernameRegistrar,
+        /// The parent has the maximum number of sub-usernames.
+        TooManySubUsernames,
+        /// The username is a sub-username controlled by its parent.
+        ControlledByParent,
+        /// The username is not a sub-username controlled by its parent.
+        NotSubUsername,
+        /// The sub-username has not been offered to the sender.
+        NoSubUsernameOffer,
+        /// The registrar's fee is higher than the maximum fee given.
+        FeeChanged,
+        /// The existing judgement holds a fee or is erroneous, and cannot be replaced.
+        StickyJudgement,
+        /// There is no judgement of the registrar on the username.
+        JudgementNotFound,
+        /// The judgement was already given and has no fee to return.
+        JudgementGiven,
+        /// A registrar cannot give a fee-paid judgement.
+        InvalidJudgement,
+        /// The username is being auctioned.
+        UsernameInAuction,
+        /// There is no auction of the username.
+        NoAuction,
+        /// The auction is not of the kind the call is for.
+        WrongAuctionKind,
+        /// The auction is too short for its kind.
+        AuctionTooShort,
+        /// Bidding in the auction is over.
+        BiddingClosed,
+        /// Sealed bids of the auction cannot be revealed now.
+        NotRevealPeriod,
+        /// The auction cannot be settled yet.
+        AuctionNotOver,
+        /// The bid is below the reserve price or the highest bid.
+        BidTooLow,
+        /// The auction has the maximum number of bidders.
+        TooManyBids,
+        /// There is no unrevealed bid of the sender.
+        NoBid,
+        /// The revealed bid does not match the commitment, or exceeds the amount reserved.
+        InvalidReveal,
+        /// The DID identity has the maximum number of linked usernames.
+        TooManyLinkedUsernames,
+        /// The username is not linked to a DID identity.
+        NotLinked,
+        /// The username is reserved for another account.
+        UsernameReserved,
+        /// The username matches a blocked pattern.
+        UsernameBlocked,
+        /// The username is not reserved.
+        NotReserved,
+        /// The pattern is not blocked.
+        NotBlocked,
     }
 }
 
@@ -122,11 +622,70 @@ decl_module! {
         const MinUsernameLength: u32 = T::MinUsernameLength::get();
         const MaxUsernameLength: u32 = T::MaxUsernameLength::get();
 
+        /// Maximum number of usernames an account can hold.
+        const MaxUsernamesPerAccount: u32 = T::MaxUsernamesPerAccount::get();
+
+        /// The amount held on deposit for a registered username.
+        const BaseDeposit: BalanceOf<T> = T::BaseDeposit::get();
+
+        /// Usernames shorter than this pay `ShortUsernameDeposit` for each character they lack.
+        const ShortUsernameLength: u32 = T::ShortUsernameLength::get();
+
+        /// The additional deposit per character a username is shorter than `ShortUsernameLength`.
+        const ShortUsernameDeposit: BalanceOf<T> = T::ShortUsernameDeposit::get();
+
+        /// The number of blocks a registration or renewal lasts.
+        const RegistrationPeriod: T::BlockNumber = T::RegistrationPeriod::get();
+
+        /// The number of blocks after expiry before anyone can release a username.
+        const GracePeriod: T::BlockNumber = T::GracePeriod::get();
+
+        /// Maximum number of sub-usernames under a username.
+        const MaxSubUsernames: u32 = T::MaxSubUsernames::get();
+
+        /// The number of blocks before the end of a candle auction among which its actual end is
+        /// drawn.
+        const EndingPeriod: T::BlockNumber = T::EndingPeriod::get();
+
+        /// The number of blocks after bidding ends during which sealed bids are revealed.
+        const RevealPeriod: T::BlockNumber = T::RevealPeriod::get();
+
+        /// Maximum number of accounts bidding in an auction.
+        const MaxBidsPerAuction: u32 = T::MaxBidsPerAuction::get();
+
         type Error = Error<T>;
 
         fn deposit_event() = default;
 
-        /// Add a registrar to the system.
+        /// Draw the randomness of the candle auctions ending at this block, before any bids can
+        /// be placed knowing it.
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let ending = <CandleAuctionsEndingAt<T>>::take(n);
+            if !ending.is_empty() {
+                let random = T::Randomness::random(b"username_registry_candle");
+                for username in &ending {
+                    <CandleRandomnessOf<T>>::insert(username, random);
+                }
+            }
+            T::WeightInfo::on_initialize_auctions(ending.len() as u32)
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            let version = StorageVersion::get();
+            if version == Releases::V4_0_0 {
+                return 0;
+            }
+
+            Self::migrate_registrations(version);
+            Self::migrate_registrars();
+            if version == Releases::V1_0_0 {
+                Self::migrate_to_account_index();
+            }
+            StorageVersion::put(Releases::V4_0_0);
+            T::MaximumBlockWeight::get()
+        }
+
+        /// Add a registrar to the system, charging no fee until it sets one.
         ///
         /// The dispatch origin for this call must be `T::RegistrarOrigin`.
         ///
@@ -146,7 +705,7 @@ decl_module! {
             let (i, registrar_count) = <Registrars<T>>::try_mutate(
                 |registrars| -> Result<(RegistrarIndex, usize), DispatchError> {
                     ensure!(registrars.len() < T::MaxRegistrars::get() as usize, Error::<T>::TooManyRegistrars);
-                    registrars.push(Some(account));
+                    registrars.push(Some(RegistrarInfo { account, fee: Zero::zero(), fields: 0 }));
                     Ok(((registrars.len() - 1) as RegistrarIndex, registrars.len()))
                 }
             )?;
@@ -156,10 +715,116 @@ decl_module! {
             Ok(Some(T::WeightInfo::add_registrar(registrar_count as u32)).into())
         }
 
+        /// Remove a registrar from the system. Its index is not reused, and pending requests to
+        /// it can still be cancelled.
+        ///
+        /// The dispatch origin for this call must be `T::RegistrarOrigin`.
+        ///
+        /// - `index`: the index of the registrar.
+        ///
+        /// Emits `RegistrarRemoved` if successful.
+        ///
+        /// # <weight>
+        /// - `O(R)` where `R` registrar-count (governance-bounded and code-bounded).
+        /// - One storage mutation (codec `O(R)`).
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::remove_registrar(T::MaxRegistrars::get())]
+        fn remove_registrar(origin, #[compact] index: RegistrarIndex) -> DispatchResultWithPostInfo {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+
+            let registrars = <Registrars<T>>::try_mutate(
+                |registrars| -> Result<usize, DispatchError> {
+                    registrars.get_mut(index as usize).and_then(Option::take).ok_or(Error::<T>::InvalidIndex)?;
+                    Ok(registrars.len())
+                }
+            )?;
+
+            Self::deposit_event(RawEvent::RegistrarRemoved(index));
+
+            Ok(Some(T::WeightInfo::remove_registrar(registrars as u32)).into())
+        }
+
+        /// Set the fee required for a judgement to be requested from a registrar.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must be the account
+        /// of the registrar whose index is `index`.
+        ///
+        /// - `index`: the index of the registrar whose fee is to be set.
+        /// - `fee`: the new fee.
+        ///
+        /// # <weight>
+        /// - `O(R)`.
+        /// - One storage mutation `O(R)`.
+        /// # </weight>
+        #[weight = T::WeightInfo::set_fee(T::MaxRegistrars::get())]
+        fn set_fee(origin,
+            #[compact] index: RegistrarIndex,
+            #[compact] fee: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registrars = Self::mutate_registrar(index, &sender, |registrar| registrar.fee = fee)?;
+
+            Ok(Some(T::WeightInfo::set_fee(registrars as u32)).into())
+        }
+
+        /// Change the account associated with a registrar.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must be the account
+        /// of the registrar whose index is `index`.
+        ///
+        /// - `index`: the index of the registrar whose account is to be changed.
+        /// - `new`: the new account ID.
+        ///
+        /// # <weight>
+        /// - `O(R)`.
+        /// - One storage mutation `O(R)`.
+        /// # </weight>
+        #[weight = T::WeightInfo::set_account_id(T::MaxRegistrars::get())]
+        fn set_account_id(origin,
+            #[compact] index: RegistrarIndex,
+            new: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registrars = Self::mutate_registrar(index, &sender, |registrar| registrar.account = new)?;
+
+            Ok(Some(T::WeightInfo::set_account_id(registrars as u32)).into())
+        }
+
+        /// Set what a registrar attests to.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must be the account
+        /// of the registrar whose index is `index`.
+        ///
+        /// - `index`: the index of the registrar whose fields are to be set.
+        /// - `fields`: the fields that the registrar concerns itself with.
+        ///
+        /// # <weight>
+        /// - `O(R)`.
+        /// - One storage mutation `O(R)`.
+        /// # </weight>
+        #[weight = T::WeightInfo::set_fields(T::MaxRegistrars::get())]
+        fn set_fields(origin,
+            #[compact] index: RegistrarIndex,
+            fields: u64,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registrars = Self::mutate_registrar(index, &sender, |registrar| registrar.fields = fields)?;
+
+            Ok(Some(T::WeightInfo::set_fields(registrars as u32)).into())
+        }
+
         /// Register an username and request registration
         ///
         /// The dispatch origin for this call must be _Signed_.
         ///
+        /// A deposit, higher for short usernames, and the fee of the registrar are reserved from
+        /// the sender. The username expires after `RegistrationPeriod` unless renewed. Judgements
+        /// of further registrars are requested with `request_judgement`.
+        ///
         /// - `username`: username.
         /// - `reg_index`: registrar index.
         ///
@@ -167,81 +832,206 @@ decl_module! {
         ///
         /// # <weight>
         /// - `O(R)` where `R` registrar-count (governance-bounded and code-bounded).
-        /// - One storage mutation (codec `O(R)`).
-        /// - One event.
+        /// - `O(L)` where `L` username length (code-bounded), matching blocked patterns.
+        /// - Three storage mutations.
+        /// - One balance reservation.
+        /// - Two events.
         /// # </weight>
-        #[weight =  T::WeightInfo::register(T::MaxRegistrars::get())]
+        #[weight = T::WeightInfo::register(T::MaxRegistrars::get(), T::MaxUsernameLength::get())]
         fn register(origin, username: Vec<u8>, #[compact] reg_index: RegistrarIndex) -> DispatchResultWithPostInfo {
             let sender = ensure_signed(origin)?;
 
             Self::validate_username(&username)?;
+            ensure!(Self::parent_of(&username).is_none(), Error::<T>::SubUsernameRequiresParent);
             ensure!(!<RegistrationOf<T>>::contains_key(&username), Error::<T>::UsernameAlreadyRegistered);
+            ensure!(!<AuctionOf<T>>::contains_key(&username), Error::<T>::UsernameInAuction);
+            ensure!(
+                Self::reserved_username_of(&username).map_or(true, |nominee| nominee == sender),
+                Error::<T>::UsernameReserved
+            );
 
             let registrars = <Registrars<T>>::get();
-            let _registrar = registrars.get(reg_index as usize).and_then(Option::as_ref)
+            let registrar = registrars.get(reg_index as usize).and_then(Option::as_ref)
                 .ok_or(Error::<T>::EmptyIndex)?;
 
-            let item = (reg_index, Judgement::Requested);
-            <RegistrationOf<T>>::insert(&username, Registration { judgements: vec![item], account_id: sender.clone() });
+            let deposit = Self::deposit_for(&username);
+            let reserved = deposit.saturating_add(registrar.fee);
+            <T as Trait>::Currency::reserve(&sender, reserved)?;
+            if let Err(e) = Self::add_username_of(&sender, &username) {
+                <T as Trait>::Currency::unreserve(&sender, reserved);
+                return Err(e.into());
+            }
+
+            let item = (reg_index, Judgement::FeePaid(registrar.fee));
+            let expiry = <frame_system::Module<T>>::block_number() + T::RegistrationPeriod::get();
+            <RegistrationOf<T>>::insert(&username, Registration {
+                judgements: vec![item],
+                account_id: sender.clone(),
+                deposit,
+                expiry,
+            });
 
             Self::deposit_event(RawEvent::UsernameRegistered(sender.clone()));
             Self::deposit_event(RawEvent::JudgementRequested(sender, reg_index));
 
-            Ok(Some(T::WeightInfo::register(registrars.len() as u32)).into())
+            Ok(Some(T::WeightInfo::register(registrars.len() as u32, username.len() as u32)).into())
+        }
+
+        /// Request a judgement on a username from a registrar.
+        ///
+        /// Payment: At most `max_fee` will be reserved for payment to the registrar if judgement
+        /// given.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        /// - `reg_index`: the index of the registrar whose judgement is requested.
+        /// - `max_fee`: the maximum fee that may be paid. This should just be auto-populated as:
+        ///
+        /// ```nocompile
+        /// Self::registrars().get(reg_index).unwrap().fee
+        /// ```
+        ///
+        /// Emits `JudgementRequested` if successful.
+        ///
+        /// # <weight>
+        /// - `O(R)` where `R` judgements on the username (code-bounded).
+        /// - One balance-reserve operation.
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::request_judgement(T::MaxRegistrars::get())]
+        fn request_judgement(origin,
+            username: Vec<u8>,
+            #[compact] reg_index: RegistrarIndex,
+            #[compact] max_fee: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let registrars = <Registrars<T>>::get();
+            let registrar = registrars.get(reg_index as usize).and_then(Option::as_ref)
+                .ok_or(Error::<T>::EmptyIndex)?;
+            ensure!(max_fee >= registrar.fee, Error::<T>::FeeChanged);
+            let mut registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(registration.account_id == sender, Error::<T>::NotUsernameOwner);
+
+            let item = (reg_index, Judgement::FeePaid(registrar.fee));
+            match registration.judgements.binary_search_by_key(&reg_index, |x| x.0) {
+                Ok(i) => if registration.judgements[i].1.is_sticky() {
+                    Err(Error::<T>::StickyJudgement)?
+                } else {
+                    registration.judgements[i] = item
+                },
+                Err(i) => registration.judgements.insert(i, item),
+            }
+
+            <T as Trait>::Currency::reserve(&sender, registrar.fee)?;
+
+            let judgements = registration.judgements.len();
+            <RegistrationOf<T>>::insert(&username, registration);
+
+            Self::deposit_event(RawEvent::JudgementRequested(sender, reg_index));
+
+            Ok(Some(T::WeightInfo::request_judgement(judgements as u32)).into())
+        }
+
+        /// Cancel a previous request.
+        ///
+        /// Payment: A previously reserved fee is returned on success.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        /// - `reg_index`: the index of the registrar whose judgement is no longer requested.
+        ///
+        /// Emits `JudgementUnrequested` if successful.
+        ///
+        /// # <weight>
+        /// - `O(R)` where `R` judgements on the username (code-bounded).
+        /// - One balance-reserve operation.
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::cancel_request(T::MaxRegistrars::get())]
+        fn cancel_request(origin, username: Vec<u8>, #[compact] reg_index: RegistrarIndex) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let mut registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(registration.account_id == sender, Error::<T>::NotUsernameOwner);
+
+            let pos = registration.judgements.binary_search_by_key(&reg_index, |x| x.0)
+                .map_err(|_| Error::<T>::JudgementNotFound)?;
+            let fee = if let Judgement::FeePaid(fee) = registration.judgements[pos].1 {
+                fee
+            } else {
+                Err(Error::<T>::JudgementGiven)?
+            };
+            registration.judgements.remove(pos);
+
+            <T as Trait>::Currency::unreserve(&sender, fee);
+            let judgements = registration.judgements.len();
+            <RegistrationOf<T>>::insert(&username, registration);
+
+            Self::deposit_event(RawEvent::JudgementUnrequested(sender, reg_index));
+
+            Ok(Some(T::WeightInfo::cancel_request(judgements as u32)).into())
         }
 
-        /// Unregister an username
+        /// Unregister an username and return its deposit
         ///
-        /// The dispatch origin for this call must be _Signed_ and the sender must have a registered
-        /// identity.
+        /// The dispatch origin for this call must be _Signed_ and the sender must control
+        /// `username`, i.e. hold it or own the DID it is linked to. The deposit is returned to the
+        /// holder. Its sub-usernames are removed or orphaned as configured.
         ///
         /// - `username`: username.
         ///
         /// Emits `UsernameUnregistered` if successful.
         ///
         /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the username (code-bounded).
         /// # </weight>
-        #[weight = T::WeightInfo::unregister()]
+        #[weight = T::WeightInfo::unregister(T::MaxSubUsernames::get())]
         fn unregister(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
             let sender = ensure_signed(origin)?;
 
-            Self::validate_username(&username)?;
-
-            if let Some(registration) = <RegistrationOf<T>>::get(&username) {
-                if registration.account_id == sender {
-                    <RegistrationOf<T>>::remove(&username)
+            let sub_count = if let Some(registration) = <RegistrationOf<T>>::get(&username) {
+                if Self::controller_of(&username, &registration) == sender {
+                    let sub_count = Self::remove_registration(&username, &registration.account_id);
+                    <T as Trait>::Currency::unreserve(&registration.account_id, registration.deposit);
+                    sub_count
                 } else {
                     return Err(Error::<T>::UnregisterForbidden.into())
                 }
             } else {
                 return Err(Error::<T>::UsernameNotFound.into())
-            }
+            };
 
             Self::deposit_event(RawEvent::UsernameUnregistered(sender));
 
-            Ok(Some(T::WeightInfo::unregister()).into())
+            Ok(Some(T::WeightInfo::unregister(sub_count)).into())
         }
 
-        /// Remove username
+        /// Remove username and slash its deposit
         ///
-        /// The dispatch origin for this call must match `T::ForceOrigin`.
+        /// The dispatch origin for this call must match `T::ForceOrigin`. Sub-usernames of the
+        /// username are removed or orphaned as configured.
         ///
         /// - `username`: username.
         ///
         /// Emits `UsernameKilled` if successful.
         ///
         /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the username (code-bounded).
         /// # </weight>
-        #[weight = T::WeightInfo::kill_username()]
+        #[weight = T::WeightInfo::kill_username(T::MaxSubUsernames::get())]
         fn kill_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
             T::ForceOrigin::ensure_origin(origin)?;
 
-            Self::validate_username(&username)?;
-            let registration = <RegistrationOf<T>>::take(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            let sub_count = Self::remove_registration(&username, &registration.account_id);
+            T::Slashed::on_unbalanced(<T as Trait>::Currency::slash_reserved(&registration.account_id, registration.deposit).0);
 
             Self::deposit_event(RawEvent::UsernameKilled(registration.account_id));
 
-            Ok(Some(T::WeightInfo::kill_username()).into())
+            Ok(Some(T::WeightInfo::kill_username(sub_count)).into())
         }
 
         /// Provide a judgement for an username.
@@ -251,7 +1041,8 @@ decl_module! {
         ///
         /// - `reg_index`: the index of the registrar whose judgement is being made.
         /// - `username`: username
-        /// - `judgement`: the judgement of the registrar of index `reg_index` about `target`.
+        /// - `judgement`: the judgement of the registrar of index `reg_index` about `target`. Any
+        ///   fee reserved for it is paid to the registrar.
         ///
         /// Emits `JudgementGiven` if successful.
         ///
@@ -264,23 +1055,33 @@ decl_module! {
         fn provide_judgement(origin,
             #[compact] reg_index: RegistrarIndex,
             username: Vec<u8>,
-            judgement: Judgement,
+            judgement: Judgement<BalanceOf<T>>,
         ) -> DispatchResultWithPostInfo {
             let sender = ensure_signed(origin)?;
 
-            Self::validate_username(&username)?;
+            ensure!(!judgement.has_deposit(), Error::<T>::InvalidJudgement);
             let mut registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
 
             let registrars = <Registrars<T>>::get();
             registrars
                 .get(reg_index as usize)
                 .and_then(Option::as_ref)
-                .and_then(|account| if *account == sender { Some(account) } else { None })
+                .and_then(|registrar| if registrar.account == sender { Some(registrar) } else { None })
                 .ok_or(Error::<T>::InvalidIndex)?;
 
             let item = (reg_index, judgement);
             match registration.judgements.binary_search_by_key(&reg_index, |x| x.0) {
-                Ok(position) => registration.judgements[position] = item,
+                Ok(position) => {
+                    if let Judgement::FeePaid(fee) = registration.judgements[position].1 {
+                        let _ = <T as Trait>::Currency::repatriate_reserved(
+                            &registration.account_id,
+                            &sender,
+                            fee,
+                            BalanceStatus::Free,
+                        );
+                    }
+                    registration.judgements[position] = item
+                }
                 Err(position) => registration.judgements.insert(position, item),
             }
 
@@ -290,23 +1091,1396 @@ decl_module! {
 
             Ok(Some(T::WeightInfo::provide_judgement(registrars.len() as u32,)).into())
         }
-    }
-}
 
-impl<T: Trait> Module<T> {
-    fn validate_username(username: &[u8]) -> DispatchResult {
-        ensure!(username.len() >= T::MinUsernameLength::get() as usize, Error::<T>::UsernameIsVeryShort);
-        ensure!(username.len() <= T::MaxUsernameLength::get() as usize, Error::<T>::UsernameIsVeryLong);
+        /// Set the username shown for the sender among those it holds.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `PrimaryUsernameSet` if successful.
+        ///
+        /// # <weight>
+        /// - `O(U)` where `U` usernames of the sender (code-bounded).
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::set_primary_username()]
+        fn set_primary_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
 
-        let is_valid_char = |c: &u8| {
-            (*c >= 48 && *c <= 57)      // '0' - '9'
-            || (*c >= 97 && *c <= 122)  // 'a' - 'z'
-            || *c == 95                 // '_'
-            || *c == 45                 // '-'
-            || *c == 46                 // '.'
-        };
-        ensure!(username.iter().all(is_valid_char), Error::<T>::UsernameHasInvalidChars);
+            ensure!(Self::usernames_of(&sender).contains(&username), Error::<T>::NotUsernameOwner);
+            <PrimaryUsernameOf<T>>::insert(&sender, username);
 
-        Ok(())
+            Self::deposit_event(RawEvent::PrimaryUsernameSet(sender));
+
+            Ok(Some(T::WeightInfo::set_primary_username()).into())
+        }
+
+        /// Extend the registration of a username by `RegistrationPeriod`.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`,
+        /// which must not be past its grace period.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `UsernameRenewed` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::renew()]
+        fn renew(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            <RegistrationOf<T>>::try_mutate(&username, |maybe_registration| -> DispatchResult {
+                let registration = maybe_registration.as_mut().ok_or(Error::<T>::UsernameNotFound)?;
+                ensure!(Self::controller_of(&username, registration) == sender, Error::<T>::NotUsernameOwner);
+                ensure!(!Self::is_controlled(&username), Error::<T>::ControlledByParent);
+
+                let now = <frame_system::Module<T>>::block_number();
+                ensure!(
+                    now <= registration.expiry.saturating_add(T::GracePeriod::get()),
+                    Error::<T>::UsernameExpired
+                );
+                registration.expiry = registration.expiry.max(now) + T::RegistrationPeriod::get();
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::UsernameRenewed(sender));
+
+            Ok(Some(T::WeightInfo::renew()).into())
+        }
+
+        /// Release a username past its grace period, for a part of its deposit.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `UsernameReleased` if successful.
+        ///
+        /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the username (code-bounded).
+        /// - Three storage mutations.
+        /// - One balance transfer.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::release_expired(T::MaxSubUsernames::get())]
+        fn release_expired(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(
+                now > registration.expiry.saturating_add(T::GracePeriod::get()),
+                Error::<T>::GracePeriodNotOver
+            );
+
+            let holder = registration.account_id;
+            let sub_count = Self::remove_registration(&username, &holder);
+
+            let reward = T::ReleaseReward::get() * registration.deposit;
+            let unpaid = <T as Trait>::Currency::repatriate_reserved(&holder, &sender, reward, BalanceStatus::Free)
+                .unwrap_or(reward);
+            <T as Trait>::Currency::unreserve(&holder, registration.deposit.saturating_sub(reward).saturating_add(unpaid));
+
+            Self::deposit_event(RawEvent::UsernameReleased(holder, sender));
+
+            Ok(Some(T::WeightInfo::release_expired(sub_count)).into())
+        }
+
+        /// Transfer a username to another account.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`,
+        /// which must not have expired. The sender pays the deposit, which is reserved on `dest`,
+        /// and the previous holder's deposit is returned. The judgements and any DID link of the
+        /// username are cleared.
+        ///
+        /// - `username`: username.
+        /// - `dest`: the account receiving the username.
+        ///
+        /// Emits `UsernameTransferred` if successful.
+        ///
+        /// # <weight>
+        /// - Four storage mutations.
+        /// - One balance transfer and two balance reservations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::transfer_username()]
+        #[transactional]
+        fn transfer_username(origin, username: Vec<u8>, dest: T::AccountId) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            Self::do_transfer_username(&username, &sender, &dest, &sender)?;
+
+            Ok(Some(T::WeightInfo::transfer_username()).into())
+        }
+
+        /// List a username for sale at an asking price, replacing any previous listing.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        /// - `price`: the asking price, in native or mission tokens.
+        ///
+        /// Emits `UsernameListed` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::list_username()]
+        fn list_username(origin, username: Vec<u8>, price: PriceOf<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(&username, &registration) == sender, Error::<T>::NotUsernameOwner);
+            ensure!(!Self::is_controlled(&username), Error::<T>::ControlledByParent);
+            Self::validate_price(&price)?;
+            <ListingOf<T>>::insert(&username, price);
+
+            Self::deposit_event(RawEvent::UsernameListed(sender));
+
+            Ok(Some(T::WeightInfo::list_username()).into())
+        }
+
+        /// Withdraw a username from sale.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `UsernameUnlisted` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::unlist_username()]
+        fn unlist_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(&username, &registration) == sender, Error::<T>::NotUsernameOwner);
+            ensure!(<ListingOf<T>>::take(&username).is_some(), Error::<T>::NotListed);
+
+            Self::deposit_event(RawEvent::UsernameUnlisted(sender));
+
+            Ok(Some(T::WeightInfo::unlist_username()).into())
+        }
+
+        /// Buy a username listed for sale, paying the asking price to its holder.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: username.
+        /// - `price`: the asking price, which must match the listing so that the holder cannot
+        ///   change it in the meantime.
+        ///
+        /// Emits `UsernameSold` and `UsernameTransferred` if successful.
+        ///
+        /// # <weight>
+        /// - Five storage mutations.
+        /// - One balance transfer and two balance reservations.
+        /// - Two events.
+        /// # </weight>
+        #[weight = T::WeightInfo::buy_username()]
+        #[transactional]
+        fn buy_username(origin, username: Vec<u8>, price: PriceOf<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let listing = <ListingOf<T>>::get(&username).ok_or(Error::<T>::NotListed)?;
+            ensure!(listing == price, Error::<T>::PriceMismatch);
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            let seller = Self::controller_of(&username, &registration);
+
+            Self::pay(&sender, &seller, &price)?;
+            Self::do_transfer_username(&username, &seller, &sender, &sender)?;
+
+            Self::deposit_event(RawEvent::UsernameSold(seller, sender));
+
+            Ok(Some(T::WeightInfo::buy_username()).into())
+        }
+
+        /// Offer to buy a username, replacing any previous offer of the sender. The offered
+        /// amount is reserved until the offer is accepted or withdrawn.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must not hold
+        /// `username`.
+        ///
+        /// - `username`: username.
+        /// - `price`: the offered price, in native or mission tokens.
+        ///
+        /// Emits `OfferMade` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - Up to two balance reservations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::make_offer()]
+        #[transactional]
+        fn make_offer(origin, username: Vec<u8>, price: PriceOf<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(&username, &registration) != sender, Error::<T>::SelfTransfer);
+            Self::validate_price(&price)?;
+
+            if let Some(previous) = <OfferOf<T>>::get(&username, &sender) {
+                Self::unreserve_price(&sender, &previous);
+            }
+            Self::reserve_price(&sender, &price)?;
+            <OfferOf<T>>::insert(&username, &sender, price);
+
+            Self::deposit_event(RawEvent::OfferMade(sender));
+
+            Ok(Some(T::WeightInfo::make_offer()).into())
+        }
+
+        /// Withdraw an offer on a username, returning the reserved amount.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `OfferWithdrawn` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One balance reservation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::withdraw_offer()]
+        fn withdraw_offer(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let offer = <OfferOf<T>>::take(&username, &sender).ok_or(Error::<T>::NoOffer)?;
+            Self::unreserve_price(&sender, &offer);
+
+            Self::deposit_event(RawEvent::OfferWithdrawn(sender));
+
+            Ok(Some(T::WeightInfo::withdraw_offer()).into())
+        }
+
+        /// Accept an offer on a username, transferring it to the offering account for the
+        /// reserved amount.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        /// - `buyer`: the account whose offer is accepted.
+        ///
+        /// Emits `UsernameSold` and `UsernameTransferred` if successful.
+        ///
+        /// # <weight>
+        /// - Five storage mutations.
+        /// - One balance transfer and two balance reservations.
+        /// - Two events.
+        /// # </weight>
+        #[weight = T::WeightInfo::accept_offer()]
+        #[transactional]
+        fn accept_offer(origin, username: Vec<u8>, buyer: T::AccountId) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let offer = <OfferOf<T>>::take(&username, &buyer).ok_or(Error::<T>::NoOffer)?;
+            Self::pay_reserved(&buyer, &sender, &offer)?;
+            Self::do_transfer_username(&username, &sender, &buyer, &buyer)?;
+
+            Self::deposit_event(RawEvent::UsernameSold(sender, buyer));
+
+            Ok(Some(T::WeightInfo::accept_offer()).into())
+        }
+
+        /// Create the sub-username `label.parent` for `owner`. Unless `owner` is the sender, the
+        /// sub-username is only offered, and created once `owner` accepts it.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `parent` or
+        /// be its sub-username registrar. The sub-username holds no deposit and does not expire;
+        /// it lasts as long as its parent, which controls it.
+        ///
+        /// - `parent`: the username the sub-username is created under.
+        /// - `label`: the part of the sub-username before `.`.
+        /// - `owner`: the account receiving the sub-username.
+        ///
+        /// Emits `SubUsernameCreated` or `SubUsernameOffered` if successful.
+        ///
+        /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the parent (code-bounded).
+        /// - `O(L)` where `L` username length (code-bounded), matching blocked patterns.
+        /// - Three storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::create_sub_username(T::MaxSubUsernames::get(), T::MaxUsernameLength::get())]
+        #[transactional]
+        fn create_sub_username(origin,
+            parent: Vec<u8>,
+            label: Vec<u8>,
+            owner: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(!label.is_empty() && !label.contains(&b'.'), Error::<T>::InvalidSubUsernameLabel);
+            let mut username = label;
+            username.push(b'.');
+            username.extend_from_slice(&parent);
+            Self::validate_username(&username)?;
+            Self::ensure_can_create_sub_username(&parent, &username, &sender, &owner)?;
+
+            if owner != sender {
+                <SubUsernameOfferOf<T>>::insert(&username, (sender.clone(), owner.clone()));
+                Self::deposit_event(RawEvent::SubUsernameOffered(sender, owner));
+                return Ok(Some(T::WeightInfo::create_sub_username(0, username.len() as u32)).into());
+            }
+
+            let sub_count = Self::do_create_sub_username(&parent, &username, &owner)?;
+
+            Self::deposit_event(RawEvent::SubUsernameCreated(sender, owner));
+
+            Ok(Some(T::WeightInfo::create_sub_username(sub_count as u32, username.len() as u32)).into())
+        }
+
+        /// Accept a sub-username offered to the sender, creating it.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sub-username must have been
+        /// offered to the sender by an account which may still create it.
+        ///
+        /// - `username`: the sub-username.
+        ///
+        /// Emits `SubUsernameCreated` if successful.
+        ///
+        /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the parent (code-bounded).
+        /// - Four storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::accept_sub_username(T::MaxSubUsernames::get())]
+        #[transactional]
+        fn accept_sub_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let (creator, owner) = <SubUsernameOfferOf<T>>::get(&username)
+                .filter(|(_, owner)| *owner == sender)
+                .ok_or(Error::<T>::NoSubUsernameOffer)?;
+            let parent = Self::parent_of(&username).ok_or(Error::<T>::NoSubUsernameOffer)?;
+            Self::ensure_can_create_sub_username(parent, &username, &creator, &owner)?;
+
+            <SubUsernameOfferOf<T>>::remove(&username);
+            let sub_count = Self::do_create_sub_username(parent, &username, &owner)?;
+
+            Self::deposit_event(RawEvent::SubUsernameCreated(creator, owner));
+
+            Ok(Some(T::WeightInfo::accept_sub_username(sub_count as u32)).into())
+        }
+
+        /// Revoke a sub-username.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold the parent of
+        /// `username`.
+        ///
+        /// - `username`: the sub-username.
+        ///
+        /// Emits `SubUsernameRevoked` if successful.
+        ///
+        /// # <weight>
+        /// - `O(S)` where `S` sub-usernames of the parent (code-bounded).
+        /// - Three storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::revoke_sub_username(T::MaxSubUsernames::get())]
+        fn revoke_sub_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::is_controlled(&username), Error::<T>::NotSubUsername);
+            let parent = Self::parent_of(&username).unwrap_or_default();
+            let parent_registration = <RegistrationOf<T>>::get(parent).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(parent, &parent_registration) == sender, Error::<T>::NotUsernameOwner);
+
+            let sub_count = Self::sub_usernames_of(parent).len();
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            Self::remove_registration(&username, &registration.account_id);
+            <T as Trait>::Currency::unreserve(&registration.account_id, registration.deposit);
+
+            Self::deposit_event(RawEvent::SubUsernameRevoked(registration.account_id));
+
+            Ok(Some(T::WeightInfo::revoke_sub_username(sub_count as u32)).into())
+        }
+
+        /// Configure how the sub-usernames of a username are managed.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must hold `username`.
+        ///
+        /// - `username`: username.
+        /// - `registrar`: an account allowed to create sub-usernames besides the sender, if any.
+        /// - `orphan_on_removal`: whether sub-usernames are kept when `username` is removed.
+        ///
+        /// Emits `SubUsernameConfigSet` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::set_sub_username_config()]
+        fn set_sub_username_config(origin,
+            username: Vec<u8>,
+            registrar: Option<T::AccountId>,
+            orphan_on_removal: bool,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(&username, &registration) == sender, Error::<T>::NotUsernameOwner);
+            ensure!(Self::parent_of(&username).is_none(), Error::<T>::NestedSubUsername);
+            <SubUsernameConfigOf<T>>::insert(&username, SubUsernameConfig { registrar, orphan_on_removal });
+
+            Self::deposit_event(RawEvent::SubUsernameConfigSet(sender));
+
+            Ok(Some(T::WeightInfo::set_sub_username_config()).into())
+        }
+
+        /// Open an auction of a username, which may be shorter than `MinUsernameLength`.
+        ///
+        /// The dispatch origin for this call must match `T::AuctionOrigin`.
+        ///
+        /// - `username`: an unregistered username without `.`.
+        /// - `kind`: how the winner is decided.
+        /// - `reserve_price`: the lowest bid accepted.
+        /// - `duration`: the number of blocks of bidding. For a candle auction, it must be at
+        ///   least `EndingPeriod`.
+        ///
+        /// Emits `AuctionOpened` if successful.
+        ///
+        /// # <weight>
+        /// - `O(L)` where `L` username length (code-bounded), matching blocked patterns.
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::open_auction(T::MaxUsernameLength::get())]
+        fn open_auction(origin,
+            username: Vec<u8>,
+            kind: AuctionKind,
+            #[compact] reserve_price: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            T::AuctionOrigin::ensure_origin(origin)?;
+
+            ensure!(!username.is_empty(), Error::<T>::UsernameIsVeryShort);
+            ensure!(username.len() <= T::MaxUsernameLength::get() as usize, Error::<T>::UsernameIsVeryLong);
+            Self::validate_chars(&username)?;
+            ensure!(!Self::is_blocked(&username), Error::<T>::UsernameBlocked);
+            ensure!(Self::parent_of(&username).is_none(), Error::<T>::SubUsernameRequiresParent);
+            ensure!(!<RegistrationOf<T>>::contains_key(&username), Error::<T>::UsernameAlreadyRegistered);
+            ensure!(!<AuctionOf<T>>::contains_key(&username), Error::<T>::UsernameInAuction);
+            ensure!(!<ReservedUsernameOf<T>>::contains_key(&username), Error::<T>::UsernameReserved);
+            ensure!(
+                !duration.is_zero() && (kind == AuctionKind::SealedBid || duration >= T::EndingPeriod::get()),
+                Error::<T>::AuctionTooShort
+            );
+
+            let end = <frame_system::Module<T>>::block_number() + duration;
+            if kind == AuctionKind::Candle {
+                <CandleAuctionsEndingAt<T>>::append(end, &username);
+            }
+            <AuctionOf<T>>::insert(&username, Auction {
+                kind,
+                reserve_price,
+                end,
+                leader: None,
+                bid_count: 0,
+                ending_leaders: 0,
+            });
+            let length = username.len() as u32;
+
+            Self::deposit_event(RawEvent::AuctionOpened(username));
+
+            Ok(Some(T::WeightInfo::open_auction(length)).into())
+        }
+
+        /// Bid in a candle auction, raising the previous bid of the sender if any.
+        ///
+        /// Payment: The difference between `amount` and the previous bid of the sender is
+        /// reserved until the auction is settled.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: the username auctioned.
+        /// - `amount`: the bid, at least the reserve price and above the highest bid.
+        ///
+        /// Emits `BidPlaced` if successful.
+        ///
+        /// # <weight>
+        /// - One balance reservation.
+        /// - Up to four storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::bid()]
+        fn bid(origin, username: Vec<u8>, #[compact] amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let mut auction = <AuctionOf<T>>::get(&username).ok_or(Error::<T>::NoAuction)?;
+            ensure!(auction.kind == AuctionKind::Candle, Error::<T>::WrongAuctionKind);
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now < auction.end, Error::<T>::BiddingClosed);
+            ensure!(
+                amount >= auction.reserve_price && auction.leader.as_ref().map_or(true, |(_, best)| amount > *best),
+                Error::<T>::BidTooLow
+            );
+
+            let previous = <BidOf<T>>::get(&username, &sender);
+            if previous.is_none() {
+                ensure!(auction.bid_count < T::MaxBidsPerAuction::get(), Error::<T>::TooManyBids);
+                auction.bid_count += 1;
+            }
+            let reserved = previous.map_or_else(Zero::zero, |bid| bid.reserved);
+            <T as Trait>::Currency::reserve(&sender, amount.saturating_sub(reserved))?;
+            <BidOf<T>>::insert(&username, &sender, Bid { commitment: None, reserved: amount });
+
+            let ending_start = auction.end.saturating_sub(T::EndingPeriod::get());
+            if now >= ending_start {
+                let offset = (now - ending_start).saturated_into::<u32>();
+                // Record who led before the ending period, unless outbid at its very start.
+                if auction.ending_leaders == 0 && offset > 0 {
+                    if let Some(leader) = &auction.leader {
+                        <CandleLeaderOf<T>>::insert(&username, 0u32, leader);
+                        auction.ending_leaders += 1;
+                    }
+                }
+                if !<CandleLeaderOf<T>>::contains_key(&username, offset) {
+                    auction.ending_leaders += 1;
+                }
+                <CandleLeaderOf<T>>::insert(&username, offset, (sender.clone(), amount));
+            }
+            auction.leader = Some((sender.clone(), amount));
+            <AuctionOf<T>>::insert(&username, auction);
+
+            Self::deposit_event(RawEvent::BidPlaced(sender, amount));
+
+            Ok(Some(T::WeightInfo::bid()).into())
+        }
+
+        /// Commit to a sealed bid, replacing the previous commitment of the sender if any.
+        ///
+        /// Payment: `collateral` is reserved until the auction is settled. It hides the amount of
+        /// the bid, which must not exceed it.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: the username auctioned.
+        /// - `commitment`: the hash of the sender, the amount of the bid and a secret salt.
+        /// - `collateral`: the amount to reserve, at least the reserve price.
+        ///
+        /// Emits `BidCommitted` if successful.
+        ///
+        /// # <weight>
+        /// - One balance reservation.
+        /// - Up to two storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::commit_bid()]
+        fn commit_bid(origin,
+            username: Vec<u8>,
+            commitment: T::Hash,
+            #[compact] collateral: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let mut auction = <AuctionOf<T>>::get(&username).ok_or(Error::<T>::NoAuction)?;
+            ensure!(auction.kind == AuctionKind::SealedBid, Error::<T>::WrongAuctionKind);
+            ensure!(<frame_system::Module<T>>::block_number() < auction.end, Error::<T>::BiddingClosed);
+            ensure!(collateral >= auction.reserve_price, Error::<T>::BidTooLow);
+
+            let previous = <BidOf<T>>::get(&username, &sender);
+            let reserved = previous.as_ref().map_or_else(Zero::zero, |bid| bid.reserved);
+            if previous.is_none() {
+                ensure!(auction.bid_count < T::MaxBidsPerAuction::get(), Error::<T>::TooManyBids);
+                auction.bid_count += 1;
+                <AuctionOf<T>>::insert(&username, auction);
+            }
+            if collateral > reserved {
+                <T as Trait>::Currency::reserve(&sender, collateral - reserved)?;
+            } else {
+                <T as Trait>::Currency::unreserve(&sender, reserved - collateral);
+            }
+            <BidOf<T>>::insert(&username, &sender, Bid { commitment: Some(commitment), reserved: collateral });
+
+            Self::deposit_event(RawEvent::BidCommitted(sender));
+
+            Ok(Some(T::WeightInfo::commit_bid()).into())
+        }
+
+        /// Reveal a sealed bid.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must have committed
+        /// to the bid.
+        ///
+        /// - `username`: the username auctioned.
+        /// - `amount`: the bid, at least the reserve price.
+        /// - `salt`: the salt of the commitment.
+        ///
+        /// Emits `BidRevealed` if successful.
+        ///
+        /// # <weight>
+        /// - Up to two storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::reveal_bid()]
+        fn reveal_bid(origin,
+            username: Vec<u8>,
+            #[compact] amount: BalanceOf<T>,
+            salt: [u8; 32],
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let mut auction = <AuctionOf<T>>::get(&username).ok_or(Error::<T>::NoAuction)?;
+            ensure!(auction.kind == AuctionKind::SealedBid, Error::<T>::WrongAuctionKind);
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(
+                now >= auction.end && now < auction.end + T::RevealPeriod::get(),
+                Error::<T>::NotRevealPeriod
+            );
+
+            let mut bid = <BidOf<T>>::get(&username, &sender).ok_or(Error::<T>::NoBid)?;
+            let commitment = bid.commitment.take().ok_or(Error::<T>::NoBid)?;
+            ensure!(
+                T::Hashing::hash_of(&(&sender, amount, salt)) == commitment && amount <= bid.reserved,
+                Error::<T>::InvalidReveal
+            );
+            ensure!(amount >= auction.reserve_price, Error::<T>::BidTooLow);
+            <BidOf<T>>::insert(&username, &sender, bid);
+
+            if auction.leader.as_ref().map_or(true, |(_, best)| amount > *best) {
+                auction.leader = Some((sender.clone(), amount));
+                <AuctionOf<T>>::insert(&username, auction);
+            }
+
+            Self::deposit_event(RawEvent::BidRevealed(sender, amount));
+
+            Ok(Some(T::WeightInfo::reveal_bid()).into())
+        }
+
+        /// Settle an auction that is over. The username is registered to the winner for one
+        /// `RegistrationPeriod`, and the deposit of the username is kept reserved out of the
+        /// winning bid, the rest of which goes to `T::AuctionProceeds`. Other bids are returned.
+        /// If the winner already holds `MaxUsernamesPerAccount` usernames, the auction fails and
+        /// every bid is returned.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `username`: the username auctioned.
+        ///
+        /// Emits `AuctionWon` or `AuctionFailed` if successful.
+        ///
+        /// # <weight>
+        /// - `O(B + L)` where `B` bidders (code-bounded) and `L` blocks of the ending period at
+        ///   which the leader changed (code-bounded).
+        /// # </weight>
+        #[weight = T::WeightInfo::settle_auction(
+            T::MaxBidsPerAuction::get(),
+            T::EndingPeriod::get().saturated_into::<u32>(),
+        )]
+        #[transactional]
+        fn settle_auction(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            ensure_signed(origin)?;
+
+            let auction = <AuctionOf<T>>::get(&username).ok_or(Error::<T>::NoAuction)?;
+            let now = <frame_system::Module<T>>::block_number();
+            let winner = match auction.kind {
+                AuctionKind::SealedBid => {
+                    ensure!(now >= auction.end + T::RevealPeriod::get(), Error::<T>::AuctionNotOver);
+                    auction.leader
+                }
+                AuctionKind::Candle => {
+                    let random = <CandleRandomnessOf<T>>::take(&username).ok_or(Error::<T>::AuctionNotOver)?;
+                    Self::candle_winner(&username, random, auction.ending_leaders, auction.leader)
+                }
+            };
+            let winner = winner.filter(|(account, _)| {
+                Self::usernames_of(account).len() < T::MaxUsernamesPerAccount::get() as usize
+            });
+            let deposit = winner.as_ref()
+                .map_or_else(Zero::zero, |(_, amount)| Self::deposit_for(&username).min(*amount));
+            <AuctionOf<T>>::remove(&username);
+
+            for (bidder, bid) in <BidOf<T>>::drain_prefix(&username) {
+                match &winner {
+                    Some((account, amount)) if *account == bidder => {
+                        let proceeds = amount.saturating_sub(deposit);
+                        T::AuctionProceeds::on_unbalanced(<T as Trait>::Currency::slash_reserved(&bidder, proceeds).0);
+                        <T as Trait>::Currency::unreserve(&bidder, bid.reserved.saturating_sub(*amount));
+                    }
+                    _ => {
+                        <T as Trait>::Currency::unreserve(&bidder, bid.reserved);
+                    }
+                }
+            }
+
+            match winner {
+                Some((account, amount)) => {
+                    Self::add_username_of(&account, &username)?;
+                    <RegistrationOf<T>>::insert(&username, Registration {
+                        judgements: vec![],
+                        account_id: account.clone(),
+                        deposit,
+                        expiry: now + T::RegistrationPeriod::get(),
+                    });
+                    Self::deposit_event(RawEvent::AuctionWon(account, amount));
+                }
+                None => Self::deposit_event(RawEvent::AuctionFailed(username)),
+            }
+
+            Ok(Some(T::WeightInfo::settle_auction(auction.bid_count, auction.ending_leaders)).into())
+        }
+
+        /// Link a username to a DID identity, replacing any previous link. From then on the
+        /// username resolves to, and is controlled by, the owner of the identity, so that changing
+        /// the owner of the identity also moves the username. The holder keeps the deposit.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must control
+        /// `username` and be the owner, or a `nameAuth` delegate, of `identity`.
+        ///
+        /// - `username`: username.
+        /// - `identity`: the DID identity.
+        ///
+        /// Emits `UsernameLinked` if successful.
+        ///
+        /// # <weight>
+        /// - Three storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::link_did()]
+        fn link_did(origin, username: Vec<u8>, identity: T::AccountId) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let registration = <RegistrationOf<T>>::get(&username).ok_or(Error::<T>::UsernameNotFound)?;
+            ensure!(Self::controller_of(&username, &registration) == sender, Error::<T>::NotUsernameOwner);
+            ensure!(
+                <frame_system::Module<T>>::block_number() <= registration.expiry,
+                Error::<T>::UsernameExpired
+            );
+            <pallet_did::Module<T>>::valid_delegate(&identity, pallet_did::USERNAME_DELEGATE_TYPE, &sender)?;
+
+            Self::remove_did_link(&username);
+            <UsernamesOfDid<T>>::try_mutate(&identity, |usernames| -> DispatchResult {
+                ensure!(
+                    usernames.len() < T::MaxUsernamesPerAccount::get() as usize,
+                    Error::<T>::TooManyLinkedUsernames
+                );
+                usernames.push(username.clone());
+                Ok(())
+            })?;
+            <DidOf<T>>::insert(&username, &identity);
+
+            Self::deposit_event(RawEvent::UsernameLinked(sender, identity));
+
+            Ok(Some(T::WeightInfo::link_did()).into())
+        }
+
+        /// Unlink a username from its DID identity, returning control to its holder.
+        ///
+        /// The dispatch origin for this call must be _Signed_ and the sender must be the owner, or
+        /// a `nameAuth` delegate, of the identity `username` is linked to.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `UsernameUnlinked` if successful.
+        ///
+        /// # <weight>
+        /// - Two storage mutations.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::unlink_did()]
+        fn unlink_did(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let identity = Self::did_of(&username).ok_or(Error::<T>::NotLinked)?;
+            <pallet_did::Module<T>>::valid_delegate(&identity, pallet_did::USERNAME_DELEGATE_TYPE, &sender)?;
+
+            Self::remove_did_link(&username);
+
+            Self::deposit_event(RawEvent::UsernameUnlinked(identity));
+
+            Ok(Some(T::WeightInfo::unlink_did()).into())
+        }
+
+        /// Reserve a username for an account, so that no other account can register it. Replaces
+        /// any previous nominee, and does not affect the username if already registered.
+        ///
+        /// The dispatch origin for this call must match `T::ForceOrigin`.
+        ///
+        /// - `username`: username.
+        /// - `account`: the account which may register the username.
+        ///
+        /// Emits `UsernameReserved` if successful.
+        ///
+        /// # <weight>
+        /// - `O(L)` where `L` username length (code-bounded), matching blocked patterns.
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::reserve_username(T::MaxUsernameLength::get())]
+        fn reserve_username(origin, username: Vec<u8>, account: T::AccountId) -> DispatchResultWithPostInfo {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            Self::validate_username(&username)?;
+            <ReservedUsernameOf<T>>::insert(&username, &account);
+            let length = username.len() as u32;
+
+            Self::deposit_event(RawEvent::UsernameReserved(username, account));
+
+            Ok(Some(T::WeightInfo::reserve_username(length)).into())
+        }
+
+        /// Remove the reservation of a username, so that any account can register it.
+        ///
+        /// The dispatch origin for this call must match `T::ForceOrigin`.
+        ///
+        /// - `username`: username.
+        ///
+        /// Emits `UsernameUnreserved` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::unreserve_username()]
+        fn unreserve_username(origin, username: Vec<u8>) -> DispatchResultWithPostInfo {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            <ReservedUsernameOf<T>>::take(&username).ok_or(Error::<T>::NotReserved)?;
+
+            Self::deposit_event(RawEvent::UsernameUnreserved(username));
+
+            Ok(Some(T::WeightInfo::unreserve_username()).into())
+        }
+
+        /// Block a pattern of usernames, so that no matching username can be registered. Usernames
+        /// already registered are not affected, and can be killed with `kill_username`.
+        ///
+        /// The dispatch origin for this call must match `T::ForceOrigin`.
+        ///
+        /// - `pattern`: the pattern, made of valid username characters.
+        /// - `kind`: whether the pattern matches usernames exactly or by prefix.
+        ///
+        /// Emits `PatternBlocked` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::block_pattern()]
+        fn block_pattern(origin, pattern: Vec<u8>, kind: BlockKind) -> DispatchResultWithPostInfo {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            ensure!(!pattern.is_empty(), Error::<T>::UsernameIsVeryShort);
+            ensure!(pattern.len() <= T::MaxUsernameLength::get() as usize, Error::<T>::UsernameIsVeryLong);
+            Self::validate_chars(&pattern)?;
+            <BlockedPatternOf<T>>::insert(&pattern, kind);
+
+            Self::deposit_event(RawEvent::PatternBlocked(pattern, kind));
+
+            Ok(Some(T::WeightInfo::block_pattern()).into())
+        }
+
+        /// Unblock a pattern of usernames.
+        ///
+        /// The dispatch origin for this call must match `T::ForceOrigin`.
+        ///
+        /// - `pattern`: the pattern.
+        ///
+        /// Emits `PatternUnblocked` if successful.
+        ///
+        /// # <weight>
+        /// - One storage mutation.
+        /// - One event.
+        /// # </weight>
+        #[weight = T::WeightInfo::unblock_pattern()]
+        fn unblock_pattern(origin, pattern: Vec<u8>) -> DispatchResultWithPostInfo {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            <BlockedPatternOf<T>>::take(&pattern).ok_or(Error::<T>::NotBlocked)?;
+
+            Self::deposit_event(RawEvent::PatternUnblocked(pattern));
+
+            Ok(Some(T::WeightInfo::unblock_pattern()).into())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The deposit for registering `username`.
+    pub fn deposit_for(username: &[u8]) -> BalanceOf<T> {
+        let missing = (T::ShortUsernameLength::get() as usize).saturating_sub(username.len());
+        T::BaseDeposit::get()
+            .saturating_add(T::ShortUsernameDeposit::get().saturating_mul((missing as u32).into()))
+    }
+
+    /// Move `username`, controlled by `from`, to `dest`. The deposit is paid by `payer` and
+    /// reserved on `dest`, and the previous holder's deposit returned. Judgements are cleared, and
+    /// fees of pending requests returned, so that the new holder is not verified on the strength
+    /// of the previous one. Any link to a DID is removed.
+    ///
+    /// Balances are moved after storage is updated, so callers must be transactional.
+    fn do_transfer_username(
+        username: &[u8],
+        from: &T::AccountId,
+        dest: &T::AccountId,
+        payer: &T::AccountId,
+    ) -> DispatchResult {
+        let mut registration = <RegistrationOf<T>>::get(username).ok_or(Error::<T>::UsernameNotFound)?;
+        ensure!(from != dest && registration.account_id != *dest, Error::<T>::SelfTransfer);
+        ensure!(Self::controller_of(username, &registration) == *from, Error::<T>::NotUsernameOwner);
+        ensure!(!Self::is_controlled(username), Error::<T>::ControlledByParent);
+        ensure!(
+            <frame_system::Module<T>>::block_number() <= registration.expiry,
+            Error::<T>::UsernameExpired
+        );
+
+        let holder = registration.account_id.clone();
+        Self::add_username_of(dest, username)?;
+        Self::remove_username_of(&holder, username);
+        <T as Trait>::Currency::unreserve(&holder, registration.deposit);
+        if payer != dest {
+            <T as Trait>::Currency::transfer(payer, dest, registration.deposit, KeepAlive)?;
+        }
+        <T as Trait>::Currency::reserve(dest, registration.deposit)?;
+        Self::refund_requests(&holder, &registration.judgements);
+
+        registration.account_id = dest.clone();
+        registration.judgements.clear();
+        <RegistrationOf<T>>::insert(username, registration);
+        <ListingOf<T>>::remove(username);
+        <SubUsernameConfigOf<T>>::remove(username);
+        Self::remove_did_link(username);
+
+        Self::deposit_event(RawEvent::UsernameTransferred(from.clone(), dest.clone()));
+        Ok(())
+    }
+
+    fn validate_price(price: &PriceOf<T>) -> DispatchResult {
+        if let Price::MissionToken(token_id, _) = price {
+            <pallet_mission_tokens::Module<T>>::validate_mission_token_id(*token_id)?;
+        }
+        Ok(())
+    }
+
+    /// Pay `price` from the free balance of `from` to `to`.
+    fn pay(from: &T::AccountId, to: &T::AccountId, price: &PriceOf<T>) -> DispatchResult {
+        match price {
+            Price::Native(amount) => <T as Trait>::Currency::transfer(from, to, *amount, KeepAlive),
+            Price::MissionToken(token_id, amount) =>
+                <pallet_mission_tokens::Module<T>>::do_transfer(from, to, *token_id, *amount, KeepAlive),
+        }
+    }
+
+    /// Pay `price` from the reserved balance of `from` to `to`.
+    fn pay_reserved(from: &T::AccountId, to: &T::AccountId, price: &PriceOf<T>) -> DispatchResult {
+        match price {
+            Price::Native(amount) => {
+                <T as Trait>::Currency::repatriate_reserved(from, to, *amount, BalanceStatus::Free)?;
+                Ok(())
+            }
+            Price::MissionToken(token_id, amount) => {
+                <pallet_mission_tokens::Module<T>>::unreserve(from, *token_id, *amount);
+                <pallet_mission_tokens::Module<T>>::do_transfer(from, to, *token_id, *amount, AllowDeath)
+            }
+        }
+    }
+
+    fn reserve_price(who: &T::AccountId, price: &PriceOf<T>) -> DispatchResult {
+        match price {
+            Price::Native(amount) => <T as Trait>::Currency::reserve(who, *amount),
+            Price::MissionToken(token_id, amount) =>
+                <pallet_mission_tokens::Module<T>>::reserve(who, *token_id, *amount),
+        }
+    }
+
+    fn unreserve_price(who: &T::AccountId, price: &PriceOf<T>) {
+        match price {
+            Price::Native(amount) => {
+                <T as Trait>::Currency::unreserve(who, *amount);
+            }
+            Price::MissionToken(token_id, amount) => {
+                <pallet_mission_tokens::Module<T>>::unreserve(who, *token_id, *amount);
+            }
+        }
+    }
+
+    /// The account controlling `username`: the owner of its DID if linked to one, otherwise its
+    /// holder. Linked usernames stay indexed under, and hold the deposit of, their holder.
+    pub fn controller_of(
+        username: &[u8],
+        registration: &Registration<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    ) -> T::AccountId {
+        match Self::did_of(username) {
+            Some(identity) => <pallet_did::Module<T>>::identity_owner(&identity),
+            None => registration.account_id.clone(),
+        }
+    }
+
+    /// Remove the link of `username` to a DID, if any.
+    fn remove_did_link(username: &[u8]) {
+        if let Some(identity) = <DidOf<T>>::take(username) {
+            let mut usernames = Self::usernames_of_did(&identity);
+            usernames.retain(|u| u.as_slice() != username);
+            if usernames.is_empty() {
+                <UsernamesOfDid<T>>::remove(&identity);
+            } else {
+                <UsernamesOfDid<T>>::insert(&identity, usernames);
+            }
+        }
+    }
+
+    /// The parent of a sub-username, i.e. what follows its first `.`.
+    pub fn parent_of(username: &[u8]) -> Option<&[u8]> {
+        username.iter().position(|c| *c == b'.').map(|i| &username[i + 1..])
+    }
+
+    /// Check that `creator` may create the sub-username `username` under `parent` for `owner`.
+    fn ensure_can_create_sub_username(
+        parent: &[u8],
+        username: &[u8],
+        creator: &T::AccountId,
+        owner: &T::AccountId,
+    ) -> DispatchResult {
+        let registration = <RegistrationOf<T>>::get(parent).ok_or(Error::<T>::UsernameNotFound)?;
+        ensure!(Self::parent_of(parent).is_none(), Error::<T>::NestedSubUsername);
+        ensure!(
+            Self::controller_of(parent, &registration) == *creator
+                || Self::sub_username_config_of(parent).registrar.as_ref() == Some(creator),
+            Error::<T>::NotSubUsernameRegistrar
+        );
+        ensure!(!<RegistrationOf<T>>::contains_key(username), Error::<T>::UsernameAlreadyRegistered);
+        ensure!(
+            Self::reserved_username_of(username).map_or(true, |nominee| nominee == *owner),
+            Error::<T>::UsernameReserved
+        );
+        ensure!(
+            Self::sub_usernames_of(parent).len() < T::MaxSubUsernames::get() as usize,
+            Error::<T>::TooManySubUsernames
+        );
+        Ok(())
+    }
+
+    /// Register the sub-username `username` of `parent` to `owner`, returning the number of
+    /// sub-usernames of `parent`. Callers must be transactional.
+    fn do_create_sub_username(parent: &[u8], username: &[u8], owner: &T::AccountId) -> Result<usize, DispatchError> {
+        let sub_count = <SubUsernamesOf<T>>::mutate(parent, |subs| {
+            subs.push(username.to_vec());
+            subs.len()
+        });
+        Self::add_username_of(owner, username)?;
+        <RegistrationOf<T>>::insert(username, Registration {
+            judgements: vec![],
+            account_id: owner.clone(),
+            deposit: Zero::zero(),
+            expiry: T::BlockNumber::max_value(),
+        });
+        Ok(sub_count)
+    }
+
+    /// Whether `username` is a sub-username controlled by its parent, rather than an orphaned or
+    /// migrated username containing `.`.
+    fn is_controlled(username: &[u8]) -> bool {
+        Self::parent_of(username)
+            .map_or(false, |parent| Self::sub_usernames_of(parent).iter().any(|sub| sub.as_slice() == username))
+    }
+
+    /// Remove the registration of `username` held by `holder`, leaving its deposit to the caller
+    /// and returning the fees of pending requests. Sub-usernames are removed, or orphaned as
+    /// ordinary usernames expiring one `RegistrationPeriod` from now. Returns the number of
+    /// sub-usernames.
+    fn remove_registration(username: &[u8], holder: &T::AccountId) -> u32 {
+        if let Some(parent) = Self::parent_of(username) {
+            let mut siblings = Self::sub_usernames_of(parent);
+            if let Some(position) = siblings.iter().position(|sub| sub.as_slice() == username) {
+                siblings.remove(position);
+                if siblings.is_empty() {
+                    <SubUsernamesOf<T>>::remove(parent);
+                } else {
+                    <SubUsernamesOf<T>>::insert(parent, siblings);
+                }
+            }
+        }
+        if let Some(registration) = <RegistrationOf<T>>::take(username) {
+            Self::refund_requests(holder, &registration.judgements);
+        }
+        <ListingOf<T>>::remove(username);
+        Self::remove_username_of(holder, username);
+        Self::remove_did_link(username);
+
+        let config = <SubUsernameConfigOf<T>>::take(username);
+        let subs = <SubUsernamesOf<T>>::take(username);
+        let expiry = <frame_system::Module<T>>::block_number() + T::RegistrationPeriod::get();
+        for sub in &subs {
+            if config.orphan_on_removal {
+                <RegistrationOf<T>>::mutate(sub, |maybe_registration| {
+                    if let Some(registration) = maybe_registration {
+                        registration.expiry = expiry;
+                    }
+                });
+            } else if let Some(registration) = <RegistrationOf<T>>::take(sub) {
+                <ListingOf<T>>::remove(sub);
+                Self::remove_did_link(sub);
+                Self::remove_username_of(&registration.account_id, sub);
+                <T as Trait>::Currency::unreserve(&registration.account_id, registration.deposit);
+                Self::refund_requests(&registration.account_id, &registration.judgements);
+            }
+        }
+        subs.len() as u32
+    }
+
+    /// Return the fees reserved by `holder` for pending judgement requests.
+    fn refund_requests(holder: &T::AccountId, judgements: &[(RegistrarIndex, Judgement<BalanceOf<T>>)]) {
+        for (_, judgement) in judgements {
+            if let Judgement::FeePaid(fee) = judgement {
+                <T as Trait>::Currency::unreserve(holder, *fee);
+            }
+        }
+    }
+
+    /// Apply `f` to the registrar of index `index`, whose account must be `sender`. Returns the
+    /// number of registrars.
+    fn mutate_registrar(
+        index: RegistrarIndex,
+        sender: &T::AccountId,
+        f: impl FnOnce(&mut RegistrarInfo<BalanceOf<T>, T::AccountId>),
+    ) -> Result<usize, DispatchError> {
+        <Registrars<T>>::try_mutate(|registrars| -> Result<usize, DispatchError> {
+            let registrar = registrars.get_mut(index as usize)
+                .and_then(|x| x.as_mut())
+                .and_then(|r| if r.account == *sender { Some(r) } else { None })
+                .ok_or(Error::<T>::InvalidIndex)?;
+            f(registrar);
+            Ok(registrars.len())
+        })
+    }
+
+    /// Add a username to those held by an account. The first username held becomes primary.
+    fn add_username_of(account: &T::AccountId, username: &[u8]) -> DispatchResult {
+        <UsernamesOf<T>>::try_mutate(account, |usernames| -> DispatchResult {
+            ensure!(
+                usernames.len() < T::MaxUsernamesPerAccount::get() as usize,
+                Error::<T>::TooManyUsernames
+            );
+            usernames.push(username.to_vec());
+            Ok(())
+        })?;
+        if !<PrimaryUsernameOf<T>>::contains_key(account) {
+            <PrimaryUsernameOf<T>>::insert(account, username.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Remove a username from those held by an account. If it was primary, the earliest
+    /// remaining username becomes primary.
+    fn remove_username_of(account: &T::AccountId, username: &[u8]) {
+        let mut usernames = Self::usernames_of(account);
+        usernames.retain(|u| u.as_slice() != username);
+
+        if Self::primary_username_of(account).as_deref() == Some(username) {
+            match usernames.first() {
+                Some(next) => <PrimaryUsernameOf<T>>::insert(account, next),
+                None => <PrimaryUsernameOf<T>>::remove(account),
+            }
+        }
+        if usernames.is_empty() {
+            <UsernamesOf<T>>::remove(account);
+        } else {
+            <UsernamesOf<T>>::insert(account, usernames);
+        }
+    }
+
+    /// The account controlling `username`, as in `controller_of`, unless expired or, if
+    /// `ApprovedUsernamesOnly`, not approved by any registrar.
+    pub fn lookup_username(username: &[u8]) -> Option<T::AccountId> {
+        Self::registration_of(username)
+            .filter(Self::is_resolvable)
+            .map(|registration| Self::controller_of(username, &registration))
+    }
+
+    /// Whether the primary username of `account` is unexpired and approved by a registrar.
+    pub fn has_approved_username(account: &T::AccountId) -> bool {
+        Self::primary_username_of(account)
+            .and_then(|username| Self::registration_of(username))
+            .map_or(false, |registration| {
+                registration.account_id == *account
+                    && <frame_system::Module<T>>::block_number() <= registration.expiry
+                    && registration.judgements.iter().any(|(_, j)| j.is_approved())
+            })
+    }
+
+    fn is_resolvable(registration: &Registration<T::AccountId, BalanceOf<T>, T::BlockNumber>) -> bool {
+        <frame_system::Module<T>>::block_number() <= registration.expiry
+            && (!T::ApprovedUsernamesOnly::get() || registration.judgements.iter().any(|(_, j)| j.is_approved()))
+    }
+
+    /// The registration of `username`, for the runtime API.
+    pub fn username_info(username: &[u8]) -> Option<UsernameInfo<T::AccountId, BalanceOf<T>, T::BlockNumber>> {
+        Self::registration_of(username).map(|registration| {
+            let resolvable = Self::is_resolvable(&registration);
+            let controller = Self::controller_of(username, &registration);
+            UsernameInfo {
+                account_id: registration.account_id,
+                controller,
+                judgements: registration.judgements.into_iter().map(|(i, j)| (i, j.info())).collect(),
+                expiry: registration.expiry,
+                did: Self::did_of(username),
+                resolvable,
+            }
+        })
+    }
+
+    /// The usernames held by `account`, for the runtime API.
+    pub fn account_usernames(account: &T::AccountId) -> AccountUsernames {
+        AccountUsernames {
+            primary: Self::primary_username_of(account),
+            usernames: Self::usernames_of(account),
+        }
+    }
+
+    /// Whether `username` can be registered, for the runtime API.
+    pub fn username_validity(username: &[u8]) -> UsernameValidity {
+        match Self::validate_username(username) {
+            Ok(()) if Self::parent_of(username).is_some() => UsernameValidity::RequiresParent,
+            Ok(()) if <RegistrationOf<T>>::contains_key(username) => UsernameValidity::Registered,
+            Ok(()) if <AuctionOf<T>>::contains_key(username) => UsernameValidity::InAuction,
+            Ok(()) if <ReservedUsernameOf<T>>::contains_key(username) => UsernameValidity::Reserved,
+            Ok(()) => UsernameValidity::Available,
+            Err(Error::<T>::UsernameIsVeryShort) => UsernameValidity::TooShort,
+            Err(Error::<T>::UsernameIsVeryLong) => UsernameValidity::TooLong,
+            Err(Error::<T>::UsernameBlocked) => UsernameValidity::Blocked,
+            Err(_) => UsernameValidity::InvalidChars,
+        }
+    }
+
+    /// Pick the actual end of a candle auction within its ending period from the randomness
+    /// drawn at its end, and return the leader at that block, clearing the recorded leaders.
+    /// Without bids during the ending period, the final leader wins.
+    fn candle_winner(
+        username: &[u8],
+        random: T::Hash,
+        ending_leaders: u32,
+        leader: Option<(T::AccountId, BalanceOf<T>)>,
+    ) -> Option<(T::AccountId, BalanceOf<T>)> {
+        if ending_leaders == 0 {
+            return leader;
+        }
+        let ending_period = T::EndingPeriod::get().saturated_into::<u32>().max(1);
+        let end_offset = u32::decode(&mut random.as_ref()).unwrap_or_default() % ending_period;
+
+        let mut winner: Option<(u32, (T::AccountId, BalanceOf<T>))> = None;
+        for (offset, leader) in <CandleLeaderOf<T>>::drain_prefix(username) {
+            if offset <= end_offset && winner.as_ref().map_or(true, |(best, _)| offset > *best) {
+                winner = Some((offset, leader));
+            }
+        }
+        winner.map(|(_, leader)| leader)
+    }
+
+    /// Upgrade the judgements of existing registrations. Registrations from before
+    /// `Releases::V3_0_0` hold no deposit and expire one `RegistrationPeriod` after the upgrade.
+    fn migrate_registrations(version: Releases) {
+        let upgrade = |judgements: Vec<(RegistrarIndex, OldJudgement)>| -> Vec<(RegistrarIndex, Judgement<BalanceOf<T>>)> {
+            judgements.into_iter().map(|(i, judgement)| (i, judgement.upgrade())).collect()
+        };
+        if version == Releases::V3_0_0 {
+            <RegistrationOf<T>>::translate::<
+                (Vec<(RegistrarIndex, OldJudgement)>, T::AccountId, BalanceOf<T>, T::BlockNumber),
+                _,
+            >(|_, (judgements, account_id, deposit, expiry)| Some(Registration {
+                judgements: upgrade(judgements),
+                account_id,
+                deposit,
+                expiry,
+            }));
+        } else {
+            let expiry = <frame_system::Module<T>>::block_number() + T::RegistrationPeriod::get();
+            <RegistrationOf<T>>::translate::<(Vec<(RegistrarIndex, OldJudgement)>, T::AccountId), _>(
+                |_, (judgements, account_id)| Some(Registration {
+                    judgements: upgrade(judgements),
+                    account_id,
+                    deposit: Zero::zero(),
+                    expiry,
+                })
+            );
+        }
+    }
+
+    /// Existing registrars charge no fee until they set one.
+    fn migrate_registrars() {
+        let _ = <Registrars<T>>::translate::<Vec<Option<T::AccountId>>, _>(|registrars| {
+            registrars.map(|registrars| registrars.into_iter()
+                .map(|account| account.map(|account| RegistrarInfo { account, fee: Zero::zero(), fields: 0 }))
+                .collect())
+        });
+    }
+
+    /// Index existing registrations by account. Accounts may end up holding more than
+    /// `MaxUsernamesPerAccount` usernames, which only prevents them registering more.
+    fn migrate_to_account_index() {
+        for (username, registration) in <RegistrationOf<T>>::iter() {
+            let account = registration.account_id;
+            if !Self::usernames_of(&account).contains(&username) {
+                <UsernamesOf<T>>::append(&account, &username);
+            }
+            if !<PrimaryUsernameOf<T>>::contains_key(&account) {
+                <PrimaryUsernameOf<T>>::insert(&account, username);
+            }
+        }
+    }
+
+    fn validate_username(username: &[u8]) -> Result<(), Error<T>> {
+        ensure!(username.len() >= T::MinUsernameLength::get() as usize, Error::<T>::UsernameIsVeryShort);
+        ensure!(username.len() <= T::MaxUsernameLength::get() as usize, Error::<T>::UsernameIsVeryLong);
+        Self::validate_chars(username)?;
+        ensure!(!Self::is_blocked(username), Error::<T>::UsernameBlocked);
+
+        Ok(())
+    }
+
+    /// Whether `username` matches a blocked pattern, exactly or by prefix.
+    pub fn is_blocked(username: &[u8]) -> bool {
+        (1..=username.len()).any(|len| match Self::blocked_pattern_of(&username[..len]) {
+            Some(BlockKind::Prefix) => true,
+            Some(BlockKind::Exact) => len == username.len(),
+            None => false,
+        })
+    }
+
+    fn validate_chars(username: &[u8]) -> Result<(), Error<T>> {
+        let is_valid_char = |c: &u8| {
+            (*c >= 48 && *c <= 57)      // '0' - '9'
+            || (*c >= 97 && *c <= 122)  // 'a' - 'z'
+            || *c == 95                 // '_'
+            || *c == 45                 // '-'
+            || *c == 46                 // '.'
+        };
+        ensure!(username.iter().all(is_valid_char), Error::<T>::UsernameHasInvalidChars);
+
+        Ok(())
+    }
+}
+
+impl<T: Trait> pallet_did::IdentityUsernames<T::AccountId> for Module<T> {
+    fn usernames_of(identity: &T::AccountId) -> Vec<Vec<u8>> {
+        Self::usernames_of_did(identity)
+    }
+}
+
+impl<T: Trait> StaticLookup for Module<T> {
+    type Source = address::Address<<T::AccountLookup as StaticLookup>::Source>;
+    type Target = T::AccountId;
+
+    fn lookup(a: Self::Source) -> Result<Self::Target, LookupError> {
+        match a {
+            address::Address::Account(source) => T::AccountLookup::lookup(source),
+            address::Address::Username(username) => Self::lookup_username(&username).ok_or(LookupError),
+        }
+    }
+
+    fn unlookup(a: Self::Target) -> Self::Source {
+        address::Address::Account(T::AccountLookup::unlookup(a))
     }
 }
@@ -1,11 +1,13 @@
-use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, ord_parameter_types, parameter_types, weights::Weight};
+use crate::{BlockKind, GenesisConfig, Module, Trait};
+use frame_support::{impl_outer_origin, ord_parameter_types, parameter_types, traits::{Get, Randomness}, weights::Weight};
 use frame_system as system;
 use frame_system::{EnsureOneOf, EnsureRoot, EnsureSignedBy};
 use sp_core::H256;
+use std::cell::RefCell;
 use sp_runtime::{
-    testing::Header,
+    testing::{Header, TestSignature, UintAuthorityId},
     traits::{BlakeTwo256, IdentityLookup},
+    transaction_validity::TransactionPriority,
     Perbill,
 };
 
@@ -25,6 +27,56 @@ parameter_types! {
     pub const MaxRegistrars: u32 = 2;
     pub const MinUsernameLength: u32 = 3;
     pub const MaxUsernameLength: u32 = 10;
+    pub const MaxUsernamesPerAccount: u32 = 2;
+    pub const ExistentialDeposit: u64 = 1;
+    pub const BaseDeposit: u64 = 10;
+    pub const ShortUsernameLength: u32 = 5;
+    pub const ShortUsernameDeposit: u64 = 5;
+    pub const RegistrationPeriod: u64 = 10;
+    pub const GracePeriod: u64 = 5;
+    pub const ReleaseReward: Perbill = Perbill::from_percent(20);
+    pub const MaxMissionTokensSupply: u128 = 1_000_000;
+    pub const MaxSubUsernames: u32 = 2;
+    pub const EndingPeriod: u64 = 4;
+    pub const RevealPeriod: u64 = 3;
+    pub const MaxBidsPerAuction: u32 = 2;
+    pub const MaxStatusListLength: u32 = 4;
+    pub const DidDepositBase: u64 = 10;
+    pub const DidDepositPerByte: u64 = 1;
+    pub const MaxDidNameLength: u32 = 64;
+    pub const MaxDidValueLength: u32 = 32;
+    pub const DidUnsignedPriority: TransactionPriority = 100;
+    pub const MaxDidExpiriesPerBlock: u32 = 2;
+}
+
+thread_local! {
+    static RANDOM_OFFSET: RefCell<u32> = RefCell::new(0);
+    static APPROVED_USERNAMES_ONLY: RefCell<bool> = RefCell::new(false);
+}
+
+pub fn set_approved_usernames_only(approved_only: bool) {
+    APPROVED_USERNAMES_ONLY.with(|v| *v.borrow_mut() = approved_only);
+}
+
+pub struct ApprovedUsernamesOnly;
+impl Get<bool> for ApprovedUsernamesOnly {
+    fn get() -> bool {
+        APPROVED_USERNAMES_ONLY.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_random_offset(offset: u32) {
+    RANDOM_OFFSET.with(|v| *v.borrow_mut() = offset);
+}
+
+/// Randomness drawing the offset into the ending period given to `set_random_offset`.
+pub struct TestRandomness;
+impl Randomness<H256> for TestRandomness {
+    fn random(_subject: &[u8]) -> H256 {
+        let mut random = H256::zero();
+        random.as_mut()[..4].copy_from_slice(&RANDOM_OFFSET.with(|v| *v.borrow()).to_le_bytes());
+        random
+    }
 }
 
 impl system::Trait for Test {
@@ -49,12 +101,56 @@ impl system::Trait for Test {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type PalletInfo = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
 
+impl pallet_balances::Trait for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl pallet_mission_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type MissionTokenId = u32;
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = MissionTokens;
+    type AccountData = pallet_mission_tokens::AccountData<u64>;
+    type OnNewAccount = ();
+    type MaxMissionTokensSupply = MaxMissionTokensSupply;
+}
+
+impl pallet_timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = ();
+    type WeightInfo = ();
+}
+
+impl pallet_did::Trait for Test {
+    type Event = ();
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+    type MaxStatusListLength = MaxStatusListLength;
+    type Currency = Balances;
+    type DepositBase = DidDepositBase;
+    type DepositPerByte = DidDepositPerByte;
+    type MaxNameLength = MaxDidNameLength;
+    type MaxValueLength = MaxDidValueLength;
+    type UnsignedPriority = DidUnsignedPriority;
+    type MaxExpiriesPerBlock = MaxDidExpiriesPerBlock;
+    type Usernames = UsernameRegistry;
+    type WeightInfo = ();
+}
+
 ord_parameter_types! {
     pub const One: u64 = 1;
     pub const Two: u64 = 2;
@@ -68,15 +164,48 @@ impl Trait for Test {
     type ForceOrigin = EnsureTwoOrRoot;
     type MinUsernameLength = MinUsernameLength;
     type MaxUsernameLength = MaxUsernameLength;
+    type MaxUsernamesPerAccount = MaxUsernamesPerAccount;
+    type Currency = Balances;
+    type BaseDeposit = BaseDeposit;
+    type ShortUsernameLength = ShortUsernameLength;
+    type ShortUsernameDeposit = ShortUsernameDeposit;
+    type RegistrationPeriod = RegistrationPeriod;
+    type GracePeriod = GracePeriod;
+    type ReleaseReward = ReleaseReward;
+    type Slashed = ();
+    type MaxSubUsernames = MaxSubUsernames;
+    type AuctionOrigin = EnsureTwoOrRoot;
+    type Randomness = TestRandomness;
+    type EndingPeriod = EndingPeriod;
+    type RevealPeriod = RevealPeriod;
+    type MaxBidsPerAuction = MaxBidsPerAuction;
+    type AuctionProceeds = ();
+    type AccountLookup = IdentityLookup<u64>;
+    type ApprovedUsernamesOnly = ApprovedUsernamesOnly;
     type WeightInfo = ();
 }
 
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type MissionTokens = pallet_mission_tokens::Module<Test>;
+pub type Did = pallet_did::Module<Test>;
 pub type UsernameRegistry = Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut t = system::GenesisConfig::default()
         .build_storage::<Test>()
-        .unwrap()
-        .into()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(4, 100), (5, 100), (6, 100)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    GenesisConfig::<Test> {
+        reserved_usernames: vec![(b"brand".to_vec(), 5)],
+        blocked_patterns: vec![(b"admin".to_vec(), BlockKind::Prefix)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    t.into()
 }
@@ -1,5 +1,14 @@
-use crate::{mock::*, Error, Judgement, Registration};
-use frame_support::{assert_noop, assert_ok, dispatch::DispatchError};
+use crate::{
+    address::Address, mock::*, AuctionKind, BlockKind, Error, Judgement, Price, RegistrarInfo, Registration,
+    Releases, StorageVersion,
+};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok, dispatch::DispatchError, storage::migration::put_storage_value,
+    traits::{OnInitialize, OnRuntimeUpgrade, ReservableCurrency}, StorageHasher, StorageValue, Twox64Concat,
+};
+use pallet_username_registry_rpc_runtime_api::{AccountUsernames, JudgementInfo, UsernameInfo, UsernameValidity};
+use sp_runtime::traits::{BlakeTwo256, Hash, LookupError, StaticLookup};
 
 #[test]
 fn adding_registrar_should_work() {
@@ -10,7 +19,10 @@ fn adding_registrar_should_work() {
             DispatchError::BadOrigin,
         );
         assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
-        assert_eq!(UsernameRegistry::registrars(), vec![Some(3)]);
+        assert_eq!(
+            UsernameRegistry::registrars(),
+            vec![Some(RegistrarInfo { account: 3, fee: 0, fields: 0 })]
+        );
     });
 }
 
@@ -35,8 +47,10 @@ fn registration_and_unregistration_should_work() {
         assert_eq!(
             UsernameRegistry::registration_of(b"foo".to_vec()),
             Some(Registration {
-                judgements: vec![(0, Judgement::Requested)],
-                account_id: 4
+                judgements: vec![(0, Judgement::FeePaid(0))],
+                account_id: 4,
+                deposit: 20,
+                expiry: 10,
             }),
         );
         assert_noop!(
@@ -68,8 +82,10 @@ fn killing_username_should_work() {
         assert_eq!(
             UsernameRegistry::registration_of(b"foo".to_vec()),
             Some(Registration {
-                judgements: vec![(0, Judgement::Requested)],
-                account_id: 4
+                judgements: vec![(0, Judgement::FeePaid(0))],
+                account_id: 4,
+                deposit: 20,
+                expiry: 10,
             }),
         );
         assert_noop!(
@@ -102,8 +118,10 @@ fn providing_judgement_should_work() {
         assert_eq!(
             UsernameRegistry::registration_of(b"foo".to_vec()),
             Some(Registration {
-                judgements: vec![(0, Judgement::Requested)],
-                account_id: 4
+                judgements: vec![(0, Judgement::FeePaid(0))],
+                account_id: 4,
+                deposit: 20,
+                expiry: 10,
             }),
         );
         assert_noop!(
@@ -111,7 +129,7 @@ fn providing_judgement_should_work() {
                 Origin::signed(4),
                 0,
                 b"foo".to_vec(),
-                Judgement::Approved
+                Judgement::KnownGood
             ),
             Error::<Test>::InvalidIndex,
         );
@@ -120,27 +138,38 @@ fn providing_judgement_should_work() {
                 Origin::signed(5),
                 0,
                 b"foo".to_vec(),
-                Judgement::Approved
+                Judgement::KnownGood
             ),
             Error::<Test>::InvalidIndex,
         );
+        assert_noop!(
+            UsernameRegistry::provide_judgement(
+                Origin::signed(3),
+                0,
+                b"foo".to_vec(),
+                Judgement::FeePaid(5)
+            ),
+            Error::<Test>::InvalidJudgement,
+        );
         assert_ok!(UsernameRegistry::provide_judgement(
             Origin::signed(3),
             0,
             b"foo".to_vec(),
-            Judgement::Approved
+            Judgement::KnownGood
         ));
         assert_ok!(UsernameRegistry::provide_judgement(
             Origin::signed(4),
             1,
             b"foo".to_vec(),
-            Judgement::Approved
+            Judgement::Reasonable
         ));
         assert_eq!(
             UsernameRegistry::registration_of(b"foo".to_vec()),
             Some(Registration {
-                judgements: vec![(0, Judgement::Approved), (1, Judgement::Approved)],
-                account_id: 4
+                judgements: vec![(0, Judgement::KnownGood), (1, Judgement::Reasonable)],
+                account_id: 4,
+                deposit: 20,
+                expiry: 10,
             }),
         );
     });
@@ -171,18 +200,20 @@ fn registration_and_unregistration_with_invalid_username_should_not_work() {
         assert_eq!(
             UsernameRegistry::registration_of(b"123".to_vec()),
             Some(Registration {
-                judgements: vec![(0, Judgement::Requested)],
-                account_id: 4
+                judgements: vec![(0, Judgement::FeePaid(0))],
+                account_id: 4,
+                deposit: 20,
+                expiry: 10,
             }),
         );
 
         assert_noop!(
             UsernameRegistry::unregister(Origin::signed(4), b"12".to_vec()),
-            Error::<Test>::UsernameIsVeryShort,
+            Error::<Test>::UsernameNotFound,
         );
         assert_noop!(
             UsernameRegistry::unregister(Origin::signed(4), b"12345678901".to_vec()),
-            Error::<Test>::UsernameIsVeryLong,
+            Error::<Test>::UsernameNotFound,
         );
         assert_ok!(UsernameRegistry::unregister(
             Origin::signed(4),
@@ -191,3 +222,884 @@ fn registration_and_unregistration_with_invalid_username_should_not_work() {
         assert_eq!(UsernameRegistry::registration_of(b"123".to_vec()), None);
     });
 }
+
+#[test]
+fn usernames_should_be_indexed_by_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"bar".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(4), b"baz".to_vec(), 0),
+            Error::<Test>::TooManyUsernames,
+        );
+        assert_eq!(UsernameRegistry::usernames_of(4), vec![b"foo".to_vec(), b"bar".to_vec()]);
+        // The first username is primary.
+        assert_eq!(UsernameRegistry::primary_username_of(4), Some(b"foo".to_vec()));
+
+        assert_noop!(
+            UsernameRegistry::set_primary_username(Origin::signed(5), b"bar".to_vec()),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_ok!(UsernameRegistry::set_primary_username(Origin::signed(4), b"bar".to_vec()));
+        assert_eq!(UsernameRegistry::primary_username_of(4), Some(b"bar".to_vec()));
+
+        // Removing the primary username makes the remaining one primary.
+        assert_ok!(UsernameRegistry::unregister(Origin::signed(4), b"bar".to_vec()));
+        assert_eq!(UsernameRegistry::usernames_of(4), vec![b"foo".to_vec()]);
+        assert_eq!(UsernameRegistry::primary_username_of(4), Some(b"foo".to_vec()));
+
+        assert_ok!(UsernameRegistry::kill_username(Origin::signed(2), b"foo".to_vec()));
+        assert!(UsernameRegistry::usernames_of(4).is_empty());
+        assert_eq!(UsernameRegistry::primary_username_of(4), None);
+    });
+}
+
+#[test]
+fn runtime_upgrade_should_index_usernames_by_account() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::put(Releases::V1_0_0);
+        // Judgements were `Requested` or `Approved`, and registrars bare accounts.
+        let requested_and_approved: Vec<(u32, u8)> = vec![(0, 0), (1, 1)];
+        for (username, account_id) in &[(b"foo", 4), (b"bar", 4), (b"baz", 5)] {
+            put_storage_value(
+                b"ValidatorRegistry",
+                b"RegistrationOf",
+                &Twox64Concat::hash(&username.to_vec().encode()),
+                (requested_and_approved.clone(), *account_id as u64),
+            );
+        }
+        put_storage_value(b"ValidatorRegistry", b"Registrars", &[], vec![Some(3u64), Some(4u64)]);
+
+        System::set_block_number(3);
+        UsernameRegistry::on_runtime_upgrade();
+        assert_eq!(
+            UsernameRegistry::registration_of(b"baz".to_vec()),
+            Some(Registration {
+                judgements: vec![(0, Judgement::FeePaid(0)), (1, Judgement::KnownGood)],
+                account_id: 5,
+                deposit: 0,
+                expiry: 13,
+            }),
+        );
+        assert_eq!(
+            UsernameRegistry::registrars(),
+            vec![
+                Some(RegistrarInfo { account: 3, fee: 0, fields: 0 }),
+                Some(RegistrarInfo { account: 4, fee: 0, fields: 0 }),
+            ]
+        );
+
+        let mut usernames = UsernameRegistry::usernames_of(4);
+        usernames.sort();
+        assert_eq!(usernames, vec![b"bar".to_vec(), b"foo".to_vec()]);
+        assert!(UsernameRegistry::primary_username_of(4).is_some());
+        assert_eq!(UsernameRegistry::usernames_of(5), vec![b"baz".to_vec()]);
+
+        // A second upgrade does nothing.
+        UsernameRegistry::on_runtime_upgrade();
+        assert_eq!(UsernameRegistry::usernames_of(5), vec![b"baz".to_vec()]);
+    });
+}
+
+#[test]
+fn runtime_upgrade_after_genesis_should_do_nothing() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::get(), Releases::V4_0_0);
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        let registration = UsernameRegistry::registration_of(b"foo".to_vec());
+        let registrars = UsernameRegistry::registrars();
+
+        assert_eq!(UsernameRegistry::on_runtime_upgrade(), 0);
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()), registration);
+        assert_eq!(UsernameRegistry::registrars(), registrars);
+        assert_eq!(UsernameRegistry::usernames_of(4), vec![b"foo".to_vec()]);
+    });
+}
+
+#[test]
+fn deposit_should_depend_on_username_length() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(UsernameRegistry::deposit_for(b"foo"), 20);
+        assert_eq!(UsernameRegistry::deposit_for(b"fooba"), 10);
+        assert_eq!(UsernameRegistry::deposit_for(b"foobarbaz"), 10);
+
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foobarbaz".to_vec(), 0));
+        assert_eq!(Balances::reserved_balance(4), 30);
+
+        assert_ok!(UsernameRegistry::unregister(Origin::signed(4), b"foo".to_vec()));
+        assert_eq!(Balances::reserved_balance(4), 10);
+        assert_ok!(UsernameRegistry::kill_username(Origin::signed(2), b"foobarbaz".to_vec()));
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(Balances::free_balance(4), 90);
+
+        // Account 7 holds no funds.
+        assert!(!Balances::can_reserve(&7, 10));
+        assert!(UsernameRegistry::register(Origin::signed(7), b"foo".to_vec(), 0).is_err());
+    });
+}
+
+#[test]
+fn renewing_username_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::renew(Origin::signed(5), b"foo".to_vec()),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_noop!(
+            UsernameRegistry::renew(Origin::signed(4), b"bar".to_vec()),
+            Error::<Test>::UsernameNotFound,
+        );
+
+        // Renewing early extends the current expiry.
+        assert_ok!(UsernameRegistry::renew(Origin::signed(4), b"foo".to_vec()));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().expiry, 20);
+
+        // Renewing during the grace period extends from now.
+        System::set_block_number(25);
+        assert_ok!(UsernameRegistry::renew(Origin::signed(4), b"foo".to_vec()));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().expiry, 35);
+
+        System::set_block_number(41);
+        assert_noop!(
+            UsernameRegistry::renew(Origin::signed(4), b"foo".to_vec()),
+            Error::<Test>::UsernameExpired,
+        );
+    });
+}
+
+#[test]
+fn releasing_expired_username_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+
+        System::set_block_number(15);
+        assert_noop!(
+            UsernameRegistry::release_expired(Origin::signed(5), b"foo".to_vec()),
+            Error::<Test>::GracePeriodNotOver,
+        );
+
+        System::set_block_number(16);
+        assert_ok!(UsernameRegistry::release_expired(Origin::signed(5), b"foo".to_vec()));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()), None);
+        assert!(UsernameRegistry::usernames_of(4).is_empty());
+        // The releaser gets 20% of the deposit, the rest goes back to the holder.
+        assert_eq!(Balances::free_balance(5), 104);
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(Balances::free_balance(4), 96);
+
+        // The username can be registered again.
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"foo".to_vec(), 0));
+    });
+}
+
+#[test]
+fn transferring_username_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::provide_judgement(
+            Origin::signed(3),
+            0,
+            b"foo".to_vec(),
+            Judgement::KnownGood
+        ));
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(5), b"foo".to_vec(), 6),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(4), b"foo".to_vec(), 4),
+            Error::<Test>::SelfTransfer,
+        );
+
+        assert_ok!(UsernameRegistry::transfer_username(Origin::signed(4), b"foo".to_vec(), 5));
+        assert_eq!(
+            UsernameRegistry::registration_of(b"foo".to_vec()),
+            Some(Registration { judgements: vec![], account_id: 5, deposit: 20, expiry: 10 }),
+        );
+        assert!(UsernameRegistry::usernames_of(4).is_empty());
+        assert_eq!(UsernameRegistry::primary_username_of(5), Some(b"foo".to_vec()));
+        // The sender pays the deposit of the new holder.
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(Balances::free_balance(4), 80);
+        assert_eq!(Balances::reserved_balance(5), 20);
+        assert_eq!(Balances::free_balance(5), 100);
+
+        System::set_block_number(11);
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(5), b"foo".to_vec(), 6),
+            Error::<Test>::UsernameExpired,
+        );
+    });
+}
+
+#[test]
+fn transferring_username_should_not_charge_or_overfill_dest() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"bar".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"baz".to_vec(), 0));
+
+        // Nothing changes if `dest` already holds the most usernames allowed.
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(4), b"foo".to_vec(), 5),
+            Error::<Test>::TooManyUsernames,
+        );
+
+        // Account 7 holds no funds, but need not pay anything to receive a username.
+        assert_ok!(UsernameRegistry::transfer_username(Origin::signed(4), b"foo".to_vec(), 7));
+        assert_eq!(UsernameRegistry::usernames_of(7), vec![b"foo".to_vec()]);
+        assert_eq!(Balances::reserved_balance(7), 20);
+        assert_eq!(Balances::free_balance(7), 0);
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(Balances::free_balance(4), 80);
+    });
+}
+
+#[test]
+fn selling_listed_username_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::buy_username(Origin::signed(5), b"foo".to_vec(), Price::Native(30)),
+            Error::<Test>::NotListed,
+        );
+        assert_noop!(
+            UsernameRegistry::list_username(Origin::signed(5), b"foo".to_vec(), Price::Native(30)),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_ok!(UsernameRegistry::list_username(Origin::signed(4), b"foo".to_vec(), Price::Native(30)));
+        assert_noop!(
+            UsernameRegistry::buy_username(Origin::signed(5), b"foo".to_vec(), Price::Native(20)),
+            Error::<Test>::PriceMismatch,
+        );
+
+        assert_ok!(UsernameRegistry::buy_username(Origin::signed(5), b"foo".to_vec(), Price::Native(30)));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().account_id, 5);
+        assert_eq!(UsernameRegistry::listing_of(b"foo".to_vec()), None);
+        assert_eq!(Balances::free_balance(4), 130);
+        assert_eq!(Balances::free_balance(5), 50);
+        assert_eq!(Balances::reserved_balance(5), 20);
+
+        // Listing in mission tokens.
+        assert_ok!(UsernameRegistry::list_username(
+            Origin::signed(5),
+            b"foo".to_vec(),
+            Price::MissionToken(1, 40)
+        ));
+        assert_noop!(
+            UsernameRegistry::buy_username(Origin::signed(6), b"foo".to_vec(), Price::MissionToken(1, 40)),
+            pallet_mission_tokens::Error::<Test>::InsufficientBalance,
+        );
+        MissionTokens::mint(6, 1, 50);
+        assert_ok!(UsernameRegistry::buy_username(
+            Origin::signed(6),
+            b"foo".to_vec(),
+            Price::MissionToken(1, 40)
+        ));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().account_id, 6);
+        assert_eq!(MissionTokens::free_balance(5, 1), 40);
+        assert_eq!(MissionTokens::free_balance(6, 1), 10);
+
+        // Unlisting.
+        assert_ok!(UsernameRegistry::list_username(Origin::signed(6), b"foo".to_vec(), Price::Native(5)));
+        assert_ok!(UsernameRegistry::unlist_username(Origin::signed(6), b"foo".to_vec()));
+        assert_noop!(
+            UsernameRegistry::buy_username(Origin::signed(4), b"foo".to_vec(), Price::Native(5)),
+            Error::<Test>::NotListed,
+        );
+    });
+}
+
+#[test]
+fn accepting_offer_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::make_offer(Origin::signed(4), b"foo".to_vec(), Price::Native(15)),
+            Error::<Test>::SelfTransfer,
+        );
+        assert_ok!(UsernameRegistry::make_offer(Origin::signed(6), b"foo".to_vec(), Price::Native(15)));
+        assert_eq!(Balances::reserved_balance(6), 15);
+        // A new offer replaces the previous one.
+        assert_ok!(UsernameRegistry::make_offer(Origin::signed(6), b"foo".to_vec(), Price::Native(25)));
+        assert_eq!(Balances::reserved_balance(6), 25);
+
+        assert_ok!(UsernameRegistry::make_offer(Origin::signed(5), b"foo".to_vec(), Price::Native(10)));
+        assert_ok!(UsernameRegistry::withdraw_offer(Origin::signed(5), b"foo".to_vec()));
+        assert_eq!(Balances::reserved_balance(5), 0);
+        assert_noop!(
+            UsernameRegistry::accept_offer(Origin::signed(4), b"foo".to_vec(), 5),
+            Error::<Test>::NoOffer,
+        );
+
+        assert_ok!(UsernameRegistry::accept_offer(Origin::signed(4), b"foo".to_vec(), 6));
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().account_id, 6);
+        assert_eq!(UsernameRegistry::offer_of(b"foo".to_vec(), 6), None);
+        assert_eq!(Balances::free_balance(4), 125);
+        assert_eq!(Balances::free_balance(6), 55);
+        assert_eq!(Balances::reserved_balance(6), 20);
+    });
+}
+
+#[test]
+fn sub_usernames_should_be_controlled_by_parent() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"team".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(5), b"bob.team".to_vec(), 0),
+            Error::<Test>::SubUsernameRequiresParent,
+        );
+        assert_noop!(
+            UsernameRegistry::create_sub_username(Origin::signed(5), b"team".to_vec(), b"bob".to_vec(), 5),
+            Error::<Test>::NotSubUsernameRegistrar,
+        );
+        assert_noop!(
+            UsernameRegistry::create_sub_username(Origin::signed(4), b"team".to_vec(), b"b.b".to_vec(), 5),
+            Error::<Test>::InvalidSubUsernameLabel,
+        );
+
+        assert_ok!(UsernameRegistry::create_sub_username(Origin::signed(4), b"team".to_vec(), b"bob".to_vec(), 5));
+        assert_eq!(UsernameRegistry::registration_of(b"bob.team".to_vec()), None);
+        assert_ok!(UsernameRegistry::accept_sub_username(Origin::signed(5), b"bob.team".to_vec()));
+        assert_eq!(
+            UsernameRegistry::registration_of(b"bob.team".to_vec()),
+            Some(Registration { judgements: vec![], account_id: 5, deposit: 0, expiry: u64::max_value() }),
+        );
+        assert_eq!(UsernameRegistry::usernames_of(5), vec![b"bob.team".to_vec()]);
+        assert_noop!(
+            UsernameRegistry::create_sub_username(Origin::signed(5), b"bob.team".to_vec(), b"x".to_vec(), 6),
+            Error::<Test>::NestedSubUsername,
+        );
+
+        // Sub-registration can be delegated.
+        assert_ok!(UsernameRegistry::set_sub_username_config(Origin::signed(4), b"team".to_vec(), Some(6), false));
+        assert_ok!(UsernameRegistry::create_sub_username(Origin::signed(6), b"team".to_vec(), b"eve".to_vec(), 6));
+        assert_noop!(
+            UsernameRegistry::create_sub_username(Origin::signed(4), b"team".to_vec(), b"carl".to_vec(), 4),
+            Error::<Test>::TooManySubUsernames,
+        );
+
+        // Only the parent can give sub-usernames away.
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(5), b"bob.team".to_vec(), 6),
+            Error::<Test>::ControlledByParent,
+        );
+        assert_noop!(
+            UsernameRegistry::revoke_sub_username(Origin::signed(6), b"eve.team".to_vec()),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_ok!(UsernameRegistry::revoke_sub_username(Origin::signed(4), b"eve.team".to_vec()));
+        assert_eq!(UsernameRegistry::registration_of(b"eve.team".to_vec()), None);
+        assert!(UsernameRegistry::usernames_of(6).is_empty());
+        assert_eq!(UsernameRegistry::sub_usernames_of(b"team".to_vec()), vec![b"bob.team".to_vec()]);
+
+        // Sub-usernames are removed with their parent.
+        assert_ok!(UsernameRegistry::unregister(Origin::signed(4), b"team".to_vec()));
+        assert_eq!(UsernameRegistry::registration_of(b"bob.team".to_vec()), None);
+        assert!(UsernameRegistry::usernames_of(5).is_empty());
+        assert!(UsernameRegistry::sub_usernames_of(b"team".to_vec()).is_empty());
+    });
+}
+
+#[test]
+fn sub_usernames_should_be_accepted_by_their_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"team".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"bar".to_vec(), 0));
+
+        // Offers do not count against the limit of the account they are made to.
+        assert_ok!(UsernameRegistry::create_sub_username(Origin::signed(4), b"team".to_vec(), b"bob".to_vec(), 5));
+        assert_eq!(UsernameRegistry::sub_username_offer_of(b"bob.team".to_vec()), Some((4, 5)));
+        assert!(UsernameRegistry::sub_usernames_of(b"team".to_vec()).is_empty());
+        assert_noop!(
+            UsernameRegistry::accept_sub_username(Origin::signed(6), b"bob.team".to_vec()),
+            Error::<Test>::NoSubUsernameOffer,
+        );
+        assert_noop!(
+            UsernameRegistry::accept_sub_username(Origin::signed(5), b"bob.team".to_vec()),
+            Error::<Test>::TooManyUsernames,
+        );
+        assert_ok!(UsernameRegistry::unregister(Origin::signed(5), b"bar".to_vec()));
+        assert_ok!(UsernameRegistry::accept_sub_username(Origin::signed(5), b"bob.team".to_vec()));
+        assert_eq!(UsernameRegistry::sub_username_offer_of(b"bob.team".to_vec()), None);
+        assert_eq!(UsernameRegistry::usernames_of(5), vec![b"foo".to_vec(), b"bob.team".to_vec()]);
+        assert_eq!(UsernameRegistry::sub_usernames_of(b"team".to_vec()), vec![b"bob.team".to_vec()]);
+
+        // An offer cannot be accepted once its creator may no longer create sub-usernames.
+        assert_ok!(UsernameRegistry::set_sub_username_config(Origin::signed(4), b"team".to_vec(), Some(6), false));
+        assert_ok!(UsernameRegistry::create_sub_username(Origin::signed(6), b"team".to_vec(), b"eve".to_vec(), 7));
+        assert_ok!(UsernameRegistry::set_sub_username_config(Origin::signed(4), b"team".to_vec(), None, false));
+        assert_noop!(
+            UsernameRegistry::accept_sub_username(Origin::signed(7), b"eve.team".to_vec()),
+            Error::<Test>::NotSubUsernameRegistrar,
+        );
+    });
+}
+
+#[test]
+fn sub_usernames_can_be_orphaned() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"team".to_vec(), 0));
+        assert_ok!(UsernameRegistry::create_sub_username(Origin::signed(4), b"team".to_vec(), b"bob".to_vec(), 5));
+        assert_ok!(UsernameRegistry::accept_sub_username(Origin::signed(5), b"bob.team".to_vec()));
+        assert_ok!(UsernameRegistry::set_sub_username_config(Origin::signed(4), b"team".to_vec(), None, true));
+
+        System::set_block_number(3);
+        assert_ok!(UsernameRegistry::kill_username(Origin::signed(2), b"team".to_vec()));
+        assert_eq!(
+            UsernameRegistry::registration_of(b"bob.team".to_vec()),
+            Some(Registration { judgements: vec![], account_id: 5, deposit: 0, expiry: 13 }),
+        );
+
+        // The orphan is an ordinary username, which the holder of a new `team` does not control.
+        assert_ok!(UsernameRegistry::transfer_username(Origin::signed(5), b"bob.team".to_vec(), 6));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"team".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::revoke_sub_username(Origin::signed(4), b"bob.team".to_vec()),
+            Error::<Test>::NotSubUsername,
+        );
+    });
+}
+
+#[test]
+fn approved_username_should_be_detected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert!(!UsernameRegistry::has_approved_username(&4));
+
+        assert_ok!(UsernameRegistry::provide_judgement(
+            Origin::signed(3),
+            0,
+            b"foo".to_vec(),
+            Judgement::LowQuality
+        ));
+        assert!(!UsernameRegistry::has_approved_username(&4));
+
+        assert_ok!(UsernameRegistry::provide_judgement(
+            Origin::signed(3),
+            0,
+            b"foo".to_vec(),
+            Judgement::Reasonable
+        ));
+        assert!(UsernameRegistry::has_approved_username(&4));
+        assert!(!UsernameRegistry::has_approved_username(&5));
+
+        // An expired username no longer counts.
+        System::set_block_number(11);
+        assert!(!UsernameRegistry::has_approved_username(&4));
+    });
+}
+
+#[test]
+fn judgement_fees_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::set_fee(Origin::signed(3), 0, 10));
+        assert_noop!(
+            UsernameRegistry::set_fee(Origin::signed(4), 0, 10),
+            Error::<Test>::InvalidIndex,
+        );
+        // Registering requests the judgement of the registrar, reserving its fee.
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_eq!(Balances::reserved_balance(4), 30);
+        assert_eq!(
+            UsernameRegistry::registration_of(b"foo".to_vec()).unwrap().judgements,
+            vec![(0, Judgement::FeePaid(10))]
+        );
+
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 1, 10),
+            Error::<Test>::EmptyIndex,
+        );
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 0, 9),
+            Error::<Test>::FeeChanged,
+        );
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(5), b"foo".to_vec(), 0, 10),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 0, 10),
+            Error::<Test>::StickyJudgement,
+        );
+
+        // Cancelling returns the fee.
+        assert_ok!(UsernameRegistry::cancel_request(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_eq!(Balances::reserved_balance(4), 20);
+        assert_noop!(
+            UsernameRegistry::cancel_request(Origin::signed(4), b"foo".to_vec(), 0),
+            Error::<Test>::JudgementNotFound,
+        );
+
+        // The registrar is paid on judgement, at its new account.
+        assert_ok!(UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 0, 10));
+        assert_ok!(UsernameRegistry::set_account_id(Origin::signed(3), 0, 6));
+        assert_ok!(UsernameRegistry::set_fields(Origin::signed(6), 0, 0b11));
+        assert_eq!(
+            UsernameRegistry::registrars(),
+            vec![Some(RegistrarInfo { account: 6, fee: 10, fields: 0b11 })]
+        );
+        assert_ok!(UsernameRegistry::provide_judgement(
+            Origin::signed(6),
+            0,
+            b"foo".to_vec(),
+            Judgement::Erroneous
+        ));
+        assert_eq!(Balances::reserved_balance(4), 20);
+        assert_eq!(Balances::free_balance(6), 110);
+        assert_noop!(
+            UsernameRegistry::cancel_request(Origin::signed(4), b"foo".to_vec(), 0),
+            Error::<Test>::JudgementGiven,
+        );
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 0, 10),
+            Error::<Test>::StickyJudgement,
+        );
+
+        // A removed registrar cannot be asked for judgements.
+        assert_noop!(
+            UsernameRegistry::remove_registrar(Origin::signed(4), 0),
+            DispatchError::BadOrigin,
+        );
+        assert_ok!(UsernameRegistry::remove_registrar(Origin::signed(1), 0));
+        assert_eq!(UsernameRegistry::registrars(), vec![None]);
+        assert_noop!(
+            UsernameRegistry::request_judgement(Origin::signed(4), b"foo".to_vec(), 0, 10),
+            Error::<Test>::EmptyIndex,
+        );
+    });
+}
+
+#[test]
+fn sealed_bid_auction_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            UsernameRegistry::open_auction(Origin::signed(3), b"ab".to_vec(), AuctionKind::SealedBid, 10, 5),
+            DispatchError::BadOrigin,
+        );
+        assert_ok!(UsernameRegistry::open_auction(Origin::signed(2), b"ab".to_vec(), AuctionKind::SealedBid, 10, 5));
+        assert_noop!(
+            UsernameRegistry::open_auction(Origin::signed(2), b"ab".to_vec(), AuctionKind::SealedBid, 10, 5),
+            Error::<Test>::UsernameInAuction,
+        );
+
+        let commitment_of = |who: u64, amount: u64, salt: [u8; 32]| BlakeTwo256::hash_of(&(who, amount, salt));
+        assert_ok!(UsernameRegistry::commit_bid(Origin::signed(4), b"ab".to_vec(), commitment_of(4, 30, [1; 32]), 40));
+        assert_ok!(UsernameRegistry::commit_bid(Origin::signed(5), b"ab".to_vec(), commitment_of(5, 25, [2; 32]), 25));
+        assert_noop!(
+            UsernameRegistry::commit_bid(Origin::signed(6), b"ab".to_vec(), commitment_of(6, 50, [3; 32]), 50),
+            Error::<Test>::TooManyBids,
+        );
+        assert_noop!(
+            UsernameRegistry::bid(Origin::signed(6), b"ab".to_vec(), 50),
+            Error::<Test>::WrongAuctionKind,
+        );
+        assert_noop!(
+            UsernameRegistry::reveal_bid(Origin::signed(4), b"ab".to_vec(), 30, [1; 32]),
+            Error::<Test>::NotRevealPeriod,
+        );
+        assert_eq!(Balances::reserved_balance(4), 40);
+
+        System::set_block_number(6);
+        assert_noop!(
+            UsernameRegistry::commit_bid(Origin::signed(4), b"ab".to_vec(), commitment_of(4, 35, [1; 32]), 40),
+            Error::<Test>::BiddingClosed,
+        );
+        assert_noop!(
+            UsernameRegistry::reveal_bid(Origin::signed(4), b"ab".to_vec(), 35, [1; 32]),
+            Error::<Test>::InvalidReveal,
+        );
+        assert_ok!(UsernameRegistry::reveal_bid(Origin::signed(4), b"ab".to_vec(), 30, [1; 32]));
+        assert_ok!(UsernameRegistry::reveal_bid(Origin::signed(5), b"ab".to_vec(), 25, [2; 32]));
+        assert_noop!(
+            UsernameRegistry::settle_auction(Origin::signed(6), b"ab".to_vec()),
+            Error::<Test>::AuctionNotOver,
+        );
+
+        System::set_block_number(9);
+        assert_ok!(UsernameRegistry::settle_auction(Origin::signed(6), b"ab".to_vec()));
+        assert_eq!(UsernameRegistry::auction_of(b"ab".to_vec()), None);
+        // The deposit of the username is kept reserved out of the winning bid.
+        assert_eq!((Balances::free_balance(4), Balances::reserved_balance(4)), (70, 25));
+        assert_eq!((Balances::free_balance(5), Balances::reserved_balance(5)), (100, 0));
+        assert_eq!(
+            UsernameRegistry::registration_of(b"ab".to_vec()),
+            Some(Registration {
+                judgements: vec![],
+                account_id: 4,
+                deposit: 25,
+                expiry: 19,
+            }),
+        );
+        assert_eq!(UsernameRegistry::usernames_of(4), vec![b"ab".to_vec()]);
+    });
+}
+
+#[test]
+fn auction_won_by_account_with_too_many_usernames_should_fail() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"bar".to_vec(), 0));
+        let reserved = Balances::reserved_balance(4);
+
+        assert_ok!(UsernameRegistry::open_auction(Origin::signed(2), b"ab".to_vec(), AuctionKind::SealedBid, 10, 5));
+        let commitment = BlakeTwo256::hash_of(&(4u64, 30u64, [1u8; 32]));
+        assert_ok!(UsernameRegistry::commit_bid(Origin::signed(4), b"ab".to_vec(), commitment, 30));
+        System::set_block_number(6);
+        assert_ok!(UsernameRegistry::reveal_bid(Origin::signed(4), b"ab".to_vec(), 30, [1; 32]));
+
+        System::set_block_number(9);
+        assert_ok!(UsernameRegistry::settle_auction(Origin::signed(6), b"ab".to_vec()));
+        assert_eq!(UsernameRegistry::auction_of(b"ab".to_vec()), None);
+        assert_eq!(UsernameRegistry::registration_of(b"ab".to_vec()), None);
+        assert_eq!(Balances::reserved_balance(4), reserved);
+        assert_eq!(UsernameRegistry::usernames_of(4), vec![b"foo".to_vec(), b"bar".to_vec()]);
+    });
+}
+
+#[test]
+fn candle_auction_should_end_at_random_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            UsernameRegistry::open_auction(Origin::signed(2), b"xyz".to_vec(), AuctionKind::Candle, 10, 3),
+            Error::<Test>::AuctionTooShort,
+        );
+        assert_ok!(UsernameRegistry::open_auction(Origin::signed(2), b"xyz".to_vec(), AuctionKind::Candle, 10, 10));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(4), b"xyz".to_vec(), 0),
+            Error::<Test>::UsernameInAuction,
+        );
+
+        System::set_block_number(2);
+        assert_noop!(
+            UsernameRegistry::bid(Origin::signed(4), b"xyz".to_vec(), 5),
+            Error::<Test>::BidTooLow,
+        );
+        assert_ok!(UsernameRegistry::bid(Origin::signed(4), b"xyz".to_vec(), 20));
+        assert_noop!(
+            UsernameRegistry::bid(Origin::signed(5), b"xyz".to_vec(), 20),
+            Error::<Test>::BidTooLow,
+        );
+
+        // The ending period covers blocks 7 to 10.
+        System::set_block_number(8);
+        assert_ok!(UsernameRegistry::bid(Origin::signed(5), b"xyz".to_vec(), 30));
+        System::set_block_number(10);
+        assert_ok!(UsernameRegistry::bid(Origin::signed(4), b"xyz".to_vec(), 40));
+        assert_eq!(Balances::reserved_balance(4), 40);
+        assert_noop!(
+            UsernameRegistry::settle_auction(Origin::signed(6), b"xyz".to_vec()),
+            Error::<Test>::AuctionNotOver,
+        );
+
+        // The randomness is drawn at block 11, and the auction ends at block 9, when 5 was leading.
+        set_random_offset(6);
+        System::set_block_number(11);
+        UsernameRegistry::on_initialize(11);
+        assert!(UsernameRegistry::candle_auctions_ending_at(11).is_empty());
+        assert_noop!(
+            UsernameRegistry::bid(Origin::signed(5), b"xyz".to_vec(), 50),
+            Error::<Test>::BiddingClosed,
+        );
+
+        // Randomness after the end does not change the winner.
+        set_random_offset(0);
+        System::set_block_number(12);
+        assert_ok!(UsernameRegistry::settle_auction(Origin::signed(6), b"xyz".to_vec()));
+        assert_eq!((Balances::free_balance(4), Balances::reserved_balance(4)), (100, 0));
+        assert_eq!((Balances::free_balance(5), Balances::reserved_balance(5)), (70, 20));
+        assert_eq!(UsernameRegistry::registration_of(b"xyz".to_vec()).map(|r| (r.account_id, r.deposit)), Some((5, 20)));
+        assert_eq!(UsernameRegistry::candle_leader_of(b"xyz".to_vec(), 1), None);
+    });
+}
+
+#[test]
+fn username_lookup_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(4));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"bar".to_vec())), Err(LookupError));
+        assert_eq!(UsernameRegistry::lookup(Address::Account(5)), Ok(5));
+        assert_eq!(UsernameRegistry::unlookup(5), Address::Account(5));
+
+        set_approved_usernames_only(true);
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Err(LookupError));
+        assert_ok!(UsernameRegistry::provide_judgement(Origin::signed(3), 0, b"foo".to_vec(), Judgement::LowQuality));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Err(LookupError));
+        assert_ok!(UsernameRegistry::provide_judgement(Origin::signed(3), 0, b"foo".to_vec(), Judgement::KnownGood));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(4));
+
+        System::set_block_number(11);
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Err(LookupError));
+        set_approved_usernames_only(false);
+    });
+}
+
+#[test]
+fn did_link_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::link_did(Origin::signed(4), b"foo".to_vec(), 5),
+            pallet_did::Error::<Test>::InvalidDelegate,
+        );
+        assert_ok!(Did::add_delegate(Origin::signed(5), 5, 4, pallet_did::USERNAME_DELEGATE_TYPE.to_vec(), None));
+        assert_ok!(UsernameRegistry::link_did(Origin::signed(4), b"foo".to_vec(), 5));
+        assert_eq!(UsernameRegistry::did_of(b"foo".to_vec()), Some(5));
+        assert_eq!(<UsernameRegistry as pallet_did::IdentityUsernames<u64>>::usernames_of(&5), vec![b"foo".to_vec()]);
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(5));
+
+        // The owner of the DID now controls the username, while 4 still holds the deposit.
+        assert_noop!(
+            UsernameRegistry::unregister(Origin::signed(4), b"foo".to_vec()),
+            Error::<Test>::UnregisterForbidden,
+        );
+        assert_ok!(Did::change_owner(Origin::signed(5), 5, 6));
+        // Payments to the username now go to the new owner, who also controls it.
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(6));
+        let info = UsernameRegistry::username_info(b"foo").unwrap();
+        assert_eq!((info.account_id, info.controller, info.did), (4, 6, Some(5)));
+        assert_noop!(
+            UsernameRegistry::transfer_username(Origin::signed(5), b"foo".to_vec(), 7),
+            Error::<Test>::NotUsernameOwner,
+        );
+        assert_ok!(UsernameRegistry::transfer_username(Origin::signed(6), b"foo".to_vec(), 6));
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(UsernameRegistry::registration_of(b"foo".to_vec()).map(|r| r.account_id), Some(6));
+        assert_eq!(UsernameRegistry::did_of(b"foo".to_vec()), None);
+        assert!(UsernameRegistry::usernames_of_did(5).is_empty());
+
+        assert_ok!(UsernameRegistry::link_did(Origin::signed(6), b"foo".to_vec(), 5));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(6));
+        assert_noop!(
+            UsernameRegistry::unlink_did(Origin::signed(7), b"foo".to_vec()),
+            pallet_did::Error::<Test>::InvalidDelegate,
+        );
+        assert_ok!(UsernameRegistry::unlink_did(Origin::signed(6), b"foo".to_vec()));
+        assert_eq!(UsernameRegistry::lookup(Address::Username(b"foo".to_vec())), Ok(6));
+        assert_noop!(
+            UsernameRegistry::unlink_did(Origin::signed(6), b"foo".to_vec()),
+            Error::<Test>::NotLinked,
+        );
+    });
+}
+
+#[test]
+fn reserved_and_blocked_usernames_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        // Reserved and blocked in the genesis config.
+        assert_eq!(UsernameRegistry::reserved_username_of(b"brand".to_vec()), Some(5));
+        assert_noop!(
+            UsernameRegistry::open_auction(Origin::signed(2), b"brand".to_vec(), AuctionKind::SealedBid, 10, 10),
+            Error::<Test>::UsernameReserved,
+        );
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(4), b"brand".to_vec(), 0),
+            Error::<Test>::UsernameReserved,
+        );
+        assert_ok!(UsernameRegistry::register(Origin::signed(5), b"brand".to_vec(), 0));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(4), b"admins".to_vec(), 0),
+            Error::<Test>::UsernameBlocked,
+        );
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"xadmin".to_vec(), 0));
+
+        assert_noop!(
+            UsernameRegistry::block_pattern(Origin::signed(4), b"evil".to_vec(), BlockKind::Exact),
+            DispatchError::BadOrigin,
+        );
+        assert_noop!(
+            UsernameRegistry::block_pattern(Origin::signed(2), b"Evil".to_vec(), BlockKind::Exact),
+            Error::<Test>::UsernameHasInvalidChars,
+        );
+        assert_ok!(UsernameRegistry::block_pattern(Origin::signed(2), b"evil".to_vec(), BlockKind::Exact));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(6), b"evil".to_vec(), 0),
+            Error::<Test>::UsernameBlocked,
+        );
+        assert_ok!(UsernameRegistry::register(Origin::signed(6), b"evils".to_vec(), 0));
+        assert_ok!(UsernameRegistry::unblock_pattern(Origin::root(), b"admin".to_vec()));
+        assert_noop!(
+            UsernameRegistry::unblock_pattern(Origin::root(), b"admin".to_vec()),
+            Error::<Test>::NotBlocked,
+        );
+        assert_ok!(UsernameRegistry::register(Origin::signed(6), b"admins".to_vec(), 0));
+
+        assert_ok!(UsernameRegistry::reserve_username(Origin::signed(2), b"shop".to_vec(), 6));
+        assert_noop!(
+            UsernameRegistry::register(Origin::signed(4), b"shop".to_vec(), 0),
+            Error::<Test>::UsernameReserved,
+        );
+        assert_ok!(UsernameRegistry::unreserve_username(Origin::signed(2), b"shop".to_vec()));
+        assert_noop!(
+            UsernameRegistry::unreserve_username(Origin::signed(2), b"shop".to_vec()),
+            Error::<Test>::NotReserved,
+        );
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"shop".to_vec(), 0));
+    });
+}
+
+#[test]
+fn runtime_api_queries_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(UsernameRegistry::add_registrar(Origin::signed(1), 3));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"foo".to_vec(), 0));
+        assert_ok!(UsernameRegistry::register(Origin::signed(4), b"bar".to_vec(), 0));
+        assert_ok!(UsernameRegistry::provide_judgement(Origin::signed(3), 0, b"foo".to_vec(), Judgement::KnownGood));
+        assert_ok!(UsernameRegistry::open_auction(Origin::signed(2), b"xyz".to_vec(), AuctionKind::Candle, 10, 10));
+
+        assert_eq!(
+            UsernameRegistry::username_info(b"foo"),
+            Some(UsernameInfo {
+                account_id: 4,
+                controller: 4,
+                judgements: vec![(0, JudgementInfo::KnownGood)],
+                expiry: 10,
+                did: None,
+                resolvable: true,
+            }),
+        );
+        assert_eq!(UsernameRegistry::username_info(b"baz"), None);
+        assert_eq!(
+            UsernameRegistry::account_usernames(&4),
+            AccountUsernames {
+                primary: Some(b"foo".to_vec()),
+                usernames: vec![b"foo".to_vec(), b"bar".to_vec()],
+            },
+        );
+
+        assert_eq!(UsernameRegistry::username_validity(b"baz"), UsernameValidity::Available);
+        assert_eq!(UsernameRegistry::username_validity(b"foo"), UsernameValidity::Registered);
+        assert_eq!(UsernameRegistry::username_validity(b"xyz"), UsernameValidity::InAuction);
+        assert_eq!(UsernameRegistry::username_validity(b"ab"), UsernameValidity::TooShort);
+        assert_eq!(UsernameRegistry::username_validity(b"abcdefghijk"), UsernameValidity::TooLong);
+        assert_eq!(UsernameRegistry::username_validity(b"Foo"), UsernameValidity::InvalidChars);
+        assert_eq!(UsernameRegistry::username_validity(b"a.foo"), UsernameValidity::RequiresParent);
+        assert_eq!(UsernameRegistry::username_validity(b"brand"), UsernameValidity::Reserved);
+        assert_eq!(UsernameRegistry::username_validity(b"admin1"), UsernameValidity::Blocked);
+    });
+}
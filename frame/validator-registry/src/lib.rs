@@ -1,9 +1,16 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, traits::Get,
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    storage::IterableStorageDoubleMap,
+    traits::Get,
+    weights::{DispatchClass, Weight},
 };
 use frame_system::ensure_signed;
+use pallet_staking::EraIndex;
+use sp_runtime::traits::Zero;
+use sp_staking::SessionIndex;
+use sp_std::{marker::PhantomData, prelude::*};
 
 #[cfg(test)]
 mod mock;
@@ -13,11 +20,34 @@ mod tests;
 
 pub trait Trait: frame_system::Trait + pallet_mission_tokens::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// The maximum number of missions a nominator can allocate its stake to.
+    type MaxNominatorMissions: Get<u32>;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as ValidatorRegistry {
         MissionOf get(fn mission_of): map hasher(blake2_128_concat) T::AccountId => T::MissionTokenId;
+
+        /// Mission of each validator elected for an era, snapshotted when the era is planned.
+        ///
+        /// Only validators with a registered mission are stored. Eras older than the staking
+        /// `HistoryDepth` are pruned.
+        ErasMissionOf get(fn eras_mission_of):
+            double_map hasher(twox_64_concat) EraIndex, hasher(blake2_128_concat) T::AccountId
+            => T::MissionTokenId;
+
+        /// Mission allocation of each nominator, as `(mission, weight)` pairs. The share of
+        /// validator rewards backed by the nominator is split between the missions in proportion
+        /// to their weights.
+        NominatorMissionsOf get(fn nominator_missions_of):
+            map hasher(blake2_128_concat) T::AccountId => Vec<(T::MissionTokenId, u32)>;
+
+        /// Mission allocation of each nominator exposed in an era, snapshotted when the era is
+        /// planned. Pruned together with `ErasMissionOf`.
+        ErasNominatorMissionsOf get(fn eras_nominator_missions_of):
+            double_map hasher(twox_64_concat) EraIndex, hasher(blake2_128_concat) T::AccountId
+            => Vec<(T::MissionTokenId, u32)>;
     }
 }
 
@@ -29,6 +59,8 @@ decl_event!(
     {
         Registered(AccountId, MissionTokenId),
         Unregistered(AccountId, MissionTokenId),
+        NominatorMissionsSet(AccountId),
+        NominatorMissionsCleared(AccountId),
     }
 );
 
@@ -36,6 +68,14 @@ decl_error! {
     pub enum Error for Module<T: Trait> {
         AlreadyRegistered,
         NotFound,
+        /// The mission allocation is empty.
+        EmptyAllocation,
+        /// The mission allocation has more than `MaxNominatorMissions` entries.
+        TooManyMissions,
+        /// A mission appears more than once in the allocation.
+        DuplicateMission,
+        /// A mission in the allocation has zero weight.
+        ZeroWeight,
     }
 }
 
@@ -43,6 +83,8 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         type Error = Error<T>;
 
+        const MaxNominatorMissions: u32 = T::MaxNominatorMissions::get();
+
         fn deposit_event() = default;
 
         #[weight = 10_000 + T::DbWeight::get().writes(1)]
@@ -70,5 +112,198 @@ decl_module! {
             Self::deposit_event(RawEvent::Unregistered(validator, mission_token_id));
             Ok(())
         }
+
+        /// Set the mission allocation of the nominator's stake as `(mission, weight)` pairs,
+        /// replacing any previous allocation.
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn set_nominator_missions(
+            origin,
+            missions: Vec<(T::MissionTokenId, u32)>,
+        ) -> dispatch::DispatchResult {
+            let nominator = ensure_signed(origin)?;
+
+            ensure!(!missions.is_empty(), Error::<T>::EmptyAllocation);
+            ensure!(
+                missions.len() <= T::MaxNominatorMissions::get() as usize,
+                Error::<T>::TooManyMissions
+            );
+            for (i, (mission_token_id, weight)) in missions.iter().enumerate() {
+                <pallet_mission_tokens::Module<T>>::validate_mission_token_id(*mission_token_id)?;
+                ensure!(*weight > 0, Error::<T>::ZeroWeight);
+                ensure!(
+                    !missions[..i].iter().any(|(id, _)| id == mission_token_id),
+                    Error::<T>::DuplicateMission
+                );
+            }
+
+            <NominatorMissionsOf<T>>::insert(&nominator, missions);
+
+            Self::deposit_event(RawEvent::NominatorMissionsSet(nominator));
+            Ok(())
+        }
+
+        /// Remove the mission allocation of the nominator's stake. Its share of rewards then
+        /// follows the mission of the validators it backs.
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        pub fn clear_nominator_missions(origin) -> dispatch::DispatchResult {
+            let nominator = ensure_signed(origin)?;
+
+            ensure!(<NominatorMissionsOf<T>>::contains_key(&nominator), Error::<T>::NotFound);
+            <NominatorMissionsOf<T>>::remove(&nominator);
+
+            Self::deposit_event(RawEvent::NominatorMissionsCleared(nominator));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Record the current mission of each of `validators` for `era`.
+    ///
+    /// The snapshots of the era falling out of `history_depth` are removed.
+    pub fn note_era_missions(era: EraIndex, validators: &[T::AccountId], history_depth: u32) {
+        for validator in validators {
+            let mission_token_id = <MissionOf<T>>::get(validator);
+            if !mission_token_id.is_zero() {
+                <ErasMissionOf<T>>::insert(era, validator, mission_token_id);
+            }
+        }
+
+        Self::prune_era_missions(era, history_depth);
+    }
+
+    /// Record the current mission allocation of each of `nominators` for `era`.
+    ///
+    /// `nominators` must not contain duplicates.
+    pub fn note_era_nominator_missions(era: EraIndex, nominators: &[T::AccountId]) {
+        for nominator in nominators {
+            if <NominatorMissionsOf<T>>::contains_key(nominator) {
+                <ErasNominatorMissionsOf<T>>::insert(
+                    era,
+                    nominator,
+                    <NominatorMissionsOf<T>>::get(nominator),
+                );
+            }
+        }
+    }
+
+    /// Copy the snapshots of the previous era to `era`, for an era planned without a change in
+    /// the validator set.
+    ///
+    /// Returns the number of snapshots copied.
+    pub fn carry_era_missions(era: EraIndex, history_depth: u32) -> u32 {
+        let mut copied = 0u32;
+        if let Some(previous_era) = era.checked_sub(1) {
+            for (validator, mission_token_id) in <ErasMissionOf<T>>::iter_prefix(previous_era) {
+                <ErasMissionOf<T>>::insert(era, validator, mission_token_id);
+                copied += 1;
+            }
+            for (nominator, missions) in <ErasNominatorMissionsOf<T>>::iter_prefix(previous_era) {
+                <ErasNominatorMissionsOf<T>>::insert(era, nominator, missions);
+                copied += 1;
+            }
+        }
+
+        Self::prune_era_missions(era, history_depth);
+        copied
+    }
+
+    fn prune_era_missions(era: EraIndex, history_depth: u32) {
+        if let Some(old_era) = era.checked_sub(history_depth + 1) {
+            <ErasMissionOf<T>>::remove_prefix(old_era);
+            <ErasNominatorMissionsOf<T>>::remove_prefix(old_era);
+        }
+    }
+}
+
+impl<T: Trait + pallet_staking::Trait> Module<T> {
+    /// Snapshot validator and nominator missions if `new_index` is the first session of a newly planned era.
+    ///
+    /// Only the nominators kept in the clipped exposure of each validator are snapshotted, since
+    /// only they are rewarded, which bounds the work by `MaxNominatorRewardedPerValidator` per
+    /// validator. The weight is registered against the current block.
+    fn note_new_session(new_index: SessionIndex, new_validators: Option<Vec<T::AccountId>>) {
+        let era = match <pallet_staking::Module<T>>::current_era() {
+            Some(era) => era,
+            None => return,
+        };
+        if <pallet_staking::Module<T>>::eras_start_session_index(era) != Some(new_index) {
+            return;
+        }
+
+        let history_depth = <pallet_staking::Module<T>>::history_depth();
+        let weight = match new_validators {
+            Some(validators) => {
+                let mut nominators = validators
+                    .iter()
+                    .flat_map(|v| <pallet_staking::Module<T>>::eras_stakers_clipped(era, v).others)
+                    .map(|exposure| exposure.who)
+                    .collect::<Vec<_>>();
+                nominators.sort();
+                nominators.dedup();
+                Self::note_era_nominator_missions(era, &nominators);
+                Self::note_era_missions(era, &validators, history_depth);
+
+                let validators = validators.len() as Weight;
+                let nominators = nominators.len() as Weight;
+                // One exposure and one mission read, and one snapshot write per validator and
+                // nominator.
+                T::DbWeight::get().reads_writes(
+                    validators.saturating_mul(2).saturating_add(nominators),
+                    validators.saturating_add(nominators),
+                )
+            }
+            None => {
+                let copied = Self::carry_era_missions(era, history_depth) as Weight;
+                T::DbWeight::get().reads_writes(copied, copied)
+            }
+        };
+        // Pruning the era falling out of the history depth.
+        let weight = weight.saturating_add(T::DbWeight::get().writes(2));
+        <frame_system::Module<T>>::register_extra_weight_unchecked(weight, DispatchClass::Mandatory);
+    }
+}
+
+/// A `SessionManager` implementation that wraps an inner `I` (usually the staking module) and
+/// snapshots the mission of each elected validator whenever a new era is planned.
+pub struct NoteEraMissions<T, I>(PhantomData<(T, I)>);
+
+impl<T, I> pallet_session::SessionManager<T::AccountId> for NoteEraMissions<T, I>
+where
+    T: Trait + pallet_staking::Trait,
+    I: pallet_session::SessionManager<T::AccountId>,
+{
+    fn new_session(new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+        let new_validators = I::new_session(new_index);
+        <Module<T>>::note_new_session(new_index, new_validators.clone());
+        new_validators
+    }
+    fn start_session(start_index: SessionIndex) {
+        I::start_session(start_index)
+    }
+    fn end_session(end_index: SessionIndex) {
+        I::end_session(end_index)
+    }
+}
+
+impl<T, I, F> pallet_session::historical::SessionManager<T::AccountId, F> for NoteEraMissions<T, I>
+where
+    T: Trait + pallet_staking::Trait,
+    I: pallet_session::historical::SessionManager<T::AccountId, F>,
+{
+    fn new_session(new_index: SessionIndex) -> Option<Vec<(T::AccountId, F)>> {
+        let new_validators_and_id =
+            <I as pallet_session::historical::SessionManager<_, _>>::new_session(new_index);
+        let new_validators = new_validators_and_id
+            .as_ref()
+            .map(|new_validators| new_validators.iter().map(|(v, _id)| v.clone()).collect());
+        <Module<T>>::note_new_session(new_index, new_validators);
+        new_validators_and_id
+    }
+    fn start_session(start_index: SessionIndex) {
+        <I as pallet_session::historical::SessionManager<_, _>>::start_session(start_index)
+    }
+    fn end_session(end_index: SessionIndex) {
+        <I as pallet_session::historical::SessionManager<_, _>>::end_session(end_index)
     }
 }
@@ -21,6 +21,7 @@ parameter_types! {
     pub const MaximumBlockWeight: Weight = 1024;
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const MaxNominatorMissions: u32 = 4;
 }
 
 impl system::Trait for Test {
@@ -54,6 +55,7 @@ impl system::Trait for Test {
 impl Trait for Test {
     type Event = ();
     type MissionId = u32;
+    type MaxNominatorMissions = MaxNominatorMissions;
 }
 
 pub type ValidatorRegistry = Module<Test>;
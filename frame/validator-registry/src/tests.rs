@@ -37,3 +37,84 @@ fn registration_with_invalid_mission_id_should_not_work() {
         );
     });
 }
+
+#[test]
+fn era_missions_are_snapshotted() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorRegistry::register(Origin::signed(1), 10));
+        ValidatorRegistry::note_era_missions(0, &[1, 2], 84);
+        assert_eq!(ValidatorRegistry::eras_mission_of(0, 1), 10);
+        assert_eq!(ValidatorRegistry::eras_mission_of(0, 2), 0);
+
+        // Switching missions does not affect the snapshot of a past era.
+        assert_ok!(ValidatorRegistry::unregister(Origin::signed(1)));
+        assert_ok!(ValidatorRegistry::register(Origin::signed(1), 11));
+        assert_eq!(ValidatorRegistry::eras_mission_of(0, 1), 10);
+
+        ValidatorRegistry::note_era_missions(1, &[1], 84);
+        assert_eq!(ValidatorRegistry::eras_mission_of(1, 1), 11);
+
+        ValidatorRegistry::carry_era_missions(2, 84);
+        assert_eq!(ValidatorRegistry::eras_mission_of(2, 1), 11);
+    });
+}
+
+#[test]
+fn era_missions_are_pruned_after_history_depth() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorRegistry::register(Origin::signed(1), 10));
+        ValidatorRegistry::note_era_missions(0, &[1], 2);
+        ValidatorRegistry::note_era_missions(2, &[1], 2);
+        assert_eq!(ValidatorRegistry::eras_mission_of(0, 1), 10);
+        ValidatorRegistry::note_era_missions(3, &[1], 2);
+        assert_eq!(ValidatorRegistry::eras_mission_of(0, 1), 0);
+        assert_eq!(ValidatorRegistry::eras_mission_of(2, 1), 10);
+    });
+}
+
+#[test]
+fn nominator_missions_should_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorRegistry::set_nominator_missions(
+            Origin::signed(5),
+            vec![(10, 3), (11, 1)]
+        ));
+        assert_eq!(ValidatorRegistry::nominator_missions_of(5), vec![(10, 3), (11, 1)]);
+
+        ValidatorRegistry::note_era_nominator_missions(0, &[5, 6]);
+        assert_eq!(ValidatorRegistry::eras_nominator_missions_of(0, 5), vec![(10, 3), (11, 1)]);
+        assert!(ValidatorRegistry::eras_nominator_missions_of(0, 6).is_empty());
+
+        assert_ok!(ValidatorRegistry::clear_nominator_missions(Origin::signed(5)));
+        assert!(ValidatorRegistry::nominator_missions_of(5).is_empty());
+        assert_noop!(
+            ValidatorRegistry::clear_nominator_missions(Origin::signed(5)),
+            Error::<Test>::NotFound
+        );
+    });
+}
+
+#[test]
+fn invalid_nominator_missions_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ValidatorRegistry::set_nominator_missions(Origin::signed(5), vec![]),
+            Error::<Test>::EmptyAllocation
+        );
+        assert_noop!(
+            ValidatorRegistry::set_nominator_missions(Origin::signed(5), vec![(10, 0)]),
+            Error::<Test>::ZeroWeight
+        );
+        assert_noop!(
+            ValidatorRegistry::set_nominator_missions(Origin::signed(5), vec![(10, 1), (10, 2)]),
+            Error::<Test>::DuplicateMission
+        );
+        assert_noop!(
+            ValidatorRegistry::set_nominator_missions(
+                Origin::signed(5),
+                vec![(8, 1), (9, 1), (10, 1), (11, 1), (12, 1)]
+            ),
+            Error::<Test>::TooManyMissions
+        );
+    });
+}